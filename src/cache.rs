@@ -1,17 +1,23 @@
+mod crypto;
+mod lru;
 pub mod persistent;
 
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 use std::path::Path;
-use std::sync::{Arc, Mutex, MutexGuard};
-use std::time::{Duration, Instant};
+use std::sync::{mpsc, Arc, Mutex, MutexGuard};
+use std::time::{Duration, Instant, SystemTime};
 
-use crate::jira::IssueRef;
+use crate::jira::{parse_jira_timestamp, IssueRef};
 use crate::logging;
 use crate::metrics::Metrics;
+use lru::LruMap;
 use persistent::PersistentCache;
 
+pub use crypto::{CacheKey, KeyError, KeySource};
+pub use persistent::{CacheError, GcPolicy, GcReport, IssueAttributes, IssueTimestamps, SearchHit};
+
 /// Batch row for issue markdown cache upserts.
-pub type IssueCacheRow = (String, Vec<u8>, Option<String>);
+pub type IssueCacheRow = persistent::PersistentIssueRow;
 /// Batch row for issue comments sidecar upserts.
 pub type IssueSidecarRow = (String, Vec<u8>, Option<String>);
 
@@ -31,6 +37,19 @@ pub struct WorkspaceIssuesSnapshot {
     pub is_stale: bool,
 }
 
+#[derive(Debug, Clone, PartialEq, Eq)]
+/// Cache update that files or directory listings already served may reflect.
+pub enum CacheChange {
+    /// Issue markdown or sidecars were replaced.
+    Issues(Vec<String>),
+    /// Issues joined or left a workspace listing.
+    Listing {
+        workspace: String,
+        added: Vec<String>,
+        removed: Vec<String>,
+    },
+}
+
 #[derive(Debug, Clone)]
 struct CachedIssue {
     markdown: Vec<u8>,
@@ -42,9 +61,11 @@ pub struct InMemoryCache {
     workspace_ttl: Duration,
     issue_ttl: Duration,
     workspace_issues: Mutex<HashMap<String, CacheEntry<Vec<IssueRef>>>>,
-    issue_markdown: Mutex<HashMap<String, CacheEntry<CachedIssue>>>,
+    issue_markdown: Mutex<LruMap<CacheEntry<CachedIssue>>>,
     persistent: Option<PersistentCache>,
     metrics: Arc<Metrics>,
+    change_listeners: Mutex<Vec<mpsc::Sender<CacheChange>>>,
+    listing_changed_at: Mutex<HashMap<String, SystemTime>>,
 }
 
 impl InMemoryCache {
@@ -54,30 +75,123 @@ impl InMemoryCache {
             workspace_ttl,
             issue_ttl,
             workspace_issues: Mutex::new(HashMap::new()),
-            issue_markdown: Mutex::new(HashMap::new()),
+            issue_markdown: Mutex::new(LruMap::new(None)),
             persistent: None,
             metrics,
+            change_listeners: Mutex::new(Vec::new()),
+            listing_changed_at: Mutex::new(HashMap::new()),
         }
     }
 
     /// Creates an in-memory cache backed by SQLite persistence.
     ///
     /// # Errors
-    /// Returns [`rusqlite::Error`] when opening or initializing persistence fails.
+    /// Returns [`CacheError`] when opening or migrating persistence fails.
     pub fn with_persistence(
         workspace_ttl: Duration,
         issue_ttl: Duration,
         db_path: &Path,
         metrics: Arc<Metrics>,
-    ) -> Result<Self, rusqlite::Error> {
-        Ok(Self {
+    ) -> Result<Self, CacheError> {
+        Ok(Self::with_persistent_cache(
+            workspace_ttl,
+            issue_ttl,
+            PersistentCache::new(db_path)?,
+            metrics,
+        ))
+    }
+
+    /// Creates an in-memory cache backed by an already opened SQLite cache,
+    /// such as one opened with an encryption key.
+    #[must_use]
+    pub fn with_persistent_cache(
+        workspace_ttl: Duration,
+        issue_ttl: Duration,
+        persistent: PersistentCache,
+        metrics: Arc<Metrics>,
+    ) -> Self {
+        Self {
             workspace_ttl,
             issue_ttl,
             workspace_issues: Mutex::new(HashMap::new()),
-            issue_markdown: Mutex::new(HashMap::new()),
-            persistent: Some(PersistentCache::new(db_path)?),
+            issue_markdown: Mutex::new(LruMap::new(None)),
+            persistent: Some(persistent),
             metrics,
-        })
+            change_listeners: Mutex::new(Vec::new()),
+            listing_changed_at: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Caps issue markdown held in memory at `limit_bytes`, evicting the
+    /// least recently read issues first. Evicted issues are re-read from
+    /// persistence on the next access; without persistence they are dropped.
+    #[must_use]
+    pub fn with_memory_limit(self, limit_bytes: usize) -> Self {
+        let evicted = self
+            .issue_markdown
+            .lock_or_recover("issue_markdown")
+            .set_limit(Some(limit_bytes));
+        self.metrics.add_memory_evictions(evicted as u64);
+        self
+    }
+
+    /// Returns how many bytes of issue markdown are held in memory.
+    pub fn memory_bytes(&self) -> usize {
+        self.issue_markdown
+            .lock_or_recover("issue_markdown")
+            .bytes()
+    }
+
+    fn store_issue(&self, issue_key: String, entry: CacheEntry<CachedIssue>) {
+        let size = issue_key.len() + entry.value.markdown.len();
+        let evicted = self
+            .issue_markdown
+            .lock_or_recover("issue_markdown")
+            .insert(issue_key, entry, size);
+        self.metrics.add_memory_evictions(evicted as u64);
+    }
+
+    /// Returns a channel that receives every later content or listing change.
+    pub fn subscribe_changes(&self) -> mpsc::Receiver<CacheChange> {
+        let (tx, rx) = mpsc::channel();
+        self.change_listeners
+            .lock_or_recover("change_listeners")
+            .push(tx);
+        rx
+    }
+
+    fn publish(&self, change: CacheChange) {
+        self.change_listeners
+            .lock_or_recover("change_listeners")
+            .retain(|tx| tx.send(change.clone()).is_ok());
+    }
+
+    fn publish_listing_diff(&self, workspace: &str, before: &[IssueRef], after: &[IssueRef]) {
+        let before_keys: HashSet<&str> = before.iter().map(|item| item.key.as_str()).collect();
+        let after_keys: HashSet<&str> = after.iter().map(|item| item.key.as_str()).collect();
+        let added: Vec<String> = after
+            .iter()
+            .filter(|item| !before_keys.contains(item.key.as_str()))
+            .map(|item| item.key.clone())
+            .collect();
+        let removed: Vec<String> = before
+            .iter()
+            .filter(|item| !after_keys.contains(item.key.as_str()))
+            .map(|item| item.key.clone())
+            .collect();
+        if !added.is_empty() || !removed.is_empty() {
+            // The first listing after startup is a load, not a change.
+            if !before.is_empty() {
+                self.listing_changed_at
+                    .lock_or_recover("listing_changed_at")
+                    .insert(workspace.to_string(), SystemTime::now());
+            }
+            self.publish(CacheChange::Listing {
+                workspace: workspace.to_string(),
+                added,
+                removed,
+            });
+        }
     }
 
     /// Gets workspace issues from cache or via `fetch`, then caches fresh values.
@@ -146,9 +260,13 @@ impl InMemoryCache {
             ttl: self.workspace_ttl,
             source_updated: None,
         };
-        self.workspace_issues
+        let previous = self
+            .workspace_issues
             .lock_or_recover("workspace_issues")
-            .insert(workspace.to_string(), entry);
+            .insert(workspace.to_string(), entry)
+            .map(|entry| entry.value)
+            .unwrap_or_default();
+        self.publish_listing_diff(workspace, &previous, &persisted_issues);
 
         if let Some(persistent) = &self.persistent {
             let _ = persistent.upsert_workspace_issue_refs(workspace, &persisted_issues);
@@ -175,6 +293,7 @@ impl InMemoryCache {
         if let Some(entry) = &existing {
             if now.duration_since(entry.cached_at) < entry.ttl {
                 self.metrics.inc_cache_hit();
+                self.metrics.inc_memory_hit();
                 return Ok(entry.value.markdown.clone());
             }
         }
@@ -190,9 +309,7 @@ impl InMemoryCache {
                         ttl: self.issue_ttl,
                         source_updated: issue.updated,
                     };
-                    self.issue_markdown
-                        .lock_or_recover("issue_markdown")
-                        .insert(issue_key.to_string(), hydrated);
+                    self.store_issue(issue_key.to_string(), hydrated);
                     self.metrics.inc_cache_hit();
                     return Ok(issue.markdown);
                 }
@@ -221,9 +338,7 @@ impl InMemoryCache {
         {
             if entry.source_updated == fresh_updated {
                 entry.cached_at = now;
-                self.issue_markdown
-                    .lock_or_recover("issue_markdown")
-                    .insert(issue_key.to_string(), entry.clone());
+                self.store_issue(issue_key.to_string(), entry.clone());
                 return Ok(entry.value.markdown);
             }
         }
@@ -236,9 +351,7 @@ impl InMemoryCache {
             ttl: self.issue_ttl,
             source_updated: fresh_updated.clone(),
         };
-        self.issue_markdown
-            .lock_or_recover("issue_markdown")
-            .insert(issue_key.to_string(), entry);
+        self.store_issue(issue_key.to_string(), entry);
 
         if let Some(persistent) = &self.persistent {
             let _ = persistent.upsert_issue(issue_key, &fresh_markdown, fresh_updated.as_deref());
@@ -251,53 +364,143 @@ impl InMemoryCache {
     pub fn cached_issue_len(&self, issue_key: &str) -> Option<u64> {
         self.issue_markdown
             .lock_or_recover("issue_markdown")
-            .get(issue_key)
+            .peek(issue_key)
             .map(|entry| entry.value.markdown.len() as u64)
     }
 
+    /// Returns the persisted `updated` timestamp for one issue when persistence is enabled.
+    pub fn persisted_issue_updated(&self, issue_key: &str) -> Option<String> {
+        self.persistent
+            .as_ref()
+            .and_then(|p| p.issue_updated(issue_key).ok().flatten())
+    }
+
+    /// Returns Jira timestamps for an issue, preferring persisted values.
+    pub fn issue_timestamps(&self, issue_key: &str) -> Option<IssueTimestamps> {
+        if let Some(timestamps) = self
+            .persistent
+            .as_ref()
+            .and_then(|p| p.issue_timestamps(issue_key).ok().flatten())
+        {
+            return Some(timestamps);
+        }
+        self.issue_markdown
+            .lock_or_recover("issue_markdown")
+            .get(issue_key)
+            .map(|entry| IssueTimestamps {
+                updated: entry.source_updated.clone(),
+                ..IssueTimestamps::default()
+            })
+    }
+
+    /// Returns the structured fields persisted for an issue.
+    pub fn issue_attributes(&self, issue_key: &str) -> Option<IssueAttributes> {
+        self.persistent
+            .as_ref()
+            .and_then(|p| p.issue_attributes(issue_key).ok().flatten())
+    }
+
+    /// Runs persistent cache garbage collection and drops whatever it removed
+    /// from memory too. Returns `None` without persistence or on failure.
+    pub fn gc(&self, policy: &GcPolicy) -> Option<GcReport> {
+        let persistent = self.persistent.as_ref()?;
+        let report = match persistent.gc(policy) {
+            Ok(report) => report,
+            Err(err) => {
+                logging::warn(format!("cache gc failed: {}", err));
+                return None;
+            }
+        };
+        {
+            let mut guard = self.issue_markdown.lock_or_recover("issue_markdown");
+            for issue_key in report.expired.iter().chain(&report.evicted) {
+                guard.remove(issue_key);
+            }
+        }
+        if !report.evicted.is_empty() {
+            self.publish(CacheChange::Issues(report.evicted.clone()));
+        }
+        Some(report)
+    }
+
+    /// Full-text searches the persisted cache, best match first. Returns no
+    /// hits without persistence.
+    pub fn search_issues(&self, terms: &str, limit: usize) -> Vec<SearchHit> {
+        let Some(persistent) = self.persistent.as_ref() else {
+            return Vec::new();
+        };
+        persistent
+            .search_issues(terms, limit)
+            .unwrap_or_else(|err| {
+                logging::warn(format!("cache search failed for {:?}: {}", terms, err));
+                Vec::new()
+            })
+    }
+
+    /// Returns when a workspace listing last changed: the newest `updated`
+    /// among its issues, or a later membership change seen by this process.
+    pub fn workspace_modified(&self, workspace: &str) -> Option<SystemTime> {
+        let newest_issue = self
+            .workspace_issues
+            .lock_or_recover("workspace_issues")
+            .get(workspace)?
+            .value
+            .iter()
+            .filter_map(|item| item.updated.as_deref())
+            .filter_map(parse_jira_timestamp)
+            .max()
+            .map(SystemTime::from);
+        let membership = self
+            .listing_changed_at
+            .lock_or_recover("listing_changed_at")
+            .get(workspace)
+            .copied();
+        newest_issue.max(membership)
+    }
+
     /// Upserts one issue payload into memory and persistence.
     pub fn upsert_issue_direct(&self, issue_key: &str, markdown: &[u8], updated: Option<&str>) {
-        let now = Instant::now();
+        // Persist first so an immediate eviction still leaves a copy to read.
+        if let Some(persistent) = &self.persistent {
+            let _ = persistent.upsert_issue(issue_key, markdown, updated);
+        }
+
         let entry = CacheEntry {
             value: CachedIssue {
                 markdown: markdown.to_vec(),
             },
-            cached_at: now,
+            cached_at: Instant::now(),
             ttl: self.issue_ttl,
             source_updated: updated.map(ToString::to_string),
         };
-        self.issue_markdown
-            .lock_or_recover("issue_markdown")
-            .insert(issue_key.to_string(), entry);
-
-        if let Some(persistent) = &self.persistent {
-            let _ = persistent.upsert_issue(issue_key, markdown, updated);
-        }
+        self.store_issue(issue_key.to_string(), entry);
+        self.publish(CacheChange::Issues(vec![issue_key.to_string()]));
     }
 
     /// Upserts a batch of issue payloads into memory and persistence.
     pub fn upsert_issues_batch(&self, issues: &[IssueCacheRow]) -> usize {
+        if let Some(persistent) = &self.persistent {
+            let _ = persistent.upsert_issues_batch(issues);
+        }
+
         let now = Instant::now();
         let mut count = 0;
-
-        {
-            let mut guard = self.issue_markdown.lock_or_recover("issue_markdown");
-            for (issue_key, markdown, updated) in issues {
-                let entry = CacheEntry {
-                    value: CachedIssue {
-                        markdown: markdown.clone(),
-                    },
-                    cached_at: now,
-                    ttl: self.issue_ttl,
-                    source_updated: updated.clone(),
-                };
-                guard.insert(issue_key.clone(), entry);
-                count += 1;
-            }
+        for issue in issues {
+            let entry = CacheEntry {
+                value: CachedIssue {
+                    markdown: issue.markdown.clone(),
+                },
+                cached_at: now,
+                ttl: self.issue_ttl,
+                source_updated: issue.updated.clone(),
+            };
+            self.store_issue(issue.key.clone(), entry);
+            count += 1;
         }
-
-        if let Some(persistent) = &self.persistent {
-            let _ = persistent.upsert_issues_batch(issues);
+        if count > 0 {
+            self.publish(CacheChange::Issues(
+                issues.iter().map(|issue| issue.key.clone()).collect(),
+            ));
         }
 
         count
@@ -305,12 +508,18 @@ impl InMemoryCache {
 
     /// Upserts a batch of sidecar payloads into persistence.
     pub fn upsert_issue_sidecars_batch(&self, sidecars: &[IssueSidecarRow]) -> usize {
-        if let Some(persistent) = &self.persistent {
-            return persistent
-                .upsert_issue_sidecars_batch(sidecars)
-                .unwrap_or(0);
+        let Some(persistent) = &self.persistent else {
+            return 0;
+        };
+        let count = persistent
+            .upsert_issue_sidecars_batch(sidecars)
+            .unwrap_or(0);
+        if count > 0 {
+            self.publish(CacheChange::Issues(
+                sidecars.iter().map(|(key, _, _)| key.clone()).collect(),
+            ));
         }
-        0
+        count
     }
 
     /// Returns persisted sync cursor for a workspace when available.
@@ -334,6 +543,141 @@ impl InMemoryCache {
         }
     }
 
+    /// Returns when a workspace listing was last reconciled, in unix seconds.
+    pub fn last_reconciled(&self, workspace: &str) -> Option<u64> {
+        self.persistent
+            .as_ref()
+            .and_then(|p| p.get_last_reconciled(workspace).ok().flatten())
+    }
+
+    /// Records that a workspace listing was reconciled now.
+    pub fn mark_reconciled(&self, workspace: &str) {
+        if let Some(persistent) = &self.persistent {
+            let _ = persistent.mark_reconciled(workspace);
+        }
+    }
+
+    /// Drops persisted listings for workspaces not in `workspaces`.
+    pub fn retain_workspaces(&self, workspaces: &[String]) -> usize {
+        self.workspace_issues
+            .lock_or_recover("workspace_issues")
+            .retain(|name, _| workspaces.contains(name));
+        self.persistent
+            .as_ref()
+            .and_then(|p| p.retain_workspaces(workspaces).ok())
+            .unwrap_or(0)
+    }
+
+    /// Tombstones persisted issues that no workspace lists and evicts them from memory.
+    pub fn tombstone_orphaned_issues(&self) -> Vec<String> {
+        let Some(persistent) = &self.persistent else {
+            return Vec::new();
+        };
+        let tombstoned = match persistent.tombstone_orphaned_issues() {
+            Ok(keys) => keys,
+            Err(err) => {
+                logging::warn(format!("failed to tombstone orphaned issues: {}", err));
+                return Vec::new();
+            }
+        };
+
+        let mut guard = self.issue_markdown.lock_or_recover("issue_markdown");
+        for issue_key in &tombstoned {
+            guard.remove(issue_key);
+        }
+        tombstoned
+    }
+
+    /// Tombstones specific issues, removing them from listings and memory.
+    pub fn tombstone_issues(&self, issue_keys: &[String]) {
+        let mut changes = Vec::new();
+        {
+            let mut guard = self.workspace_issues.lock_or_recover("workspace_issues");
+            for (workspace, entry) in guard.iter_mut() {
+                let removed: Vec<String> = entry
+                    .value
+                    .iter()
+                    .filter(|item| issue_keys.contains(&item.key))
+                    .map(|item| item.key.clone())
+                    .collect();
+                if removed.is_empty() {
+                    continue;
+                }
+                entry.value.retain(|item| !issue_keys.contains(&item.key));
+                self.listing_changed_at
+                    .lock_or_recover("listing_changed_at")
+                    .insert(workspace.clone(), SystemTime::now());
+                changes.push(CacheChange::Listing {
+                    workspace: workspace.clone(),
+                    added: Vec::new(),
+                    removed,
+                });
+            }
+        }
+        for change in changes {
+            self.publish(change);
+        }
+        {
+            let mut guard = self.issue_markdown.lock_or_recover("issue_markdown");
+            for issue_key in issue_keys {
+                guard.remove(issue_key);
+            }
+        }
+        if let Some(persistent) = &self.persistent {
+            if let Err(err) = persistent.tombstone_issues(issue_keys) {
+                logging::warn(format!("failed to tombstone issues: {}", err));
+            }
+        }
+    }
+
+    /// Queues issue refs for a later backfill run.
+    pub fn enqueue_backfill(&self, workspace: &str, issue_refs: &[IssueRef]) -> usize {
+        self.persistent
+            .as_ref()
+            .and_then(|p| p.enqueue_backfill(workspace, issue_refs).ok())
+            .unwrap_or(0)
+    }
+
+    /// Returns up to `limit` queued backfill refs for a workspace.
+    pub fn next_backfill(&self, workspace: &str, limit: usize) -> Vec<IssueRef> {
+        self.persistent
+            .as_ref()
+            .and_then(|p| p.next_backfill(workspace, limit).ok())
+            .unwrap_or_default()
+    }
+
+    /// Removes issue keys from one workspace's backfill queue, or every queue for `None`.
+    pub fn remove_backfill(&self, workspace: Option<&str>, issue_keys: &[String]) -> usize {
+        self.persistent
+            .as_ref()
+            .and_then(|p| p.remove_backfill(workspace, issue_keys).ok())
+            .unwrap_or(0)
+    }
+
+    /// Returns the number of queued backfill entries.
+    pub fn backfill_remaining(&self) -> usize {
+        self.persistent
+            .as_ref()
+            .and_then(|p| p.backfill_count(None).ok())
+            .unwrap_or(0)
+    }
+
+    /// Returns how many issues are queued for backfill in one workspace.
+    pub fn workspace_backfill_remaining(&self, workspace: &str) -> usize {
+        self.persistent
+            .as_ref()
+            .and_then(|p| p.backfill_count(Some(workspace)).ok())
+            .unwrap_or(0)
+    }
+
+    /// Returns the total read count of cached issues listed in one workspace.
+    pub fn workspace_activity(&self, workspace: &str) -> u64 {
+        self.persistent
+            .as_ref()
+            .and_then(|p| p.workspace_access_count(workspace).ok())
+            .unwrap_or(0)
+    }
+
     /// Returns persisted issue count for a project prefix.
     pub fn cached_issue_count(&self, project_prefix: &str) -> usize {
         self.persistent
@@ -475,4 +819,77 @@ mod tests {
             .expect("loaded from cache");
         assert_eq!(got, b"persisted");
     }
+
+    #[test]
+    fn memory_limit_evicts_to_persistence() {
+        let metrics = metrics();
+        let cache = InMemoryCache::with_persistence(
+            Duration::from_secs(60),
+            Duration::from_secs(60),
+            Path::new(":memory:"),
+            Arc::clone(&metrics),
+        )
+        .expect("cache")
+        .with_memory_limit(32);
+
+        cache.upsert_issue_direct("PROJ-1", &[b'a'; 20], None);
+        cache.upsert_issue_direct("PROJ-2", &[b'b'; 20], None);
+        assert!(cache.cached_issue_len("PROJ-1").is_none());
+        assert_eq!(cache.cached_issue_len("PROJ-2"), Some(20));
+        assert!(cache.memory_bytes() <= 32);
+        assert_eq!(metrics.memory_snapshot(), (0, 1));
+
+        let evicted = cache
+            .get_issue_markdown_stale_safe("PROJ-1", || {
+                Err::<(Vec<u8>, Option<String>), _>("offline".to_string())
+            })
+            .expect("read back from persistence");
+        assert_eq!(evicted, vec![b'a'; 20]);
+        assert!(cache.cached_issue_len("PROJ-2").is_none());
+        assert_eq!(metrics.memory_snapshot(), (0, 2));
+
+        cache
+            .get_issue_markdown_stale_safe("PROJ-1", || {
+                Err::<(Vec<u8>, Option<String>), _>("offline".to_string())
+            })
+            .expect("memory hit");
+        assert_eq!(metrics.memory_snapshot(), (1, 2));
+    }
+
+    #[test]
+    fn publishes_content_and_listing_changes() {
+        let cache = InMemoryCache::new(Duration::from_secs(60), Duration::from_secs(60), metrics());
+        let changes = cache.subscribe_changes();
+        let issue_ref = |key: &str| IssueRef {
+            key: key.to_string(),
+            updated: None,
+        };
+
+        cache.upsert_workspace_issues("default", vec![issue_ref("PROJ-1"), issue_ref("PROJ-2")]);
+        cache.upsert_workspace_issues("default", vec![issue_ref("PROJ-1"), issue_ref("PROJ-2")]);
+        cache.upsert_issues_batch(&[IssueCacheRow {
+            key: "PROJ-1".to_string(),
+            markdown: b"v2".to_vec(),
+            ..IssueCacheRow::default()
+        }]);
+        cache.tombstone_issues(&["PROJ-2".to_string()]);
+
+        let received: Vec<CacheChange> = changes.try_iter().collect();
+        assert_eq!(
+            received,
+            vec![
+                CacheChange::Listing {
+                    workspace: "default".into(),
+                    added: vec!["PROJ-1".into(), "PROJ-2".into()],
+                    removed: Vec::new(),
+                },
+                CacheChange::Issues(vec!["PROJ-1".into()]),
+                CacheChange::Listing {
+                    workspace: "default".into(),
+                    added: Vec::new(),
+                    removed: vec!["PROJ-2".into()],
+                },
+            ]
+        );
+    }
 }
@@ -0,0 +1,237 @@
+use std::fmt;
+use std::path::PathBuf;
+use std::process::{Command, Stdio};
+
+use chacha20poly1305::aead::{Aead, AeadCore, KeyInit, OsRng, Payload};
+use chacha20poly1305::{XChaCha20Poly1305, XNonce};
+use zeroize::{Zeroize, Zeroizing};
+
+use crate::logging;
+
+/// Prefix of every encrypted blob, so the format can change later.
+const BLOB_MAGIC: &[u8; 4] = b"JFE1";
+const NONCE_LEN: usize = 24;
+const TAG_LEN: usize = 16;
+/// Bytes an encrypted blob adds to its plaintext.
+pub(crate) const BLOB_OVERHEAD: u64 = (BLOB_MAGIC.len() + NONCE_LEN + TAG_LEN) as u64;
+
+/// Where the cache encryption key is read from.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum KeySource {
+    /// Environment variable holding the hex key.
+    Env(String),
+    /// File whose contents are the hex key.
+    File(PathBuf),
+    /// Shell command that prints the hex key on stdout.
+    Command(String),
+}
+
+#[derive(Debug, thiserror::Error)]
+pub enum KeyError {
+    #[error("cache key environment variable {0} is not set")]
+    MissingEnv(String),
+    #[error("failed to read cache key file {path}: {source}")]
+    File {
+        path: String,
+        source: std::io::Error,
+    },
+    #[error("cache key command `{command}` failed: {message}")]
+    Command { command: String, message: String },
+    #[error("cache key must be 64 hex characters (32 bytes), e.g. from `openssl rand -hex 32`")]
+    Format,
+}
+
+/// 256-bit key for cached issue content, wiped from memory on drop.
+pub struct CacheKey([u8; 32]);
+
+impl CacheKey {
+    /// Reads and decodes the key from `source`.
+    ///
+    /// # Errors
+    /// Returns [`KeyError`] when the source is unavailable or does not hold a
+    /// 64-character hex key.
+    pub fn load(source: &KeySource) -> Result<Self, KeyError> {
+        let text = match source {
+            KeySource::Env(var) => {
+                Zeroizing::new(std::env::var(var).map_err(|_| KeyError::MissingEnv(var.clone()))?)
+            }
+            KeySource::File(path) => {
+                warn_if_shared(path);
+                Zeroizing::new(
+                    std::fs::read_to_string(path).map_err(|source| KeyError::File {
+                        path: path.display().to_string(),
+                        source,
+                    })?,
+                )
+            }
+            KeySource::Command(command) => Zeroizing::new(run_key_command(command)?),
+        };
+        Self::from_hex(&text)
+    }
+
+    /// Decodes a key from 64 hex characters, ignoring surrounding whitespace.
+    ///
+    /// # Errors
+    /// Returns [`KeyError::Format`] for anything else.
+    pub fn from_hex(text: &str) -> Result<Self, KeyError> {
+        let mut bytes = [0_u8; 32];
+        hex::decode_to_slice(text.trim(), &mut bytes).map_err(|_| KeyError::Format)?;
+        Ok(Self(bytes))
+    }
+}
+
+impl Drop for CacheKey {
+    fn drop(&mut self) {
+        self.0.zeroize();
+    }
+}
+
+impl fmt::Debug for CacheKey {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("CacheKey(..)")
+    }
+}
+
+#[derive(Debug, thiserror::Error)]
+pub(crate) enum BlobError {
+    #[error("cached blob is not encrypted or uses an unknown format")]
+    Format,
+    #[error("cached blob failed authentication; wrong key or corrupted data")]
+    Authentication,
+    #[error("blob too large to encrypt")]
+    TooLarge,
+}
+
+/// XChaCha20-Poly1305 sealing for cached blobs.
+///
+/// Blobs are `JFE1 || nonce || ciphertext || tag` with a random 192-bit nonce
+/// per write. Callers pass the row and column as associated data, so a blob
+/// copied onto another issue fails to open.
+pub(crate) struct BlobCipher {
+    aead: XChaCha20Poly1305,
+}
+
+impl BlobCipher {
+    pub(crate) fn new(key: &CacheKey) -> Self {
+        Self {
+            aead: XChaCha20Poly1305::new((&key.0).into()),
+        }
+    }
+
+    pub(crate) fn seal(&self, aad: &[u8], plaintext: &[u8]) -> Result<Vec<u8>, BlobError> {
+        let nonce = XChaCha20Poly1305::generate_nonce(&mut OsRng);
+        let sealed = self
+            .aead
+            .encrypt(
+                &nonce,
+                Payload {
+                    msg: plaintext,
+                    aad,
+                },
+            )
+            .map_err(|_| BlobError::TooLarge)?;
+        let mut blob = Vec::with_capacity(BLOB_MAGIC.len() + NONCE_LEN + sealed.len());
+        blob.extend_from_slice(BLOB_MAGIC);
+        blob.extend_from_slice(&nonce);
+        blob.extend_from_slice(&sealed);
+        Ok(blob)
+    }
+
+    pub(crate) fn open(&self, aad: &[u8], blob: &[u8]) -> Result<Vec<u8>, BlobError> {
+        let Some(rest) = blob.strip_prefix(BLOB_MAGIC.as_slice()) else {
+            return Err(BlobError::Format);
+        };
+        if rest.len() < NONCE_LEN + TAG_LEN {
+            return Err(BlobError::Format);
+        }
+        let (nonce, sealed) = rest.split_at(NONCE_LEN);
+        self.aead
+            .decrypt(XNonce::from_slice(nonce), Payload { msg: sealed, aad })
+            .map_err(|_| BlobError::Authentication)
+    }
+}
+
+impl fmt::Debug for BlobCipher {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("BlobCipher(..)")
+    }
+}
+
+fn run_key_command(command: &str) -> Result<String, KeyError> {
+    let failed = |message: String| KeyError::Command {
+        command: command.to_string(),
+        message,
+    };
+    let output = Command::new("sh")
+        .arg("-c")
+        .arg(command)
+        .stdin(Stdio::null())
+        .stderr(Stdio::inherit())
+        .output()
+        .map_err(|err| failed(err.to_string()))?;
+    if !output.status.success() {
+        return Err(failed(format!("exited with {}", output.status)));
+    }
+    String::from_utf8(output.stdout).map_err(|_| failed("printed non-UTF-8 output".into()))
+}
+
+/// Warns when a key file can be read by other users.
+fn warn_if_shared(path: &std::path::Path) {
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+
+        if let Ok(metadata) = std::fs::metadata(path) {
+            if metadata.permissions().mode() & 0o077 != 0 {
+                logging::warn(format!(
+                    "cache key file {} is accessible by other users; chmod 600 it",
+                    path.display()
+                ));
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn sealed_blobs_open_only_with_matching_key_and_context() {
+        let key = CacheKey::from_hex(&"11".repeat(32)).expect("key");
+        let cipher = BlobCipher::new(&key);
+        let blob = cipher.seal(b"PROJ-1/markdown", b"secret").expect("seal");
+        assert!(blob.starts_with(BLOB_MAGIC));
+        assert_eq!(blob.len() as u64, 6 + BLOB_OVERHEAD);
+        assert_eq!(
+            cipher.open(b"PROJ-1/markdown", &blob).expect("open"),
+            b"secret"
+        );
+        assert!(matches!(
+            cipher.open(b"PROJ-2/markdown", &blob),
+            Err(BlobError::Authentication)
+        ));
+
+        let other = BlobCipher::new(&CacheKey::from_hex(&"22".repeat(32)).expect("key"));
+        assert!(matches!(
+            other.open(b"PROJ-1/markdown", &blob),
+            Err(BlobError::Authentication)
+        ));
+        assert!(matches!(
+            cipher.open(b"PROJ-1/markdown", b"secret"),
+            Err(BlobError::Format)
+        ));
+        assert!(matches!(CacheKey::from_hex("abcd"), Err(KeyError::Format)));
+    }
+
+    #[test]
+    fn loads_keys_from_commands() {
+        let hex_key = "ab".repeat(32);
+        let key = CacheKey::load(&KeySource::Command(format!("echo {hex_key}"))).expect("key");
+        assert_eq!(key.0, [0xab; 32]);
+        assert!(matches!(
+            CacheKey::load(&KeySource::Command("exit 3".into())),
+            Err(KeyError::Command { .. })
+        ));
+    }
+}
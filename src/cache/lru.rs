@@ -0,0 +1,124 @@
+use std::collections::{BTreeMap, HashMap};
+
+/// Byte-bounded map that evicts least recently used entries first.
+///
+/// Recency is a monotonically increasing tick per access, so finding the
+/// oldest entry is a `BTreeMap` lookup instead of a scan.
+#[derive(Debug)]
+pub(crate) struct LruMap<V> {
+    entries: HashMap<String, LruSlot<V>>,
+    recency: BTreeMap<u64, String>,
+    tick: u64,
+    bytes: usize,
+    limit: Option<usize>,
+}
+
+#[derive(Debug)]
+struct LruSlot<V> {
+    value: V,
+    size: usize,
+    tick: u64,
+}
+
+impl<V> LruMap<V> {
+    pub(crate) fn new(limit: Option<usize>) -> Self {
+        Self {
+            entries: HashMap::new(),
+            recency: BTreeMap::new(),
+            tick: 0,
+            bytes: 0,
+            limit,
+        }
+    }
+
+    /// Changes the byte limit and returns how many entries it evicted.
+    pub(crate) fn set_limit(&mut self, limit: Option<usize>) -> usize {
+        self.limit = limit;
+        self.evict_over_limit()
+    }
+
+    /// Returns an entry and marks it most recently used.
+    pub(crate) fn get(&mut self, key: &str) -> Option<&V> {
+        let tick = self.next_tick();
+        let slot = self.entries.get_mut(key)?;
+        self.recency.remove(&slot.tick);
+        self.recency.insert(tick, key.to_string());
+        slot.tick = tick;
+        Some(&slot.value)
+    }
+
+    /// Returns an entry without changing its recency.
+    pub(crate) fn peek(&self, key: &str) -> Option<&V> {
+        self.entries.get(key).map(|slot| &slot.value)
+    }
+
+    /// Inserts or replaces an entry of `size` bytes, then evicts least
+    /// recently used entries until the map fits its limit again. Returns how
+    /// many entries were evicted; an entry larger than the whole limit is
+    /// evicted right away.
+    pub(crate) fn insert(&mut self, key: String, value: V, size: usize) -> usize {
+        self.remove(&key);
+        let tick = self.next_tick();
+        self.recency.insert(tick, key.clone());
+        self.bytes += size;
+        self.entries.insert(key, LruSlot { value, size, tick });
+        self.evict_over_limit()
+    }
+
+    pub(crate) fn remove(&mut self, key: &str) -> Option<V> {
+        let slot = self.entries.remove(key)?;
+        self.recency.remove(&slot.tick);
+        self.bytes -= slot.size;
+        Some(slot.value)
+    }
+
+    pub(crate) fn bytes(&self) -> usize {
+        self.bytes
+    }
+
+    fn evict_over_limit(&mut self) -> usize {
+        let Some(limit) = self.limit else {
+            return 0;
+        };
+        let mut evicted = 0;
+        while self.bytes > limit {
+            let Some((_, key)) = self.recency.pop_first() else {
+                break;
+            };
+            if let Some(slot) = self.entries.remove(&key) {
+                self.bytes -= slot.size;
+                evicted += 1;
+            }
+        }
+        evicted
+    }
+
+    fn next_tick(&mut self) -> u64 {
+        self.tick += 1;
+        self.tick
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn evicts_least_recently_used_entries_past_the_limit() {
+        let mut map = LruMap::new(Some(10));
+        assert_eq!(map.insert("a".into(), 1, 4), 0);
+        assert_eq!(map.insert("b".into(), 2, 4), 0);
+        assert_eq!(map.get("a"), Some(&1));
+
+        assert_eq!(map.insert("c".into(), 3, 4), 1);
+        assert!(map.peek("b").is_none());
+        assert_eq!(map.peek("a"), Some(&1));
+        assert_eq!(map.bytes(), 8);
+
+        assert_eq!(map.insert("a".into(), 10, 2), 0);
+        assert_eq!(map.bytes(), 6);
+        assert_eq!(map.insert("huge".into(), 0, 11), 3);
+        assert_eq!(map.bytes(), 0);
+        assert_eq!(map.set_limit(None), 0);
+    }
+}
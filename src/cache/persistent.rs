@@ -1,13 +1,38 @@
+use std::borrow::Cow;
+use std::collections::HashMap;
+use std::fs::{File, TryLockError};
+use std::ops::Deref;
 use std::path::Path;
-use std::sync::{Mutex, MutexGuard};
-use std::time::{SystemTime, UNIX_EPOCH};
+use std::sync::{Condvar, Mutex, MutexGuard};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
 
-use rusqlite::{params, Connection};
+use rusqlite::types::Type;
+use rusqlite::{params, Connection, OpenFlags, OptionalExtension};
+use serde::{Deserialize, Serialize};
 
+use super::crypto::{BlobCipher, CacheKey, BLOB_OVERHEAD};
 use crate::jira::IssueRef;
 use crate::logging;
 
-pub type PersistentIssueRow = (String, Vec<u8>, Option<String>);
+#[derive(Debug, Clone, Default)]
+/// Batch row for persisted issue markdown.
+pub struct PersistentIssueRow {
+    pub key: String,
+    pub markdown: Vec<u8>,
+    pub updated: Option<String>,
+    pub created: Option<String>,
+    pub attributes: Option<IssueAttributes>,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+/// Structured issue fields served as extended attributes.
+pub struct IssueAttributes {
+    pub status: Option<String>,
+    pub assignee: Option<String>,
+    pub assignee_id: Option<String>,
+    pub url: String,
+    pub labels: Vec<String>,
+}
 pub type PersistentSidecarRow = (String, Vec<u8>, Option<String>);
 
 #[derive(Debug, Clone)]
@@ -17,79 +42,426 @@ pub struct PersistentIssue {
     pub updated: Option<String>,
 }
 
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+/// Jira timestamps persisted for one issue and its comments sidecar.
+pub struct IssueTimestamps {
+    pub updated: Option<String>,
+    pub created: Option<String>,
+    pub comments_updated: Option<String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+/// One full-text search match, best first.
+pub struct SearchHit {
+    pub key: String,
+    pub snippet: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+/// Limits enforced by [`PersistentCache::gc`].
+pub struct GcPolicy {
+    /// Evict the least-read issues until live pages fit this size.
+    pub max_db_bytes: Option<u64>,
+    /// Delete issues tombstoned longer ago than this.
+    pub tombstone_max_age: Duration,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+/// What one garbage collection pass removed.
+pub struct GcReport {
+    /// Tombstoned issues past the maximum age, deleted for good.
+    pub expired: Vec<String>,
+    /// Listed issues evicted to meet the size limit. Their `updated` stamp is
+    /// kept so sync does not download them again until they change.
+    pub evicted: Vec<String>,
+    pub bytes_before: u64,
+    pub bytes_after: u64,
+}
+
+#[derive(Debug, thiserror::Error)]
+/// Errors returned when opening the persistent cache.
+pub enum CacheError {
+    #[error("persistent cache error: {0}")]
+    Sqlite(#[from] rusqlite::Error),
+    #[error(
+        "cache database {path} has schema version {found}, but this jirafs only knows up to \
+         {supported}; upgrade jirafs or point cache.db_path at a fresh file"
+    )]
+    SchemaTooNew {
+        path: String,
+        found: u32,
+        supported: u32,
+    },
+    #[error("cache database {path} is encrypted; configure [cache.encryption] with its key")]
+    KeyRequired { path: String },
+    #[error("cache key does not match the key cache database {path} was encrypted with")]
+    WrongKey { path: String },
+    #[error("cache database {path} is in use by another jirafs process; stop it and retry")]
+    InUse { path: String },
+    #[error("failed to lock cache database {path}: {source}")]
+    Lock {
+        path: String,
+        source: std::io::Error,
+    },
+}
+
+impl CacheError {
+    /// Names the database file in errors raised before it was known.
+    fn at(self, path: &Path) -> Self {
+        let path = path.display().to_string();
+        match self {
+            Self::SchemaTooNew {
+                found, supported, ..
+            } => Self::SchemaTooNew {
+                path,
+                found,
+                supported,
+            },
+            Self::KeyRequired { .. } => Self::KeyRequired { path },
+            Self::WrongKey { .. } => Self::WrongKey { path },
+            other @ (Self::Sqlite(_) | Self::InUse { .. } | Self::Lock { .. }) => other,
+        }
+    }
+}
+
+/// Encrypted under the cache key and stored as `cache_meta.key_check`, so a
+/// missing or wrong key is caught at open rather than on the first read.
+const KEY_CHECK_PLAINTEXT: &[u8] = b"jirafs cache key check";
+const KEY_CHECK_AAD: &[u8] = b"cache_meta/key_check";
+
+/// Schema version written by this build, stored in `PRAGMA user_version`.
+pub const SCHEMA_VERSION: u32 = MIGRATIONS.len() as u32;
+
+type Migration = fn(&Connection) -> Result<(), rusqlite::Error>;
+
+/// Ordered schema migrations; entry `n` upgrades version `n` to `n + 1`.
+///
+/// Databases created before versioning report version 0 but may already hold
+/// any of these tables, so every step must tolerate objects that exist.
+/// Append new steps; never edit or reorder shipped ones.
+const MIGRATIONS: &[Migration] = &[
+    migrate_base_tables,
+    migrate_issue_created,
+    migrate_issue_attributes,
+    create_search_index,
+    migrate_issue_evictions,
+    migrate_cache_meta,
+    migrate_issue_assignee_id,
+    migrate_sealed_issue_attributes,
+];
+
 #[derive(Debug)]
 /// SQLite-backed cache for issue content and sync metadata.
 pub struct PersistentCache {
-    conn: Mutex<Connection>,
+    writer: Mutex<Connection>,
+    /// Read-only connections; `None` for `:memory:` databases, which a second
+    /// connection cannot see, so reads share the writer.
+    readers: Option<ReaderPool>,
+    /// Reads per issue not yet added to `access_count`.
+    pending_reads: Mutex<HashMap<String, i64>>,
+    /// Seals issue and comment markdown when the cache is encrypted.
+    cipher: Option<BlobCipher>,
+    /// Shared lock on `<db_path>.lock` held while the cache is open, so
+    /// [`PersistentCache::rekey`] can tell when another process uses it.
+    /// `None` for `:memory:` databases.
+    lock: Option<File>,
+    path: String,
+}
+
+/// Read-only connections to put in [`PersistentCache`]'s pool.
+const READER_POOL_SIZE: usize = 4;
+/// How long a connection waits on another process's lock before failing.
+const BUSY_TIMEOUT: Duration = Duration::from_secs(5);
+/// Pending reads that trigger an opportunistic `access_count` flush.
+const ACCESS_FLUSH_THRESHOLD: usize = 64;
+
+#[derive(Debug)]
+/// Idle read-only connections, handed out one per read.
+struct ReaderPool {
+    idle: Mutex<Vec<Connection>>,
+    returned: Condvar,
+}
+
+/// Connection borrowed for one read, either from the pool or the writer.
+enum ReadConn<'a> {
+    Pooled {
+        pool: &'a ReaderPool,
+        conn: Option<Connection>,
+    },
+    Writer(MutexGuard<'a, Connection>),
+}
+
+impl ReaderPool {
+    fn open(path: &Path, size: usize) -> Result<Self, rusqlite::Error> {
+        let flags = OpenFlags::SQLITE_OPEN_READ_ONLY
+            | OpenFlags::SQLITE_OPEN_NO_MUTEX
+            | OpenFlags::SQLITE_OPEN_URI;
+        let idle = (0..size)
+            .map(|_| {
+                let conn = Connection::open_with_flags(path, flags)?;
+                conn.busy_timeout(BUSY_TIMEOUT)?;
+                Ok(conn)
+            })
+            .collect::<Result<Vec<_>, rusqlite::Error>>()?;
+        Ok(Self {
+            idle: Mutex::new(idle),
+            returned: Condvar::new(),
+        })
+    }
+
+    fn checkout(&self) -> ReadConn<'_> {
+        let mut idle = lock_or_recover(&self.idle, "persistent cache reader pool");
+        loop {
+            if let Some(conn) = idle.pop() {
+                return ReadConn::Pooled {
+                    pool: self,
+                    conn: Some(conn),
+                };
+            }
+            idle = match self.returned.wait(idle) {
+                Ok(guard) => guard,
+                Err(poisoned) => {
+                    logging::warn("recovering poisoned mutex after condvar wait: reader pool");
+                    poisoned.into_inner()
+                }
+            };
+        }
+    }
+}
+
+impl Deref for ReadConn<'_> {
+    type Target = Connection;
+
+    fn deref(&self) -> &Connection {
+        match self {
+            Self::Pooled { conn, .. } => conn.as_ref().expect("pooled connection present"),
+            Self::Writer(guard) => guard,
+        }
+    }
+}
+
+impl Drop for ReadConn<'_> {
+    fn drop(&mut self) {
+        if let Self::Pooled { pool, conn } = self {
+            if let Some(conn) = conn.take() {
+                lock_or_recover(&pool.idle, "persistent cache reader pool").push(conn);
+                pool.returned.notify_one();
+            }
+        }
+    }
+}
+
+impl Drop for PersistentCache {
+    fn drop(&mut self) {
+        let mut conn = lock_or_recover(&self.writer, "persistent cache writer");
+        if let Err(err) = self.flush_access_counts(&mut conn) {
+            logging::warn(format!("failed to flush issue access counts: {err}"));
+        }
+    }
 }
 
 impl PersistentCache {
-    /// Opens or creates the persistent cache database.
+    /// Opens or creates an unencrypted persistent cache database and migrates
+    /// its schema to [`SCHEMA_VERSION`].
     ///
     /// # Errors
-    /// Returns [`rusqlite::Error`] when opening or initializing SQLite fails.
-    pub fn new(path: &Path) -> Result<Self, rusqlite::Error> {
-        let conn = Connection::open(path)?;
-        conn.execute_batch(
-            "
-CREATE TABLE IF NOT EXISTS issues (
-  issue_key TEXT PRIMARY KEY,
-  markdown BLOB NOT NULL,
-  updated TEXT,
-  cached_at TEXT NOT NULL,
-  access_count INTEGER NOT NULL DEFAULT 0
-);
-
-CREATE TABLE IF NOT EXISTS sync_cursor (
-  workspace TEXT PRIMARY KEY,
-  last_sync TEXT NOT NULL
-);
-
-CREATE TABLE IF NOT EXISTS workspace_issues (
-  workspace TEXT NOT NULL,
-  issue_key TEXT NOT NULL,
-  updated TEXT,
-  PRIMARY KEY(workspace, issue_key)
-);
+    /// Returns [`CacheError::SchemaTooNew`] when the database was written by a
+    /// newer jirafs, [`CacheError::KeyRequired`] when it is encrypted, and
+    /// [`CacheError::Sqlite`] when opening or migrating fails.
+    pub fn new(path: &Path) -> Result<Self, CacheError> {
+        Self::open(path, None)
+    }
 
-CREATE INDEX IF NOT EXISTS idx_workspace_issues_issue_key ON workspace_issues(issue_key);
+    /// Opens or creates the persistent cache database, encrypting issue and
+    /// comment markdown with `key` when one is given.
+    ///
+    /// An unencrypted database opened with a key is encrypted in place, and
+    /// its full-text index is dropped since it would hold plaintext.
+    ///
+    /// # Errors
+    /// Returns [`CacheError::WrongKey`] when `key` does not match the one the
+    /// database was encrypted with, plus the errors of [`PersistentCache::new`].
+    pub fn open(path: &Path, key: Option<&CacheKey>) -> Result<Self, CacheError> {
+        let lock = lock_shared(path)?;
+        let mut conn = Connection::open(path)?;
+        conn.busy_timeout(BUSY_TIMEOUT)?;
+        // Only takes effect on a new database; `gc` converts older ones.
+        conn.execute_batch("PRAGMA auto_vacuum = INCREMENTAL;")?;
+        // WAL lets the reader pool run alongside the writer. It persists in
+        // the file, so older databases switch on their first open here.
+        let journal_mode: String =
+            conn.query_row("PRAGMA journal_mode = WAL", [], |row| row.get(0))?;
+        conn.execute_batch("PRAGMA synchronous = NORMAL;")?;
+        migrate(&mut conn, SCHEMA_VERSION).map_err(|err| err.at(path))?;
+        let cipher = key.map(BlobCipher::new);
+        check_encryption(&mut conn, cipher.as_ref()).map_err(|err| err.at(path))?;
 
-CREATE TABLE IF NOT EXISTS issue_sidecars (
-  issue_key TEXT PRIMARY KEY,
-  comments_md BLOB NOT NULL,
-  updated TEXT,
-  cached_at TEXT NOT NULL
-);
- ",
-        )?;
+        let readers = if journal_mode.eq_ignore_ascii_case("wal") {
+            Some(ReaderPool::open(path, READER_POOL_SIZE)?)
+        } else {
+            None
+        };
 
         Ok(Self {
-            conn: Mutex::new(conn),
+            writer: Mutex::new(conn),
+            readers,
+            pending_reads: Mutex::new(HashMap::new()),
+            cipher,
+            lock,
+            path: path.display().to_string(),
         })
     }
 
-    /// Loads one persisted issue and increments its access counter.
+    /// Whether issue and comment markdown are stored encrypted.
+    #[must_use]
+    pub fn is_encrypted(&self) -> bool {
+        self.cipher.is_some()
+    }
+
+    /// Re-encrypts every stored blob under `key` and makes it the cache key.
+    /// Encrypts an unencrypted cache. Returns how many issue, comment and
+    /// attribute blobs were rewritten.
+    ///
+    /// A process that still has the old key open would write blobs the new
+    /// key cannot read, so this refuses to run while any other
+    /// [`PersistentCache`] holds the same database.
     ///
     /// # Errors
-    /// Returns [`rusqlite::Error`] when query or update execution fails.
-    pub fn get_issue(&self, issue_key: &str) -> Result<Option<PersistentIssue>, rusqlite::Error> {
-        let conn = lock_conn_or_recover(&self.conn);
-        let mut stmt = conn.prepare("SELECT markdown, updated FROM issues WHERE issue_key = ?1")?;
-        let mut rows = stmt.query(params![issue_key])?;
+    /// Returns [`CacheError::InUse`] while another process has the cache
+    /// open, [`CacheError::WrongKey`] when a blob does not open with the
+    /// current key, and [`CacheError::Sqlite`] when SQL execution fails.
+    pub fn rekey(&mut self, key: &CacheKey) -> Result<usize, CacheError> {
+        let _exclusive = lock_exclusive(self.lock.as_ref(), &self.path)?;
+        let cipher = BlobCipher::new(key);
+        let conn = self
+            .writer
+            .get_mut()
+            .unwrap_or_else(std::sync::PoisonError::into_inner);
+        let rewritten = reencrypt(conn, self.cipher.as_ref(), &cipher)?;
+        self.cipher = Some(cipher);
+        Ok(rewritten)
+    }
 
-        if let Some(row) = rows.next()? {
-            conn.execute(
-                "UPDATE issues SET access_count = access_count + 1 WHERE issue_key = ?1",
-                params![issue_key],
-            )?;
+    /// Encrypts `bytes` for `column` of `issue_key` when the cache is
+    /// encrypted.
+    fn seal<'a>(
+        &self,
+        issue_key: &str,
+        column: &str,
+        bytes: &'a [u8],
+    ) -> Result<Cow<'a, [u8]>, rusqlite::Error> {
+        match &self.cipher {
+            Some(cipher) => cipher
+                .seal(&blob_aad(issue_key, column), bytes)
+                .map(Cow::Owned)
+                .map_err(|err| rusqlite::Error::ToSqlConversionFailure(Box::new(err))),
+            None => Ok(Cow::Borrowed(bytes)),
+        }
+    }
 
-            return Ok(Some(PersistentIssue {
-                markdown: row.get(0)?,
-                updated: row.get(1)?,
-            }));
+    /// Reverses [`PersistentCache::seal`] for a blob read back from SQLite.
+    fn unseal(
+        &self,
+        issue_key: &str,
+        column: &str,
+        blob: Vec<u8>,
+    ) -> Result<Vec<u8>, rusqlite::Error> {
+        match &self.cipher {
+            Some(cipher) => cipher
+                .open(&blob_aad(issue_key, column), &blob)
+                .map_err(|err| {
+                    rusqlite::Error::FromSqlConversionFailure(0, Type::Blob, Box::new(err))
+                }),
+            None => Ok(blob),
         }
+    }
 
-        Ok(None)
+    /// Plaintext length of a stored blob of `len` bytes.
+    fn plaintext_len(&self, len: i64) -> u64 {
+        let len = len.max(0) as u64;
+        if self.cipher.is_some() {
+            len.saturating_sub(BLOB_OVERHEAD)
+        } else {
+            len
+        }
+    }
+
+    /// Borrows a connection for a read, waiting for a pooled one if all are
+    /// busy rather than queueing behind the writer.
+    fn reader(&self) -> ReadConn<'_> {
+        match &self.readers {
+            Some(pool) => pool.checkout(),
+            None => ReadConn::Writer(lock_or_recover(&self.writer, "persistent cache writer")),
+        }
+    }
+
+    fn writer(&self) -> MutexGuard<'_, Connection> {
+        lock_or_recover(&self.writer, "persistent cache writer")
+    }
+
+    /// Counts one read of `issue_key`. Counts are written in batches: once
+    /// enough are pending and the writer is free, or before the next write
+    /// that depends on them.
+    fn record_read(&self, issue_key: &str) {
+        let pending = {
+            let mut reads = lock_or_recover(&self.pending_reads, "persistent cache pending reads");
+            *reads.entry(issue_key.to_string()).or_insert(0) += 1;
+            reads.len()
+        };
+        if pending < ACCESS_FLUSH_THRESHOLD {
+            return;
+        }
+        if let Ok(mut conn) = self.writer.try_lock() {
+            if let Err(err) = self.flush_access_counts(&mut conn) {
+                logging::warn(format!("failed to flush issue access counts: {err}"));
+            }
+        }
+    }
+
+    /// Adds pending reads to `access_count` in one transaction.
+    fn flush_access_counts(&self, conn: &mut Connection) -> Result<(), rusqlite::Error> {
+        let reads = std::mem::take(&mut *lock_or_recover(
+            &self.pending_reads,
+            "persistent cache pending reads",
+        ));
+        if reads.is_empty() {
+            return Ok(());
+        }
+        let tx = conn.transaction()?;
+        {
+            let mut stmt = tx.prepare(
+                "UPDATE issues SET access_count = access_count + ?2 WHERE issue_key = ?1",
+            )?;
+            for (issue_key, count) in &reads {
+                stmt.execute(params![issue_key, count])?;
+            }
+        }
+        tx.commit()
+    }
+
+    /// Loads one persisted issue and counts the read towards its access
+    /// counter.
+    ///
+    /// # Errors
+    /// Returns [`rusqlite::Error`] when query execution fails.
+    pub fn get_issue(&self, issue_key: &str) -> Result<Option<PersistentIssue>, rusqlite::Error> {
+        let issue = {
+            let conn = self.reader();
+            let mut stmt =
+                conn.prepare("SELECT markdown, updated FROM issues WHERE issue_key = ?1")?;
+            let mut rows = stmt.query(params![issue_key])?;
+            match rows.next()? {
+                Some(row) => Some(PersistentIssue {
+                    markdown: self.unseal(issue_key, "markdown", row.get(0)?)?,
+                    updated: row.get(1)?,
+                }),
+                None => None,
+            }
+        };
+        if issue.is_some() {
+            self.record_read(issue_key);
+        }
+        Ok(issue)
     }
 
     /// Upserts one issue markdown payload.
@@ -103,16 +475,16 @@ CREATE TABLE IF NOT EXISTS issue_sidecars (
         updated: Option<&str>,
     ) -> Result<(), rusqlite::Error> {
         let now = unix_epoch_seconds_string();
-        let conn = lock_conn_or_recover(&self.conn);
+        let markdown = self.seal(issue_key, "markdown", markdown)?;
+        let conn = self.writer();
         conn.execute(
             "
-INSERT INTO issues(issue_key, markdown, updated, cached_at, access_count)
-VALUES (?1, ?2, ?3, ?4, 1)
+INSERT INTO issues(issue_key, markdown, updated, cached_at)
+VALUES (?1, ?2, ?3, ?4)
 ON CONFLICT(issue_key) DO UPDATE SET
   markdown = excluded.markdown,
   updated = excluded.updated,
-  cached_at = excluded.cached_at,
-  access_count = issues.access_count + 1
+  cached_at = excluded.cached_at
 ",
             params![issue_key, markdown, updated, now],
         )?;
@@ -128,23 +500,57 @@ ON CONFLICT(issue_key) DO UPDATE SET
         issues: &[PersistentIssueRow],
     ) -> Result<usize, rusqlite::Error> {
         let now = unix_epoch_seconds_string();
-        let mut conn = lock_conn_or_recover(&self.conn);
+        let mut conn = self.writer();
+        self.flush_access_counts(&mut conn)?;
         let tx = conn.transaction()?;
 
         let mut count = 0;
-        for (issue_key, markdown, updated) in issues {
+        for issue in issues {
+            let markdown = self.seal(&issue.key, "markdown", &issue.markdown)?;
             tx.execute(
                 "
-INSERT INTO issues(issue_key, markdown, updated, cached_at, access_count)
-VALUES (?1, ?2, ?3, ?4, 1)
+INSERT INTO issues(issue_key, markdown, updated, created, cached_at)
+VALUES (?1, ?2, ?3, ?4, ?5)
 ON CONFLICT(issue_key) DO UPDATE SET
   markdown = excluded.markdown,
   updated = excluded.updated,
-  cached_at = excluded.cached_at,
-  access_count = issues.access_count + 1
+  created = COALESCE(excluded.created, issues.created),
+  cached_at = excluded.cached_at
 ",
-                params![issue_key, markdown, updated, now],
+                params![issue.key, markdown, issue.updated, issue.created, now],
             )?;
+            if let Some(attributes) = &issue.attributes {
+                match &self.cipher {
+                    Some(cipher) => {
+                        let sealed = seal_attributes(cipher, &issue.key, attributes)?;
+                        write_sealed_attributes(&tx, &issue.key, &sealed)?;
+                    }
+                    None => {
+                        let labels = serde_json::to_string(&attributes.labels).unwrap_or_default();
+                        tx.execute(
+                            "
+INSERT INTO issue_attributes(issue_key, status, assignee, assignee_id, url, labels, sealed)
+VALUES (?1, ?2, ?3, ?4, ?5, ?6, NULL)
+ON CONFLICT(issue_key) DO UPDATE SET
+  status = excluded.status,
+  assignee = excluded.assignee,
+  assignee_id = excluded.assignee_id,
+  url = excluded.url,
+  labels = excluded.labels,
+  sealed = NULL
+",
+                            params![
+                                issue.key,
+                                attributes.status,
+                                attributes.assignee,
+                                attributes.assignee_id,
+                                attributes.url,
+                                labels
+                            ],
+                        )?;
+                    }
+                }
+            }
             count += 1;
         }
 
@@ -157,7 +563,7 @@ ON CONFLICT(issue_key) DO UPDATE SET
     /// # Errors
     /// Returns [`rusqlite::Error`] when SQL execution fails.
     pub fn get_sync_cursor(&self, workspace: &str) -> Result<Option<String>, rusqlite::Error> {
-        let conn = lock_conn_or_recover(&self.conn);
+        let conn = self.reader();
         let mut stmt = conn.prepare("SELECT last_sync FROM sync_cursor WHERE workspace = ?1")?;
         let mut rows = stmt.query(params![workspace])?;
 
@@ -173,7 +579,7 @@ ON CONFLICT(issue_key) DO UPDATE SET
     /// # Errors
     /// Returns [`rusqlite::Error`] when SQL execution fails.
     pub fn set_sync_cursor(&self, workspace: &str, last_sync: &str) -> Result<(), rusqlite::Error> {
-        let conn = lock_conn_or_recover(&self.conn);
+        let conn = self.writer();
         conn.execute(
             "
 INSERT INTO sync_cursor(workspace, last_sync)
@@ -191,7 +597,7 @@ ON CONFLICT(workspace) DO UPDATE SET
     /// # Errors
     /// Returns [`rusqlite::Error`] when SQL execution fails.
     pub fn clear_sync_cursor(&self, workspace: &str) -> Result<(), rusqlite::Error> {
-        let conn = lock_conn_or_recover(&self.conn);
+        let conn = self.writer();
         conn.execute(
             "DELETE FROM sync_cursor WHERE workspace = ?1",
             params![workspace],
@@ -204,7 +610,7 @@ ON CONFLICT(workspace) DO UPDATE SET
     /// # Errors
     /// Returns [`rusqlite::Error`] when SQL execution fails.
     pub fn cached_issue_count(&self, project_prefix: &str) -> Result<usize, rusqlite::Error> {
-        let conn = lock_conn_or_recover(&self.conn);
+        let conn = self.reader();
         let pattern = format!("{}-%", project_prefix);
         let count: usize = conn.query_row(
             "SELECT COUNT(*) FROM issues WHERE issue_key LIKE ?1",
@@ -219,13 +625,95 @@ ON CONFLICT(workspace) DO UPDATE SET
     /// # Errors
     /// Returns [`rusqlite::Error`] when SQL execution fails.
     pub fn issue_markdown_len(&self, issue_key: &str) -> Result<Option<u64>, rusqlite::Error> {
-        let conn = lock_conn_or_recover(&self.conn);
+        let conn = self.reader();
         let mut stmt = conn.prepare("SELECT length(markdown) FROM issues WHERE issue_key = ?1")?;
         let mut rows = stmt.query(params![issue_key])?;
 
         if let Some(row) = rows.next()? {
-            let len: i64 = row.get(0)?;
-            return Ok(Some(len.max(0) as u64));
+            return Ok(Some(self.plaintext_len(row.get(0)?)));
+        }
+
+        Ok(None)
+    }
+
+    /// Returns the persisted `updated` timestamp for one issue without counting an access.
+    ///
+    /// Issues evicted by [`PersistentCache::gc`] still report the `updated`
+    /// they had when evicted.
+    ///
+    /// # Errors
+    /// Returns [`rusqlite::Error`] when query execution fails.
+    pub fn issue_updated(&self, issue_key: &str) -> Result<Option<String>, rusqlite::Error> {
+        let conn = self.reader();
+        let mut stmt = conn.prepare(
+            "
+SELECT updated FROM issues WHERE issue_key = ?1
+UNION ALL
+SELECT updated FROM issue_evictions WHERE issue_key = ?1
+LIMIT 1
+",
+        )?;
+        let mut rows = stmt.query(params![issue_key])?;
+
+        if let Some(row) = rows.next()? {
+            return row.get(0);
+        }
+
+        Ok(None)
+    }
+
+    /// Reads the issue and comments sidecar timestamps used for file attributes.
+    ///
+    /// # Errors
+    /// Returns [`rusqlite::Error`] when SQL execution fails.
+    pub fn issue_timestamps(
+        &self,
+        issue_key: &str,
+    ) -> Result<Option<IssueTimestamps>, rusqlite::Error> {
+        let conn = self.reader();
+        let mut stmt = conn.prepare(
+            "
+SELECT issues.updated, issues.created, issue_sidecars.updated
+FROM issues
+LEFT JOIN issue_sidecars ON issue_sidecars.issue_key = issues.issue_key
+WHERE issues.issue_key = ?1
+",
+        )?;
+        let mut rows = stmt.query(params![issue_key])?;
+
+        if let Some(row) = rows.next()? {
+            return Ok(Some(IssueTimestamps {
+                updated: row.get(0)?,
+                created: row.get(1)?,
+                comments_updated: row.get(2)?,
+            }));
+        }
+
+        Ok(None)
+    }
+
+    /// Reads the structured fields stored for one issue.
+    ///
+    /// # Errors
+    /// Returns [`rusqlite::Error`] when SQL execution fails.
+    pub fn issue_attributes(
+        &self,
+        issue_key: &str,
+    ) -> Result<Option<IssueAttributes>, rusqlite::Error> {
+        let conn = self.reader();
+        let mut stmt = conn.prepare(
+            "SELECT status, assignee, assignee_id, url, labels, sealed FROM issue_attributes WHERE issue_key = ?1",
+        )?;
+        let mut rows = stmt.query(params![issue_key])?;
+
+        if let Some(row) = rows.next()? {
+            if let Some(sealed) = row.get::<_, Option<Vec<u8>>>(5)? {
+                let json = self.unseal(issue_key, "attributes", sealed)?;
+                return serde_json::from_slice(&json).map(Some).map_err(|err| {
+                    rusqlite::Error::FromSqlConversionFailure(5, Type::Blob, Box::new(err))
+                });
+            }
+            return plaintext_attributes(row, 0).map(Some);
         }
 
         Ok(None)
@@ -240,7 +728,7 @@ ON CONFLICT(workspace) DO UPDATE SET
         workspace: &str,
         issue_refs: &[IssueRef],
     ) -> Result<(), rusqlite::Error> {
-        let mut conn = lock_conn_or_recover(&self.conn);
+        let mut conn = self.writer();
         let tx = conn.transaction()?;
         tx.execute(
             "DELETE FROM workspace_issues WHERE workspace = ?1",
@@ -264,7 +752,7 @@ ON CONFLICT(workspace) DO UPDATE SET
         &self,
         workspace: &str,
     ) -> Result<Vec<IssueRef>, rusqlite::Error> {
-        let conn = lock_conn_or_recover(&self.conn);
+        let conn = self.reader();
         let mut stmt = conn.prepare(
             "SELECT issue_key, updated FROM workspace_issues WHERE workspace = ?1 ORDER BY issue_key ASC",
         )?;
@@ -281,80 +769,444 @@ ON CONFLICT(workspace) DO UPDATE SET
         Ok(out)
     }
 
-    /// Upserts markdown comment sidecar for one issue.
+    /// Removes listings for workspaces that are no longer configured.
+    ///
+    /// # Errors
+    /// Returns [`rusqlite::Error`] when transaction or SQL execution fails.
+    pub fn retain_workspaces(&self, workspaces: &[String]) -> Result<usize, rusqlite::Error> {
+        let mut conn = self.writer();
+        let tx = conn.transaction()?;
+        let stale: Vec<String> = {
+            let mut stmt = tx.prepare("SELECT DISTINCT workspace FROM workspace_issues")?;
+            let names = stmt.query_map([], |row| row.get::<_, String>(0))?;
+            names
+                .collect::<Result<Vec<_>, _>>()?
+                .into_iter()
+                .filter(|name| !workspaces.contains(name))
+                .collect()
+        };
+        for workspace in &stale {
+            tx.execute(
+                "DELETE FROM workspace_issues WHERE workspace = ?1",
+                params![workspace],
+            )?;
+            tx.execute(
+                "DELETE FROM workspace_reconcile WHERE workspace = ?1",
+                params![workspace],
+            )?;
+            tx.execute(
+                "DELETE FROM sync_backfill WHERE workspace = ?1",
+                params![workspace],
+            )?;
+        }
+        tx.commit()?;
+        Ok(stale.len())
+    }
+
+    /// Reads when a workspace listing was last reconciled, in unix seconds.
     ///
     /// # Errors
     /// Returns [`rusqlite::Error`] when SQL execution fails.
-    pub fn upsert_issue_sidecars(
-        &self,
-        issue_key: &str,
-        comments_md: &[u8],
-        updated: Option<&str>,
-    ) -> Result<(), rusqlite::Error> {
+    pub fn get_last_reconciled(&self, workspace: &str) -> Result<Option<u64>, rusqlite::Error> {
+        let conn = self.reader();
+        let mut stmt =
+            conn.prepare("SELECT last_reconciled FROM workspace_reconcile WHERE workspace = ?1")?;
+        let mut rows = stmt.query(params![workspace])?;
+
+        if let Some(row) = rows.next()? {
+            let value: String = row.get(0)?;
+            return Ok(value.parse().ok());
+        }
+
+        Ok(None)
+    }
+
+    /// Records that a workspace listing was reconciled now.
+    ///
+    /// # Errors
+    /// Returns [`rusqlite::Error`] when SQL execution fails.
+    pub fn mark_reconciled(&self, workspace: &str) -> Result<(), rusqlite::Error> {
         let now = unix_epoch_seconds_string();
-        let conn = lock_conn_or_recover(&self.conn);
+        let conn = self.writer();
         conn.execute(
             "
-INSERT INTO issue_sidecars(issue_key, comments_md, updated, cached_at)
-VALUES (?1, ?2, ?3, ?4)
-ON CONFLICT(issue_key) DO UPDATE SET
-  comments_md = excluded.comments_md,
-  updated = excluded.updated,
-  cached_at = excluded.cached_at
+INSERT INTO workspace_reconcile(workspace, last_reconciled)
+VALUES (?1, ?2)
+ON CONFLICT(workspace) DO UPDATE SET
+  last_reconciled = excluded.last_reconciled
 ",
-            params![issue_key, comments_md, updated, now],
+            params![workspace, now],
         )?;
         Ok(())
     }
 
-    /// Upserts markdown comment sidecars in one transaction.
+    /// Tombstones cached issues that no workspace lists anymore and clears
+    /// tombstones for issues that are listed again.
+    ///
+    /// Returns the keys tombstoned by this call.
     ///
     /// # Errors
     /// Returns [`rusqlite::Error`] when transaction or SQL execution fails.
-    pub fn upsert_issue_sidecars_batch(
-        &self,
-        sidecars: &[PersistentSidecarRow],
-    ) -> Result<usize, rusqlite::Error> {
+    pub fn tombstone_orphaned_issues(&self) -> Result<Vec<String>, rusqlite::Error> {
         let now = unix_epoch_seconds_string();
-        let mut conn = lock_conn_or_recover(&self.conn);
+        let mut conn = self.writer();
         let tx = conn.transaction()?;
-
-        let mut count = 0;
-        for (issue_key, comments_md, updated) in sidecars {
-            tx.execute(
+        let orphaned: Vec<String> = {
+            let mut stmt = tx.prepare(
                 "
-INSERT INTO issue_sidecars(issue_key, comments_md, updated, cached_at)
-VALUES (?1, ?2, ?3, ?4)
-ON CONFLICT(issue_key) DO UPDATE SET
-  comments_md = excluded.comments_md,
-  updated = excluded.updated,
-  cached_at = excluded.cached_at
+SELECT issue_key FROM issues
+WHERE issue_key NOT IN (SELECT issue_key FROM workspace_issues)
+  AND issue_key NOT IN (SELECT issue_key FROM issue_tombstones)
+ORDER BY issue_key ASC
 ",
-                params![issue_key, comments_md, updated, now],
             )?;
-            count += 1;
+            let keys = stmt.query_map([], |row| row.get(0))?;
+            keys.collect::<Result<_, _>>()?
+        };
+        for issue_key in &orphaned {
+            tx.execute(
+                "INSERT INTO issue_tombstones(issue_key, tombstoned_at) VALUES (?1, ?2)",
+                params![issue_key, now],
+            )?;
         }
-
+        tx.execute(
+            "DELETE FROM issue_tombstones WHERE issue_key IN (SELECT issue_key FROM workspace_issues)",
+            [],
+        )?;
         tx.commit()?;
-        Ok(count)
+        Ok(orphaned)
     }
 
-    /// Loads markdown comment sidecar bytes for one issue.
+    /// Tombstones specific issues and drops them from every listing and queue.
     ///
     /// # Errors
-    /// Returns [`rusqlite::Error`] when SQL execution fails.
-    pub fn get_issue_comments_md(
-        &self,
-        issue_key: &str,
-    ) -> Result<Option<Vec<u8>>, rusqlite::Error> {
-        let conn = lock_conn_or_recover(&self.conn);
-        let mut stmt =
-            conn.prepare("SELECT comments_md FROM issue_sidecars WHERE issue_key = ?1")?;
-        let mut rows = stmt.query(params![issue_key])?;
-        if let Some(row) = rows.next()? {
-            let bytes: Vec<u8> = row.get(0)?;
-            return Ok(Some(bytes));
-        }
+    /// Returns [`rusqlite::Error`] when transaction or SQL execution fails.
+    pub fn tombstone_issues(&self, issue_keys: &[String]) -> Result<usize, rusqlite::Error> {
+        let now = unix_epoch_seconds_string();
+        let mut conn = self.writer();
+        let tx = conn.transaction()?;
+        for issue_key in issue_keys {
+            tx.execute(
+                "DELETE FROM workspace_issues WHERE issue_key = ?1",
+                params![issue_key],
+            )?;
+            tx.execute(
+                "DELETE FROM sync_backfill WHERE issue_key = ?1",
+                params![issue_key],
+            )?;
+            tx.execute(
+                "INSERT OR REPLACE INTO issue_tombstones(issue_key, tombstoned_at) VALUES (?1, ?2)",
+                params![issue_key, now],
+            )?;
+        }
+        tx.commit()?;
+        Ok(issue_keys.len())
+    }
+
+    /// Deletes expired tombstoned issues, evicts the least-read issues while
+    /// the database is over its size limit, then returns freed pages to the
+    /// filesystem with an incremental `VACUUM`.
+    ///
+    /// # Errors
+    /// Returns [`rusqlite::Error`] when transaction or SQL execution fails.
+    pub fn gc(&self, policy: &GcPolicy) -> Result<GcReport, rusqlite::Error> {
+        let now = unix_epoch_seconds_string();
+        let cutoff = now
+            .parse::<u64>()
+            .unwrap_or(0)
+            .saturating_sub(policy.tombstone_max_age.as_secs());
+        let mut conn = self.writer();
+        self.flush_access_counts(&mut conn)?;
+        let mut report = GcReport {
+            bytes_before: file_bytes(&conn)?,
+            ..GcReport::default()
+        };
+
+        let tx = conn.transaction()?;
+        report.expired = {
+            let mut stmt = tx.prepare(
+                "
+SELECT issue_key FROM issue_tombstones
+WHERE CAST(tombstoned_at AS INTEGER) <= ?1
+ORDER BY issue_key ASC
+",
+            )?;
+            let keys = stmt.query_map(params![cutoff as i64], |row| row.get(0))?;
+            keys.collect::<Result<_, _>>()?
+        };
+        for issue_key in &report.expired {
+            delete_issue_rows(&tx, issue_key)?;
+            tx.execute(
+                "DELETE FROM issue_tombstones WHERE issue_key = ?1",
+                params![issue_key],
+            )?;
+        }
+        // Evicted issues that left every listing have nothing left to keep.
+        tx.execute(
+            "DELETE FROM issue_evictions WHERE issue_key NOT IN (SELECT issue_key FROM workspace_issues)",
+            [],
+        )?;
+
+        if let Some(max_db_bytes) = policy.max_db_bytes {
+            let mut live = live_bytes(&tx)?;
+            'evict: while live > max_db_bytes {
+                let batch: Vec<(String, Option<String>)> = {
+                    let mut stmt = tx.prepare(
+                        "
+SELECT issue_key, updated FROM issues
+ORDER BY access_count ASC, CAST(cached_at AS INTEGER) ASC, issue_key ASC
+LIMIT 64
+",
+                    )?;
+                    let rows = stmt.query_map([], |row| Ok((row.get(0)?, row.get(1)?)))?;
+                    rows.collect::<Result<_, _>>()?
+                };
+                if batch.is_empty() {
+                    logging::warn(format!(
+                        "cache gc: nothing left to evict but {} bytes are still in use (limit {})",
+                        live, max_db_bytes
+                    ));
+                    break;
+                }
+                for (issue_key, updated) in batch {
+                    delete_issue_rows(&tx, &issue_key)?;
+                    tx.execute(
+                        "INSERT OR REPLACE INTO issue_evictions(issue_key, updated, evicted_at) VALUES (?1, ?2, ?3)",
+                        params![issue_key, updated, now],
+                    )?;
+                    report.evicted.push(issue_key);
+                    if live_bytes(&tx)? <= max_db_bytes {
+                        break 'evict;
+                    }
+                }
+                // Rows outside `issues` can keep the database over the limit.
+                let after = live_bytes(&tx)?;
+                if after >= live {
+                    logging::warn(format!(
+                        "cache gc: evicting issues no longer shrinks the database; {} bytes in use cannot meet the {} byte limit",
+                        after, max_db_bytes
+                    ));
+                    break;
+                }
+                live = after;
+            }
+        }
+        tx.commit()?;
+
+        let auto_vacuum: i64 = conn.query_row("PRAGMA auto_vacuum", [], |row| row.get(0))?;
+        if auto_vacuum == 2 {
+            // Each step frees one page, so run it to completion.
+            let mut stmt = conn.prepare("PRAGMA incremental_vacuum")?;
+            let mut rows = stmt.query([])?;
+            while rows.next()?.is_some() {}
+        } else {
+            // Databases created before incremental vacuum need one full rebuild.
+            conn.execute_batch("PRAGMA auto_vacuum = INCREMENTAL; VACUUM;")?;
+        }
+        report.bytes_after = file_bytes(&conn)?;
+        Ok(report)
+    }
+
+    /// Reports whether an issue is tombstoned.
+    ///
+    /// # Errors
+    /// Returns [`rusqlite::Error`] when SQL execution fails.
+    pub fn is_tombstoned(&self, issue_key: &str) -> Result<bool, rusqlite::Error> {
+        let conn = self.reader();
+        let count: i64 = conn.query_row(
+            "SELECT COUNT(*) FROM issue_tombstones WHERE issue_key = ?1",
+            params![issue_key],
+            |row| row.get(0),
+        )?;
+        Ok(count > 0)
+    }
+
+    /// Queues issue refs that a budget-limited sync could not cache.
+    ///
+    /// # Errors
+    /// Returns [`rusqlite::Error`] when transaction or SQL execution fails.
+    pub fn enqueue_backfill(
+        &self,
+        workspace: &str,
+        issue_refs: &[IssueRef],
+    ) -> Result<usize, rusqlite::Error> {
+        let now = unix_epoch_seconds_string();
+        let mut conn = self.writer();
+        let tx = conn.transaction()?;
+        for issue in issue_refs {
+            tx.execute(
+                "
+INSERT INTO sync_backfill(workspace, issue_key, updated, queued_at)
+VALUES (?1, ?2, ?3, ?4)
+ON CONFLICT(workspace, issue_key) DO UPDATE SET
+  updated = excluded.updated
+",
+                params![workspace, issue.key, issue.updated, now],
+            )?;
+        }
+        tx.commit()?;
+        Ok(issue_refs.len())
+    }
+
+    /// Returns up to `limit` queued backfill refs for a workspace, newest first.
+    ///
+    /// # Errors
+    /// Returns [`rusqlite::Error`] when SQL execution fails.
+    pub fn next_backfill(
+        &self,
+        workspace: &str,
+        limit: usize,
+    ) -> Result<Vec<IssueRef>, rusqlite::Error> {
+        let conn = self.reader();
+        let mut stmt = conn.prepare(
+            "
+SELECT issue_key, updated FROM sync_backfill
+WHERE workspace = ?1
+ORDER BY updated DESC, issue_key ASC
+LIMIT ?2
+",
+        )?;
+        let limit = i64::try_from(limit).unwrap_or(i64::MAX);
+        let refs = stmt.query_map(params![workspace, limit], |row| {
+            Ok(IssueRef {
+                key: row.get(0)?,
+                updated: row.get(1)?,
+            })
+        })?;
+        refs.collect()
+    }
+
+    /// Removes issue keys from one workspace's backfill queue, or from every
+    /// queue when `workspace` is `None`.
+    ///
+    /// # Errors
+    /// Returns [`rusqlite::Error`] when transaction or SQL execution fails.
+    pub fn remove_backfill(
+        &self,
+        workspace: Option<&str>,
+        issue_keys: &[String],
+    ) -> Result<usize, rusqlite::Error> {
+        let mut conn = self.writer();
+        let tx = conn.transaction()?;
+        let mut removed = 0;
+        for issue_key in issue_keys {
+            removed += tx.execute(
+                "DELETE FROM sync_backfill WHERE issue_key = ?1 AND (?2 IS NULL OR workspace = ?2)",
+                params![issue_key, workspace],
+            )?;
+        }
+        tx.commit()?;
+        Ok(removed)
+    }
+
+    /// Counts queued backfill entries for one workspace, or all workspaces for `None`.
+    ///
+    /// # Errors
+    /// Returns [`rusqlite::Error`] when SQL execution fails.
+    pub fn backfill_count(&self, workspace: Option<&str>) -> Result<usize, rusqlite::Error> {
+        let conn = self.reader();
+        conn.query_row(
+            "SELECT COUNT(*) FROM sync_backfill WHERE ?1 IS NULL OR workspace = ?1",
+            params![workspace],
+            |row| row.get(0),
+        )
+    }
+
+    /// Sums read counts of the cached issues listed in one workspace.
+    ///
+    /// # Errors
+    /// Returns [`rusqlite::Error`] when SQL execution fails.
+    pub fn workspace_access_count(&self, workspace: &str) -> Result<u64, rusqlite::Error> {
+        let mut conn = self.writer();
+        self.flush_access_counts(&mut conn)?;
+        let total: i64 = conn.query_row(
+            "
+SELECT COALESCE(SUM(i.access_count), 0)
+FROM workspace_issues w
+JOIN issues i ON i.issue_key = w.issue_key
+WHERE w.workspace = ?1
+",
+            params![workspace],
+            |row| row.get(0),
+        )?;
+        Ok(total.max(0) as u64)
+    }
+
+    /// Upserts markdown comment sidecar for one issue.
+    ///
+    /// # Errors
+    /// Returns [`rusqlite::Error`] when SQL execution fails.
+    pub fn upsert_issue_sidecars(
+        &self,
+        issue_key: &str,
+        comments_md: &[u8],
+        updated: Option<&str>,
+    ) -> Result<(), rusqlite::Error> {
+        let now = unix_epoch_seconds_string();
+        let comments_md = self.seal(issue_key, "comments_md", comments_md)?;
+        let conn = self.writer();
+        conn.execute(
+            "
+INSERT INTO issue_sidecars(issue_key, comments_md, updated, cached_at)
+VALUES (?1, ?2, ?3, ?4)
+ON CONFLICT(issue_key) DO UPDATE SET
+  comments_md = excluded.comments_md,
+  updated = excluded.updated,
+  cached_at = excluded.cached_at
+",
+            params![issue_key, comments_md, updated, now],
+        )?;
+        Ok(())
+    }
+
+    /// Upserts markdown comment sidecars in one transaction.
+    ///
+    /// # Errors
+    /// Returns [`rusqlite::Error`] when transaction or SQL execution fails.
+    pub fn upsert_issue_sidecars_batch(
+        &self,
+        sidecars: &[PersistentSidecarRow],
+    ) -> Result<usize, rusqlite::Error> {
+        let now = unix_epoch_seconds_string();
+        let mut conn = self.writer();
+        let tx = conn.transaction()?;
+
+        let mut count = 0;
+        for (issue_key, comments_md, updated) in sidecars {
+            let comments_md = self.seal(issue_key, "comments_md", comments_md)?;
+            tx.execute(
+                "
+INSERT INTO issue_sidecars(issue_key, comments_md, updated, cached_at)
+VALUES (?1, ?2, ?3, ?4)
+ON CONFLICT(issue_key) DO UPDATE SET
+  comments_md = excluded.comments_md,
+  updated = excluded.updated,
+  cached_at = excluded.cached_at
+",
+                params![issue_key, comments_md, updated, now],
+            )?;
+            count += 1;
+        }
+
+        tx.commit()?;
+        Ok(count)
+    }
+
+    /// Loads markdown comment sidecar bytes for one issue.
+    ///
+    /// # Errors
+    /// Returns [`rusqlite::Error`] when SQL execution fails.
+    pub fn get_issue_comments_md(
+        &self,
+        issue_key: &str,
+    ) -> Result<Option<Vec<u8>>, rusqlite::Error> {
+        let conn = self.reader();
+        let mut stmt =
+            conn.prepare("SELECT comments_md FROM issue_sidecars WHERE issue_key = ?1")?;
+        let mut rows = stmt.query(params![issue_key])?;
+        if let Some(row) = rows.next()? {
+            return Ok(Some(self.unseal(issue_key, "comments_md", row.get(0)?)?));
+        }
         Ok(None)
     }
 
@@ -363,25 +1215,640 @@ ON CONFLICT(issue_key) DO UPDATE SET
     /// # Errors
     /// Returns [`rusqlite::Error`] when SQL execution fails.
     pub fn issue_comments_md_len(&self, issue_key: &str) -> Result<Option<u64>, rusqlite::Error> {
-        let conn = lock_conn_or_recover(&self.conn);
+        let conn = self.reader();
         let mut stmt =
             conn.prepare("SELECT length(comments_md) FROM issue_sidecars WHERE issue_key = ?1")?;
         let mut rows = stmt.query(params![issue_key])?;
 
         if let Some(row) = rows.next()? {
-            let len: i64 = row.get(0)?;
-            return Ok(Some(len.max(0) as u64));
+            return Ok(Some(self.plaintext_len(row.get(0)?)));
         }
 
         Ok(None)
     }
+
+    /// Full-text searches issue markdown and comments, best match first.
+    ///
+    /// Every whitespace-separated term must appear; FTS5 query syntax in
+    /// `terms` is matched literally. Tombstoned issues are skipped. An
+    /// encrypted cache has no index and never matches.
+    ///
+    /// # Errors
+    /// Returns [`rusqlite::Error`] when SQL execution fails.
+    pub fn search_issues(
+        &self,
+        terms: &str,
+        limit: usize,
+    ) -> Result<Vec<SearchHit>, rusqlite::Error> {
+        let Some(query) = fts_query(terms) else {
+            return Ok(Vec::new());
+        };
+        if self.is_encrypted() {
+            return Ok(Vec::new());
+        }
+        let conn = self.reader();
+        let mut stmt = conn.prepare(
+            "
+SELECT issue_key, snippet(issue_search, -1, '[', ']', '...', 12)
+FROM issue_search
+WHERE issue_search MATCH ?1
+  AND issue_key NOT IN (SELECT issue_key FROM issue_tombstones)
+ORDER BY bm25(issue_search, 0.0, 1.0, 0.5)
+LIMIT ?2
+",
+        )?;
+        let hits = stmt.query_map(params![query, limit as i64], |row| {
+            Ok(SearchHit {
+                key: row.get(0)?,
+                snippet: row.get(1)?,
+            })
+        })?;
+        hits.collect()
+    }
+
+    /// Visits every persisted markdown document as `(file name, bytes)`.
+    ///
+    /// Issue files are named `KEY.md` and sidecars `KEY.comments.md`. Access
+    /// counters are not touched.
+    ///
+    /// # Errors
+    /// Returns [`rusqlite::Error`] when SQL execution fails.
+    pub fn for_each_document(
+        &self,
+        mut visit: impl FnMut(&str, &[u8]),
+    ) -> Result<usize, rusqlite::Error> {
+        let conn = self.reader();
+        let mut visited = 0;
+        for (sql, column, suffix) in [
+            (
+                "SELECT issue_key, markdown FROM issues ORDER BY issue_key",
+                "markdown",
+                ".md",
+            ),
+            (
+                "SELECT issue_key, comments_md FROM issue_sidecars ORDER BY issue_key",
+                "comments_md",
+                ".comments.md",
+            ),
+        ] {
+            let mut stmt = conn.prepare(sql)?;
+            let mut rows = stmt.query([])?;
+            while let Some(row) = rows.next()? {
+                let key: String = row.get(0)?;
+                let bytes = self.unseal(&key, column, row.get(1)?)?;
+                visit(&format!("{key}{suffix}"), &bytes);
+                visited += 1;
+            }
+        }
+        Ok(visited)
+    }
+}
+
+/// Brings the schema up to `target`, one transaction per step.
+fn migrate(conn: &mut Connection, target: u32) -> Result<(), CacheError> {
+    let found: u32 = conn.query_row("PRAGMA user_version", [], |row| row.get(0))?;
+    if found > SCHEMA_VERSION {
+        return Err(CacheError::SchemaTooNew {
+            path: String::new(),
+            found,
+            supported: SCHEMA_VERSION,
+        });
+    }
+    for version in found..target {
+        let tx = conn.transaction()?;
+        MIGRATIONS[version as usize](&tx)?;
+        tx.pragma_update(None, "user_version", version + 1)?;
+        tx.commit()?;
+        logging::debug(format!(
+            "migrated persistent cache schema to version {}",
+            version + 1
+        ));
+    }
+    Ok(())
+}
+
+fn migrate_base_tables(conn: &Connection) -> Result<(), rusqlite::Error> {
+    conn.execute_batch(
+        "
+CREATE TABLE IF NOT EXISTS issues (
+  issue_key TEXT PRIMARY KEY,
+  markdown BLOB NOT NULL,
+  updated TEXT,
+  cached_at TEXT NOT NULL,
+  access_count INTEGER NOT NULL DEFAULT 0
+);
+
+CREATE TABLE IF NOT EXISTS sync_cursor (
+  workspace TEXT PRIMARY KEY,
+  last_sync TEXT NOT NULL
+);
+
+CREATE TABLE IF NOT EXISTS workspace_issues (
+  workspace TEXT NOT NULL,
+  issue_key TEXT NOT NULL,
+  updated TEXT,
+  PRIMARY KEY(workspace, issue_key)
+);
+
+CREATE INDEX IF NOT EXISTS idx_workspace_issues_issue_key ON workspace_issues(issue_key);
+
+CREATE TABLE IF NOT EXISTS issue_sidecars (
+  issue_key TEXT PRIMARY KEY,
+  comments_md BLOB NOT NULL,
+  updated TEXT,
+  cached_at TEXT NOT NULL
+);
+
+CREATE TABLE IF NOT EXISTS issue_tombstones (
+  issue_key TEXT PRIMARY KEY,
+  tombstoned_at TEXT NOT NULL
+);
+
+CREATE TABLE IF NOT EXISTS workspace_reconcile (
+  workspace TEXT PRIMARY KEY,
+  last_reconciled TEXT NOT NULL
+);
+
+CREATE TABLE IF NOT EXISTS sync_backfill (
+  workspace TEXT NOT NULL,
+  issue_key TEXT NOT NULL,
+  updated TEXT,
+  queued_at TEXT NOT NULL,
+  PRIMARY KEY(workspace, issue_key)
+);
+",
+    )
 }
 
-fn lock_conn_or_recover(conn: &Mutex<Connection>) -> MutexGuard<'_, Connection> {
-    match conn.lock() {
+fn migrate_issue_created(conn: &Connection) -> Result<(), rusqlite::Error> {
+    add_column_if_missing(conn, "issues", "created", "TEXT")
+}
+
+fn migrate_issue_attributes(conn: &Connection) -> Result<(), rusqlite::Error> {
+    conn.execute_batch(
+        "
+CREATE TABLE IF NOT EXISTS issue_attributes (
+  issue_key TEXT PRIMARY KEY,
+  status TEXT,
+  assignee TEXT,
+  url TEXT NOT NULL,
+  labels TEXT NOT NULL
+);
+",
+    )
+}
+
+fn migrate_issue_evictions(conn: &Connection) -> Result<(), rusqlite::Error> {
+    conn.execute_batch(
+        "
+CREATE TABLE IF NOT EXISTS issue_evictions (
+  issue_key TEXT PRIMARY KEY,
+  updated TEXT,
+  evicted_at TEXT NOT NULL
+);
+
+CREATE TRIGGER IF NOT EXISTS issue_evictions_clear AFTER INSERT ON issues BEGIN
+  DELETE FROM issue_evictions WHERE issue_key = new.issue_key;
+END;
+",
+    )
+}
+
+fn migrate_cache_meta(conn: &Connection) -> Result<(), rusqlite::Error> {
+    conn.execute_batch(
+        "
+CREATE TABLE IF NOT EXISTS cache_meta (
+  name TEXT PRIMARY KEY,
+  value BLOB NOT NULL
+);
+",
+    )
+}
+
+fn migrate_issue_assignee_id(conn: &Connection) -> Result<(), rusqlite::Error> {
+    add_column_if_missing(conn, "issue_attributes", "assignee_id", "TEXT")
+}
+
+/// Adds the column encrypted caches keep all of an issue's attributes in.
+fn migrate_sealed_issue_attributes(conn: &Connection) -> Result<(), rusqlite::Error> {
+    add_column_if_missing(conn, "issue_attributes", "sealed", "BLOB")
+}
+
+/// Takes a shared lock on the lock file beside `path`.
+///
+/// Every open cache holds one; [`PersistentCache::rekey`] needs the only one.
+fn lock_shared(path: &Path) -> Result<Option<File>, CacheError> {
+    if path.as_os_str() == ":memory:" {
+        return Ok(None);
+    }
+    let lock_path = format!("{}.lock", path.display());
+    let failed = |source| CacheError::Lock {
+        path: path.display().to_string(),
+        source,
+    };
+    let file = File::options()
+        .create(true)
+        .truncate(false)
+        .write(true)
+        .open(&lock_path)
+        .map_err(failed)?;
+    match file.try_lock_shared() {
+        Ok(()) => Ok(Some(file)),
+        Err(TryLockError::WouldBlock) => Err(CacheError::InUse {
+            path: path.display().to_string(),
+        }),
+        Err(TryLockError::Error(source)) => Err(failed(source)),
+    }
+}
+
+/// Trades a cache's shared lock for an exclusive one until the guard drops.
+fn lock_exclusive<'a>(
+    lock: Option<&'a File>,
+    path: &str,
+) -> Result<Option<ExclusiveLock<'a>>, CacheError> {
+    let Some(lock) = lock else {
+        return Ok(None);
+    };
+    let failed = |source| CacheError::Lock {
+        path: path.to_string(),
+        source,
+    };
+    lock.unlock().map_err(failed)?;
+    match lock.try_lock() {
+        Ok(()) => Ok(Some(ExclusiveLock(lock))),
+        Err(err) => {
+            lock.lock_shared().map_err(failed)?;
+            match err {
+                TryLockError::WouldBlock => Err(CacheError::InUse {
+                    path: path.to_string(),
+                }),
+                TryLockError::Error(source) => Err(failed(source)),
+            }
+        }
+    }
+}
+
+/// Exclusive hold on a cache lock file; restores the shared lock on drop.
+struct ExclusiveLock<'a>(&'a File);
+
+impl Drop for ExclusiveLock<'_> {
+    fn drop(&mut self) {
+        if let Err(err) = self.0.unlock().and_then(|()| self.0.lock_shared()) {
+            logging::warn(format!("failed to restore shared cache lock: {err}"));
+        }
+    }
+}
+
+/// Checks `cipher` against the stored key check, encrypting a database that
+/// has none yet.
+fn check_encryption(conn: &mut Connection, cipher: Option<&BlobCipher>) -> Result<(), CacheError> {
+    let key_check: Option<Vec<u8>> = conn
+        .query_row(
+            "SELECT value FROM cache_meta WHERE name = 'key_check'",
+            [],
+            |row| row.get(0),
+        )
+        .optional()?;
+    match (key_check, cipher) {
+        (None, None) => Ok(()),
+        (Some(_), None) => Err(CacheError::KeyRequired {
+            path: String::new(),
+        }),
+        (Some(key_check), Some(cipher)) => {
+            cipher
+                .open(KEY_CHECK_AAD, &key_check)
+                .map_err(|_| CacheError::WrongKey {
+                    path: String::new(),
+                })?;
+            conn.execute_batch("PRAGMA secure_delete = ON;")?;
+            // Caches encrypted before attributes were sealed still hold
+            // them in plaintext.
+            let plaintext: i64 = conn.query_row(
+                "SELECT COUNT(*) FROM issue_attributes WHERE sealed IS NULL",
+                [],
+                |row| row.get(0),
+            )?;
+            if plaintext > 0 {
+                reencrypt(conn, Some(cipher), cipher)?;
+                logging::info(format!("encrypted attributes of {plaintext} cached issues"));
+            }
+            Ok(())
+        }
+        (None, Some(cipher)) => {
+            let rewritten = reencrypt(conn, None, cipher)?;
+            logging::info(format!(
+                "encrypted {rewritten} cached documents; full-text search is now disabled"
+            ));
+            Ok(())
+        }
+    }
+}
+
+/// Rewrites issue, comment and attribute blobs from `old` (plaintext when
+/// `None`) to `new` and stores a new key check.
+///
+/// Drops the full-text index, then vacuums and truncates the WAL so no
+/// plaintext or old-key pages stay behind in the files.
+fn reencrypt(
+    conn: &mut Connection,
+    old: Option<&BlobCipher>,
+    new: &BlobCipher,
+) -> Result<usize, CacheError> {
+    conn.execute_batch("PRAGMA secure_delete = ON;")?;
+    let tx = conn.transaction()?;
+    drop_search_index(&tx)?;
+    let mut rewritten = 0;
+    for (table, column) in [("issues", "markdown"), ("issue_sidecars", "comments_md")] {
+        let blobs = {
+            let mut stmt = tx.prepare(&format!("SELECT issue_key, {column} FROM {table}"))?;
+            let rows = stmt
+                .query_map([], |row| {
+                    Ok((row.get::<_, String>(0)?, row.get::<_, Vec<u8>>(1)?))
+                })?
+                .collect::<Result<Vec<_>, _>>()?;
+            rows
+        };
+        let mut update = tx.prepare(&format!(
+            "UPDATE {table} SET {column} = ?2 WHERE issue_key = ?1"
+        ))?;
+        for (issue_key, blob) in blobs {
+            let aad = blob_aad(&issue_key, column);
+            let plaintext = match old {
+                Some(old) => old.open(&aad, &blob).map_err(|_| CacheError::WrongKey {
+                    path: String::new(),
+                })?,
+                None => blob,
+            };
+            let sealed = new
+                .seal(&aad, &plaintext)
+                .map_err(|err| rusqlite::Error::ToSqlConversionFailure(Box::new(err)))?;
+            update.execute(params![issue_key, sealed])?;
+            rewritten += 1;
+        }
+    }
+    let attributes = {
+        let mut stmt = tx.prepare(
+            "SELECT issue_key, status, assignee, assignee_id, url, labels, sealed FROM issue_attributes",
+        )?;
+        let rows = stmt
+            .query_map([], |row| {
+                Ok((
+                    row.get::<_, String>(0)?,
+                    plaintext_attributes(row, 1)?,
+                    row.get::<_, Option<Vec<u8>>>(6)?,
+                ))
+            })?
+            .collect::<Result<Vec<_>, _>>()?;
+        rows
+    };
+    for (issue_key, plaintext, sealed) in attributes {
+        let attributes = match sealed {
+            None => plaintext,
+            Some(sealed) => {
+                let wrong_key = || CacheError::WrongKey {
+                    path: String::new(),
+                };
+                let json = old
+                    .ok_or_else(wrong_key)?
+                    .open(&blob_aad(&issue_key, "attributes"), &sealed)
+                    .map_err(|_| wrong_key())?;
+                serde_json::from_slice(&json).map_err(|_| wrong_key())?
+            }
+        };
+        let sealed = seal_attributes(new, &issue_key, &attributes)?;
+        write_sealed_attributes(&tx, &issue_key, &sealed)?;
+        rewritten += 1;
+    }
+    let key_check = new
+        .seal(KEY_CHECK_AAD, KEY_CHECK_PLAINTEXT)
+        .map_err(|err| rusqlite::Error::ToSqlConversionFailure(Box::new(err)))?;
+    tx.execute(
+        "
+INSERT INTO cache_meta(name, value) VALUES ('key_check', ?1)
+ON CONFLICT(name) DO UPDATE SET value = excluded.value
+",
+        params![key_check],
+    )?;
+    tx.commit()?;
+    conn.execute_batch("VACUUM;")?;
+    conn.query_row("PRAGMA wal_checkpoint(TRUNCATE)", [], |_| Ok(()))?;
+    Ok(rewritten)
+}
+
+/// Reads the plaintext attribute columns, `status` first at index `first`.
+fn plaintext_attributes(
+    row: &rusqlite::Row<'_>,
+    first: usize,
+) -> Result<IssueAttributes, rusqlite::Error> {
+    let labels: String = row.get(first + 4)?;
+    Ok(IssueAttributes {
+        status: row.get(first)?,
+        assignee: row.get(first + 1)?,
+        assignee_id: row.get(first + 2)?,
+        url: row.get(first + 3)?,
+        labels: serde_json::from_str(&labels).unwrap_or_default(),
+    })
+}
+
+/// Seals all of an issue's attributes as one JSON blob.
+fn seal_attributes(
+    cipher: &BlobCipher,
+    issue_key: &str,
+    attributes: &IssueAttributes,
+) -> Result<Vec<u8>, rusqlite::Error> {
+    let json = serde_json::to_vec(attributes)
+        .map_err(|err| rusqlite::Error::ToSqlConversionFailure(Box::new(err)))?;
+    cipher
+        .seal(&blob_aad(issue_key, "attributes"), &json)
+        .map_err(|err| rusqlite::Error::ToSqlConversionFailure(Box::new(err)))
+}
+
+/// Stores sealed attributes, clearing the plaintext columns.
+fn write_sealed_attributes(
+    conn: &Connection,
+    issue_key: &str,
+    sealed: &[u8],
+) -> Result<(), rusqlite::Error> {
+    conn.execute(
+        "
+INSERT INTO issue_attributes(issue_key, status, assignee, assignee_id, url, labels, sealed)
+VALUES (?1, NULL, NULL, NULL, '', '', ?2)
+ON CONFLICT(issue_key) DO UPDATE SET
+  status = NULL,
+  assignee = NULL,
+  assignee_id = NULL,
+  url = '',
+  labels = '',
+  sealed = excluded.sealed
+",
+        params![issue_key, sealed],
+    )?;
+    Ok(())
+}
+
+/// Associated data binding a sealed blob to its row and column.
+fn blob_aad(issue_key: &str, column: &str) -> Vec<u8> {
+    format!("{issue_key}/{column}").into_bytes()
+}
+
+/// Removes the full-text index and the triggers that feed it.
+fn drop_search_index(conn: &Connection) -> Result<(), rusqlite::Error> {
+    conn.execute_batch(
+        "
+DROP TRIGGER IF EXISTS issue_search_insert;
+DROP TRIGGER IF EXISTS issue_search_update;
+DROP TRIGGER IF EXISTS issue_search_delete;
+DROP TRIGGER IF EXISTS issue_search_sidecar_insert;
+DROP TRIGGER IF EXISTS issue_search_sidecar_update;
+DROP TRIGGER IF EXISTS issue_search_sidecar_delete;
+DROP TABLE IF EXISTS issue_search;
+",
+    )
+}
+
+/// Creates the FTS5 index over issue and comment markdown.
+///
+/// Triggers keep it in step with `issues` and `issue_sidecars` inside the
+/// writing transaction. The index shares `rowid` with `issues`, and is filled
+/// from existing rows when it is first created.
+fn create_search_index(conn: &Connection) -> Result<(), rusqlite::Error> {
+    let exists: bool = conn.query_row(
+        "SELECT EXISTS(SELECT 1 FROM sqlite_master WHERE name = 'issue_search')",
+        [],
+        |row| row.get(0),
+    )?;
+    conn.execute_batch(
+        "
+CREATE VIRTUAL TABLE IF NOT EXISTS issue_search USING fts5(
+  issue_key UNINDEXED,
+  body,
+  comments,
+  tokenize = 'porter unicode61'
+);
+
+CREATE TRIGGER IF NOT EXISTS issue_search_insert AFTER INSERT ON issues BEGIN
+  INSERT INTO issue_search(rowid, issue_key, body, comments)
+  VALUES (
+    new.rowid,
+    new.issue_key,
+    CAST(new.markdown AS TEXT),
+    (SELECT CAST(comments_md AS TEXT) FROM issue_sidecars WHERE issue_key = new.issue_key)
+  );
+END;
+
+CREATE TRIGGER IF NOT EXISTS issue_search_update AFTER UPDATE OF markdown ON issues BEGIN
+  UPDATE issue_search SET body = CAST(new.markdown AS TEXT) WHERE rowid = new.rowid;
+END;
+
+CREATE TRIGGER IF NOT EXISTS issue_search_delete AFTER DELETE ON issues BEGIN
+  DELETE FROM issue_search WHERE rowid = old.rowid;
+END;
+
+CREATE TRIGGER IF NOT EXISTS issue_search_sidecar_insert AFTER INSERT ON issue_sidecars BEGIN
+  UPDATE issue_search SET comments = CAST(new.comments_md AS TEXT)
+  WHERE rowid = (SELECT rowid FROM issues WHERE issue_key = new.issue_key);
+END;
+
+CREATE TRIGGER IF NOT EXISTS issue_search_sidecar_update
+AFTER UPDATE OF comments_md ON issue_sidecars BEGIN
+  UPDATE issue_search SET comments = CAST(new.comments_md AS TEXT)
+  WHERE rowid = (SELECT rowid FROM issues WHERE issue_key = new.issue_key);
+END;
+
+CREATE TRIGGER IF NOT EXISTS issue_search_sidecar_delete AFTER DELETE ON issue_sidecars BEGIN
+  UPDATE issue_search SET comments = NULL
+  WHERE rowid = (SELECT rowid FROM issues WHERE issue_key = old.issue_key);
+END;
+",
+    )?;
+    if !exists {
+        conn.execute(
+            "
+INSERT INTO issue_search(rowid, issue_key, body, comments)
+SELECT issues.rowid, issues.issue_key, CAST(issues.markdown AS TEXT),
+       CAST(issue_sidecars.comments_md AS TEXT)
+FROM issues LEFT JOIN issue_sidecars ON issue_sidecars.issue_key = issues.issue_key
+",
+            [],
+        )?;
+    }
+    Ok(())
+}
+
+/// Turns free-form search terms into an FTS5 query that matches every term
+/// as a literal phrase, so keys like `PROJ-12` are not read as operators.
+fn fts_query(terms: &str) -> Option<String> {
+    let phrases: Vec<String> = terms
+        .split_whitespace()
+        .map(|term| format!("\"{}\"", term.replace('"', "\"\"")))
+        .collect();
+    if phrases.is_empty() {
+        None
+    } else {
+        Some(phrases.join(" "))
+    }
+}
+
+/// Deletes an issue's markdown, sidecar and attributes.
+fn delete_issue_rows(conn: &Connection, issue_key: &str) -> Result<(), rusqlite::Error> {
+    for table in ["issues", "issue_sidecars", "issue_attributes"] {
+        conn.execute(
+            &format!("DELETE FROM {table} WHERE issue_key = ?1"),
+            params![issue_key],
+        )?;
+    }
+    Ok(())
+}
+
+/// Size of the database file, in bytes.
+fn file_bytes(conn: &Connection) -> Result<u64, rusqlite::Error> {
+    conn.query_row(
+        "SELECT page_count * page_size FROM pragma_page_count(), pragma_page_size()",
+        [],
+        |row| row.get::<_, i64>(0),
+    )
+    .map(|bytes| bytes.max(0) as u64)
+}
+
+/// Bytes in pages that hold data, excluding the free list.
+fn live_bytes(conn: &Connection) -> Result<u64, rusqlite::Error> {
+    conn.query_row(
+        "
+SELECT (page_count - freelist_count) * page_size
+FROM pragma_page_count(), pragma_freelist_count(), pragma_page_size()
+",
+        [],
+        |row| row.get::<_, i64>(0),
+    )
+    .map(|bytes| bytes.max(0) as u64)
+}
+
+/// Adds a column to a table created by an older version of the schema.
+fn add_column_if_missing(
+    conn: &Connection,
+    table: &str,
+    column: &str,
+    definition: &str,
+) -> Result<(), rusqlite::Error> {
+    let mut stmt = conn.prepare(&format!("PRAGMA table_info({table})"))?;
+    let exists = stmt
+        .query_map([], |row| row.get::<_, String>(1))?
+        .collect::<Result<Vec<_>, _>>()?
+        .iter()
+        .any(|name| name == column);
+    if !exists {
+        conn.execute_batch(&format!(
+            "ALTER TABLE {table} ADD COLUMN {column} {definition};"
+        ))?;
+    }
+    Ok(())
+}
+
+fn lock_or_recover<'a, T>(mutex: &'a Mutex<T>, name: &'static str) -> MutexGuard<'a, T> {
+    match mutex.lock() {
         Ok(guard) => guard,
         Err(poisoned) => {
-            logging::warn("recovering poisoned mutex: persistent cache connection");
+            logging::warn(format!("recovering poisoned mutex: {name}"));
             poisoned.into_inner()
         }
     }
@@ -470,4 +1937,703 @@ mod tests {
             2
         );
     }
+
+    #[test]
+    fn visits_issue_and_sidecar_documents() {
+        let db = PersistentCache::new(Path::new(":memory:")).expect("db open");
+        db.upsert_issue("ST-2", b"two", None).expect("upsert");
+        db.upsert_issue("ST-1", b"one", None).expect("upsert");
+        db.upsert_issue_sidecars("ST-1", b"comments", None)
+            .expect("upsert sidecars");
+
+        let mut names = Vec::new();
+        let visited = db
+            .for_each_document(|name, _| names.push(name.to_string()))
+            .expect("visit");
+        assert_eq!(visited, 3);
+        assert_eq!(names, vec!["ST-1.md", "ST-2.md", "ST-1.comments.md"]);
+    }
+
+    #[test]
+    fn tombstones_issues_missing_from_every_workspace() {
+        let db = PersistentCache::new(Path::new(":memory:")).expect("db open");
+        db.upsert_issue("ST-1", b"one", None).expect("upsert");
+        db.upsert_issue("ST-2", b"two", None).expect("upsert");
+        let listed = |key: &str| IssueRef {
+            key: key.to_string(),
+            updated: None,
+        };
+        db.upsert_workspace_issue_refs("default", &[listed("ST-1"), listed("ST-2")])
+            .expect("upsert refs");
+        db.upsert_workspace_issue_refs("removed", &[listed("ST-2")])
+            .expect("upsert refs");
+
+        assert_eq!(
+            db.retain_workspaces(&["default".to_string()])
+                .expect("retain"),
+            1
+        );
+        assert!(db
+            .tombstone_orphaned_issues()
+            .expect("tombstone")
+            .is_empty());
+
+        db.upsert_workspace_issue_refs("default", &[listed("ST-1")])
+            .expect("upsert refs");
+        assert_eq!(
+            db.tombstone_orphaned_issues().expect("tombstone"),
+            vec!["ST-2".to_string()]
+        );
+        assert!(db.is_tombstoned("ST-2").expect("lookup"));
+
+        db.upsert_workspace_issue_refs("default", &[listed("ST-1"), listed("ST-2")])
+            .expect("upsert refs");
+        assert!(db
+            .tombstone_orphaned_issues()
+            .expect("tombstone")
+            .is_empty());
+        assert!(!db.is_tombstoned("ST-2").expect("lookup"));
+    }
+
+    #[test]
+    fn backfill_queue_roundtrip() {
+        let db = PersistentCache::new(Path::new(":memory:")).expect("db open");
+        let queued = |key: &str, updated: &str| IssueRef {
+            key: key.to_string(),
+            updated: Some(updated.to_string()),
+        };
+        db.enqueue_backfill(
+            "default",
+            &[
+                queued("ST-1", "2026-02-20T00:00:00.000+0000"),
+                queued("ST-2", "2026-02-22T00:00:00.000+0000"),
+                queued("ST-3", "2026-02-21T00:00:00.000+0000"),
+            ],
+        )
+        .expect("enqueue");
+        db.enqueue_backfill("ops", &[queued("ST-3", "2026-02-21T00:00:00.000+0000")])
+            .expect("enqueue");
+        assert_eq!(db.backfill_count(None).expect("count"), 4);
+        assert_eq!(db.backfill_count(Some("ops")).expect("count"), 1);
+
+        let next: Vec<_> = db
+            .next_backfill("default", 2)
+            .expect("next")
+            .into_iter()
+            .map(|item| item.key)
+            .collect();
+        assert_eq!(next, vec!["ST-2".to_string(), "ST-3".to_string()]);
+
+        assert_eq!(
+            db.remove_backfill(Some("ops"), &["ST-3".to_string()])
+                .expect("remove"),
+            1
+        );
+        assert_eq!(
+            db.remove_backfill(None, &["ST-3".to_string()])
+                .expect("remove"),
+            1
+        );
+        assert_eq!(db.backfill_count(None).expect("count"), 2);
+    }
+
+    #[test]
+    fn reads_issue_timestamps_and_upgrades_old_issue_tables() {
+        let dir = std::env::temp_dir().join(format!("jirafs-ts-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).expect("temp dir");
+        let path = dir.join("cache.db");
+        let _ = std::fs::remove_file(&path);
+        Connection::open(&path)
+            .expect("open legacy db")
+            .execute_batch(
+                "CREATE TABLE issues (
+                   issue_key TEXT PRIMARY KEY,
+                   markdown BLOB NOT NULL,
+                   updated TEXT,
+                   cached_at TEXT NOT NULL,
+                   access_count INTEGER NOT NULL DEFAULT 0
+                 );",
+            )
+            .expect("legacy schema");
+
+        let db = PersistentCache::new(&path).expect("db open");
+        db.upsert_issues_batch(&[PersistentIssueRow {
+            key: "PROJ-1".to_string(),
+            markdown: b"hello".to_vec(),
+            updated: Some("2026-02-20T10:00:00.000+0000".to_string()),
+            created: Some("2026-01-02T09:00:00.000+0000".to_string()),
+            attributes: Some(IssueAttributes {
+                status: Some("In Progress".to_string()),
+                assignee: None,
+                assignee_id: None,
+                url: "https://example.atlassian.net/browse/PROJ-1".to_string(),
+                labels: vec!["backend".to_string(), "urgent".to_string()],
+            }),
+        }])
+        .expect("upsert");
+        db.upsert_issue_sidecars_batch(&[(
+            "PROJ-1".to_string(),
+            b"comments".to_vec(),
+            Some("2026-02-19T08:00:00.000+0000".to_string()),
+        )])
+        .expect("sidecar");
+
+        assert_eq!(
+            db.issue_timestamps("PROJ-1").expect("read"),
+            Some(IssueTimestamps {
+                updated: Some("2026-02-20T10:00:00.000+0000".to_string()),
+                created: Some("2026-01-02T09:00:00.000+0000".to_string()),
+                comments_updated: Some("2026-02-19T08:00:00.000+0000".to_string()),
+            })
+        );
+        assert_eq!(db.issue_timestamps("PROJ-2").expect("read"), None);
+        let attributes = db
+            .issue_attributes("PROJ-1")
+            .expect("read")
+            .expect("attributes stored");
+        assert_eq!(attributes.status.as_deref(), Some("In Progress"));
+        assert_eq!(attributes.labels, vec!["backend", "urgent"]);
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn migrates_every_earlier_schema_version() {
+        let dir = std::env::temp_dir().join(format!("jirafs-migrate-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).expect("temp dir");
+        for version in 0..SCHEMA_VERSION {
+            let path = dir.join(format!("cache-v{version}.db"));
+            let _ = std::fs::remove_file(&path);
+            let mut conn = Connection::open(&path).expect("open old db");
+            migrate(&mut conn, version).expect("build old schema");
+            if version > 0 {
+                conn.execute(
+                    "INSERT INTO issues(issue_key, markdown, updated, cached_at)
+                     VALUES ('OLD-1', CAST('legacy timeout' AS BLOB), 'u1', '0')",
+                    [],
+                )
+                .expect("old row");
+            }
+            drop(conn);
+
+            let db = PersistentCache::new(&path).expect("db open");
+            let user_version: u32 = db
+                .writer()
+                .query_row("PRAGMA user_version", [], |row| row.get(0))
+                .expect("user_version");
+            assert_eq!(user_version, SCHEMA_VERSION, "from version {version}");
+            db.upsert_issues_batch(&[PersistentIssueRow {
+                key: "NEW-1".to_string(),
+                markdown: b"fresh timeout".to_vec(),
+                updated: Some("u2".to_string()),
+                created: Some("c2".to_string()),
+                attributes: Some(IssueAttributes::default()),
+            }])
+            .expect("upsert after migration");
+            let mut hits: Vec<String> = db
+                .search_issues("timeout", 10)
+                .expect("search")
+                .into_iter()
+                .map(|hit| hit.key)
+                .collect();
+            hits.sort();
+            let expected: &[&str] = if version > 0 {
+                &["NEW-1", "OLD-1"]
+            } else {
+                &["NEW-1"]
+            };
+            assert_eq!(hits, expected, "from version {version}");
+            drop(db);
+
+            PersistentCache::new(&path).expect("reopen is a no-op");
+        }
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    /// Schema created by the first release, before any migration existed.
+    const BASELINE_SCHEMA: &str = "
+CREATE TABLE IF NOT EXISTS issues (
+  issue_key TEXT PRIMARY KEY,
+  markdown BLOB NOT NULL,
+  updated TEXT,
+  cached_at TEXT NOT NULL,
+  access_count INTEGER NOT NULL DEFAULT 0
+);
+
+CREATE TABLE IF NOT EXISTS sync_cursor (
+  workspace TEXT PRIMARY KEY,
+  last_sync TEXT NOT NULL
+);
+
+CREATE TABLE IF NOT EXISTS workspace_issues (
+  workspace TEXT NOT NULL,
+  issue_key TEXT NOT NULL,
+  updated TEXT,
+  PRIMARY KEY(workspace, issue_key)
+);
+
+CREATE INDEX IF NOT EXISTS idx_workspace_issues_issue_key ON workspace_issues(issue_key);
+
+CREATE TABLE IF NOT EXISTS issue_sidecars (
+  issue_key TEXT PRIMARY KEY,
+  comments_md BLOB NOT NULL,
+  updated TEXT,
+  cached_at TEXT NOT NULL
+);
+";
+
+    /// Tables the last unversioned release added on top of the baseline,
+    /// including the full-text index and its triggers.
+    const UNVERSIONED_SEARCH_SCHEMA: &str = "
+CREATE TABLE IF NOT EXISTS issue_tombstones (
+  issue_key TEXT PRIMARY KEY,
+  tombstoned_at TEXT NOT NULL
+);
+
+CREATE TABLE IF NOT EXISTS workspace_reconcile (
+  workspace TEXT PRIMARY KEY,
+  last_reconciled TEXT NOT NULL
+);
+
+CREATE TABLE IF NOT EXISTS issue_attributes (
+  issue_key TEXT PRIMARY KEY,
+  status TEXT,
+  assignee TEXT,
+  url TEXT NOT NULL,
+  labels TEXT NOT NULL
+);
+
+CREATE TABLE IF NOT EXISTS sync_backfill (
+  workspace TEXT NOT NULL,
+  issue_key TEXT NOT NULL,
+  updated TEXT,
+  queued_at TEXT NOT NULL,
+  PRIMARY KEY(workspace, issue_key)
+);
+
+ALTER TABLE issues ADD COLUMN created TEXT;
+
+CREATE VIRTUAL TABLE IF NOT EXISTS issue_search USING fts5(
+  issue_key UNINDEXED,
+  body,
+  comments,
+  tokenize = 'porter unicode61'
+);
+
+CREATE TRIGGER IF NOT EXISTS issue_search_insert AFTER INSERT ON issues BEGIN
+  INSERT INTO issue_search(rowid, issue_key, body, comments)
+  VALUES (
+    new.rowid,
+    new.issue_key,
+    CAST(new.markdown AS TEXT),
+    (SELECT CAST(comments_md AS TEXT) FROM issue_sidecars WHERE issue_key = new.issue_key)
+  );
+END;
+
+CREATE TRIGGER IF NOT EXISTS issue_search_update AFTER UPDATE OF markdown ON issues BEGIN
+  UPDATE issue_search SET body = CAST(new.markdown AS TEXT) WHERE rowid = new.rowid;
+END;
+
+CREATE TRIGGER IF NOT EXISTS issue_search_delete AFTER DELETE ON issues BEGIN
+  DELETE FROM issue_search WHERE rowid = old.rowid;
+END;
+
+CREATE TRIGGER IF NOT EXISTS issue_search_sidecar_insert AFTER INSERT ON issue_sidecars BEGIN
+  UPDATE issue_search SET comments = CAST(new.comments_md AS TEXT)
+  WHERE rowid = (SELECT rowid FROM issues WHERE issue_key = new.issue_key);
+END;
+
+CREATE TRIGGER IF NOT EXISTS issue_search_sidecar_update
+AFTER UPDATE OF comments_md ON issue_sidecars BEGIN
+  UPDATE issue_search SET comments = CAST(new.comments_md AS TEXT)
+  WHERE rowid = (SELECT rowid FROM issues WHERE issue_key = new.issue_key);
+END;
+
+CREATE TRIGGER IF NOT EXISTS issue_search_sidecar_delete AFTER DELETE ON issue_sidecars BEGIN
+  UPDATE issue_search SET comments = NULL
+  WHERE rowid = (SELECT rowid FROM issues WHERE issue_key = old.issue_key);
+END;
+";
+
+    const LEGACY_ROWS: &str = "
+INSERT INTO issues(issue_key, markdown, updated, cached_at, access_count)
+VALUES ('OLD-1', CAST('# Login timeout on SSO' AS BLOB), '2026-01-05T10:00:00.000+0000', '100', 3);
+INSERT INTO issue_sidecars(issue_key, comments_md, updated, cached_at)
+VALUES ('OLD-1', CAST('customer saw a gateway error' AS BLOB), NULL, '100');
+INSERT INTO workspace_issues(workspace, issue_key, updated)
+VALUES ('default', 'OLD-1', '2026-01-05T10:00:00.000+0000');
+INSERT INTO sync_cursor(workspace, last_sync) VALUES ('default', '2026-01-05T10:00:00Z');
+";
+
+    #[test]
+    fn migrates_literal_legacy_databases() {
+        let dir = std::env::temp_dir().join(format!("jirafs-legacy-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).expect("temp dir");
+        let fixtures = [
+            ("baseline", format!("{BASELINE_SCHEMA}{LEGACY_ROWS}")),
+            (
+                "unversioned-search",
+                format!("{BASELINE_SCHEMA}{UNVERSIONED_SEARCH_SCHEMA}{LEGACY_ROWS}"),
+            ),
+        ];
+        for (name, sql) in fixtures {
+            let path = dir.join(format!("{name}.db"));
+            let _ = std::fs::remove_file(&path);
+            {
+                let conn = Connection::open(&path).expect("open legacy db");
+                conn.execute_batch(&sql).expect("legacy fixture");
+                let user_version: u32 = conn
+                    .query_row("PRAGMA user_version", [], |row| row.get(0))
+                    .expect("user_version");
+                assert_eq!(user_version, 0, "{name}");
+            }
+
+            let db = PersistentCache::new(&path).expect("db open");
+            let user_version: u32 = db
+                .writer()
+                .query_row("PRAGMA user_version", [], |row| row.get(0))
+                .expect("user_version");
+            assert_eq!(user_version, SCHEMA_VERSION, "{name}");
+
+            let issue = db.get_issue("OLD-1").expect("read").expect("kept issue");
+            assert_eq!(issue.markdown, b"# Login timeout on SSO", "{name}");
+            assert_eq!(
+                db.issue_updated("OLD-1").expect("read").as_deref(),
+                Some("2026-01-05T10:00:00.000+0000"),
+                "{name}"
+            );
+            assert_eq!(
+                db.get_issue_comments_md("OLD-1").expect("read").as_deref(),
+                Some(&b"customer saw a gateway error"[..]),
+                "{name}"
+            );
+            let refs = db.list_workspace_issue_refs("default").expect("refs");
+            assert_eq!(refs.len(), 1, "{name}");
+            assert_eq!(refs[0].key, "OLD-1", "{name}");
+            assert_eq!(
+                db.get_sync_cursor("default").expect("cursor").as_deref(),
+                Some("2026-01-05T10:00:00Z"),
+                "{name}"
+            );
+            // Reads made before the upgrade still rank the issue for eviction.
+            assert_eq!(db.workspace_access_count("default").expect("count"), 4);
+
+            // The search index is backfilled (or kept) for rows written
+            // before it, over both the body and the comments.
+            let keys = |terms: &str| -> Vec<String> {
+                db.search_issues(terms, 10)
+                    .expect("search")
+                    .into_iter()
+                    .map(|hit| hit.key)
+                    .collect()
+            };
+            assert_eq!(keys("login timeout"), vec!["OLD-1"], "{name}");
+            assert_eq!(keys("gateway"), vec!["OLD-1"], "{name}");
+
+            db.upsert_issues_batch(&[PersistentIssueRow {
+                key: "NEW-1".to_string(),
+                markdown: b"# Another timeout".to_vec(),
+                updated: Some("2026-02-01T00:00:00.000+0000".to_string()),
+                created: Some("2026-01-30T00:00:00.000+0000".to_string()),
+                attributes: Some(IssueAttributes {
+                    assignee_id: Some("5b10ac8d82e05b22cc7d4ef5".to_string()),
+                    ..IssueAttributes::default()
+                }),
+            }])
+            .expect("upsert after migration");
+            let mut hits = keys("timeout");
+            hits.sort();
+            assert_eq!(hits, vec!["NEW-1", "OLD-1"], "{name}");
+            assert_eq!(
+                db.issue_attributes("NEW-1")
+                    .expect("read")
+                    .and_then(|attributes| attributes.assignee_id)
+                    .as_deref(),
+                Some("5b10ac8d82e05b22cc7d4ef5"),
+                "{name}"
+            );
+        }
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn refuses_schema_from_newer_release() {
+        let dir = std::env::temp_dir().join(format!("jirafs-downgrade-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).expect("temp dir");
+        let path = dir.join("cache.db");
+        let _ = std::fs::remove_file(&path);
+        drop(PersistentCache::new(&path).expect("db open"));
+        Connection::open(&path)
+            .expect("open")
+            .pragma_update(None, "user_version", SCHEMA_VERSION + 1)
+            .expect("bump version");
+
+        match PersistentCache::new(&path) {
+            Err(CacheError::SchemaTooNew {
+                path: reported,
+                found,
+                supported,
+            }) => {
+                assert_eq!(reported, path.display().to_string());
+                assert_eq!(found, SCHEMA_VERSION + 1);
+                assert_eq!(supported, SCHEMA_VERSION);
+            }
+            other => panic!("expected SchemaTooNew, got {other:?}"),
+        }
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn reads_proceed_while_the_writer_is_busy() {
+        let dir = std::env::temp_dir().join(format!("jirafs-wal-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).expect("temp dir");
+        let path = dir.join("cache.db");
+        let _ = std::fs::remove_file(&path);
+        let db = std::sync::Arc::new(PersistentCache::new(&path).expect("db open"));
+        db.upsert_issue("PROJ-1", b"hello", Some("u1"))
+            .expect("upsert");
+        db.upsert_workspace_issue_refs(
+            "default",
+            &[IssueRef {
+                key: "PROJ-1".to_string(),
+                updated: Some("u1".to_string()),
+            }],
+        )
+        .expect("refs");
+
+        let writer = db.writer();
+        let (done, finished) = std::sync::mpsc::channel();
+        let reader = std::sync::Arc::clone(&db);
+        std::thread::spawn(move || {
+            let len = reader.issue_markdown_len("PROJ-1").expect("len");
+            let issue = reader.get_issue("PROJ-1").expect("read");
+            let _ = done.send((len, issue.map(|issue| issue.markdown)));
+        });
+        let (len, markdown) = finished
+            .recv_timeout(Duration::from_secs(5))
+            .expect("reads finish while the writer is held");
+        drop(writer);
+        assert_eq!(len, Some(5));
+        assert_eq!(markdown.as_deref(), Some(&b"hello"[..]));
+
+        // Only the batched `get_issue` read counts; writes do not.
+        assert_eq!(db.workspace_access_count("default").expect("count"), 1);
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn encrypts_existing_cache_and_rekeys() {
+        let dir = std::env::temp_dir().join(format!("jirafs-crypt-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).expect("temp dir");
+        let path = dir.join("cache.db");
+        let _ = std::fs::remove_file(&path);
+        let first = CacheKey::from_hex(&"01".repeat(32)).expect("key");
+        let second = CacheKey::from_hex(&"02".repeat(32)).expect("key");
+        {
+            let db = PersistentCache::new(&path).expect("db open");
+            db.upsert_issue("SEC-1", b"customer password hunter2", Some("u1"))
+                .expect("upsert");
+            db.upsert_issue_sidecars("SEC-1", b"comment hunter2", None)
+                .expect("sidecar");
+            db.upsert_issues_batch(&[PersistentIssueRow {
+                key: "SEC-3".to_string(),
+                markdown: b"attributes only".to_vec(),
+                attributes: Some(IssueAttributes {
+                    status: Some("Waiting for Hunter2 Corp".to_string()),
+                    assignee: Some("Hunter Two".to_string()),
+                    assignee_id: Some("acct-hunter2".to_string()),
+                    url: "https://example.atlassian.net/browse/SEC-3".to_string(),
+                    labels: vec!["hunter2-escalation".to_string()],
+                }),
+                ..PersistentIssueRow::default()
+            }])
+            .expect("attributes");
+        }
+        let attributes = |db: &PersistentCache| {
+            db.issue_attributes("SEC-3")
+                .expect("read")
+                .expect("attributes")
+        };
+
+        let mut db = PersistentCache::open(&path, Some(&first)).expect("encrypt on open");
+        assert!(db.is_encrypted());
+        let stored: Vec<u8> = db
+            .writer()
+            .query_row("SELECT markdown FROM issues", [], |row| row.get(0))
+            .expect("raw blob");
+        assert!(stored.starts_with(b"JFE1"));
+        let (status, labels): (Option<String>, String) = db
+            .writer()
+            .query_row(
+                "SELECT status, labels FROM issue_attributes WHERE issue_key = 'SEC-3'",
+                [],
+                |row| Ok((row.get(0)?, row.get(1)?)),
+            )
+            .expect("raw attributes");
+        assert_eq!((status, labels.as_str()), (None, ""));
+        assert_eq!(attributes(&db).assignee_id.as_deref(), Some("acct-hunter2"));
+        let file = std::fs::read(&path).expect("db bytes");
+        assert!(!file.windows(7).any(|window| window == b"hunter2"));
+        assert_eq!(
+            db.get_issue("SEC-1")
+                .expect("read")
+                .map(|issue| issue.markdown),
+            Some(b"customer password hunter2".to_vec())
+        );
+        assert_eq!(db.issue_markdown_len("SEC-1").expect("len"), Some(25));
+        assert_eq!(db.issue_comments_md_len("SEC-1").expect("len"), Some(15));
+        assert!(db.search_issues("hunter2", 10).expect("search").is_empty());
+
+        // A running daemon keeps the old key; rekeying under it would strand
+        // its writes.
+        let daemon = PersistentCache::open(&path, Some(&first)).expect("second open");
+        assert!(matches!(db.rekey(&second), Err(CacheError::InUse { .. })));
+        drop(daemon);
+        assert_eq!(db.rekey(&second).expect("rekey"), 4);
+        db.upsert_issue("SEC-2", b"new", None).expect("upsert");
+        drop(db);
+
+        assert!(matches!(
+            PersistentCache::new(&path),
+            Err(CacheError::KeyRequired { .. })
+        ));
+        assert!(matches!(
+            PersistentCache::open(&path, Some(&first)),
+            Err(CacheError::WrongKey { .. })
+        ));
+        let db = PersistentCache::open(&path, Some(&second)).expect("new key opens");
+        assert_eq!(attributes(&db).labels, vec!["hunter2-escalation"]);
+        assert_eq!(
+            db.get_issue_comments_md("SEC-1").expect("read"),
+            Some(b"comment hunter2".to_vec())
+        );
+        assert_eq!(
+            db.get_issue("SEC-2")
+                .expect("read")
+                .map(|issue| issue.markdown),
+            Some(b"new".to_vec())
+        );
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn gc_expires_tombstones_and_evicts_least_read_issues() {
+        let dir = std::env::temp_dir().join(format!("jirafs-gc-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).expect("temp dir");
+        let path = dir.join("cache.db");
+        let _ = std::fs::remove_file(&path);
+        let db = PersistentCache::new(&path).expect("db open");
+
+        let body = |seed: u8| -> Vec<u8> { (0..64 * 1024).map(|i| (i as u8) ^ seed).collect() };
+        let refs: Vec<IssueRef> = (1..=8)
+            .map(|n| IssueRef {
+                key: format!("GC-{n}"),
+                updated: Some(format!("u{n}")),
+            })
+            .collect();
+        for (n, issue) in refs.iter().enumerate() {
+            db.upsert_issue(&issue.key, &body(n as u8), issue.updated.as_deref())
+                .expect("upsert");
+        }
+        db.upsert_workspace_issue_refs("default", &refs[1..])
+            .expect("refs");
+        for issue in &refs[2..] {
+            db.get_issue(&issue.key).expect("read");
+        }
+        // Sync rewrites are not reads and must not protect an issue.
+        for _ in 0..3 {
+            db.upsert_issue("GC-2", &body(1), Some("u2"))
+                .expect("resync");
+        }
+        db.tombstone_issues(&["GC-1".to_string()])
+            .expect("tombstone");
+
+        let keep_all = GcPolicy {
+            max_db_bytes: None,
+            tombstone_max_age: Duration::from_secs(3600),
+        };
+        let report = db.gc(&keep_all).expect("gc");
+        assert!(report.expired.is_empty() && report.evicted.is_empty());
+
+        let report = db
+            .gc(&GcPolicy {
+                max_db_bytes: Some(400 * 1024),
+                tombstone_max_age: Duration::ZERO,
+            })
+            .expect("gc");
+        assert_eq!(report.expired, vec!["GC-1"]);
+        assert_eq!(report.evicted.first().map(String::as_str), Some("GC-2"));
+        assert!(report.bytes_after <= 400 * 1024);
+        assert!(report.bytes_after < report.bytes_before);
+        assert!(db.get_issue("GC-1").expect("read").is_none());
+        assert!(!db.is_tombstoned("GC-1").expect("lookup"));
+
+        assert!(db.issue_markdown_len("GC-2").expect("len").is_none());
+        assert_eq!(
+            db.issue_updated("GC-2").expect("read").as_deref(),
+            Some("u2")
+        );
+        db.upsert_issue("GC-2", b"back", Some("u2b"))
+            .expect("refetch");
+        assert_eq!(
+            db.issue_updated("GC-2").expect("read").as_deref(),
+            Some("u2b")
+        );
+
+        // A limit the schema alone exceeds stops once nothing shrinks.
+        let report = db
+            .gc(&GcPolicy {
+                max_db_bytes: Some(1),
+                tombstone_max_age: Duration::from_secs(3600),
+            })
+            .expect("gc");
+        assert!(report.bytes_after > 1);
+        assert!(db.issue_markdown_len("GC-8").expect("len").is_none());
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn search_index_follows_issue_and_comment_writes() {
+        let dir = std::env::temp_dir().join(format!("jirafs-fts-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).expect("temp dir");
+        let path = dir.join("cache.db");
+        let _ = std::fs::remove_file(&path);
+        {
+            let db = PersistentCache::new(&path).expect("db open");
+            db.upsert_issue("PROJ-1", b"# Login timeout on SSO", None)
+                .expect("upsert");
+            db.upsert_issue("PROJ-2", b"# Dashboard colors", None)
+                .expect("upsert");
+            db.upsert_issue_sidecars("PROJ-2", b"customer reports login timeouts too", None)
+                .expect("sidecar");
+            // Simulate a cache written before the index existed.
+            let conn = db.writer();
+            conn.execute_batch("DROP TABLE issue_search; PRAGMA user_version = 0;")
+                .expect("drop index");
+        }
+
+        let db = PersistentCache::new(&path).expect("db reopen");
+        let keys = |terms: &str| -> Vec<String> {
+            db.search_issues(terms, 10)
+                .expect("search")
+                .into_iter()
+                .map(|hit| hit.key)
+                .collect()
+        };
+        assert_eq!(keys("login timeout"), vec!["PROJ-1", "PROJ-2"]);
+        assert_eq!(keys("proj-1 \"OR"), Vec::<String>::new());
+        let hits = db.search_issues("dashboard", 10).expect("search");
+        assert_eq!(hits[0].snippet, "# [Dashboard] colors");
+
+        db.upsert_issue("PROJ-1", b"# Retitled", None)
+            .expect("rewrite");
+        db.upsert_issue_sidecars("PROJ-2", b"resolved", None)
+            .expect("rewrite sidecar");
+        assert!(keys("login").is_empty());
+        assert_eq!(keys("retitled"), vec!["PROJ-1"]);
+
+        db.tombstone_issues(&["PROJ-1".to_string()])
+            .expect("tombstone");
+        assert!(keys("retitled").is_empty());
+        let _ = std::fs::remove_dir_all(&dir);
+    }
 }
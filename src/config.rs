@@ -4,8 +4,15 @@ use std::path::PathBuf;
 
 use serde::Deserialize;
 
+use crate::cache::KeySource;
+use crate::redaction::{Preset, Redactor, ReplacementStyle};
+use crate::render::InlineComments;
+use crate::warmup::AllocationStrategy;
+
 #[derive(Debug, Deserialize)]
 pub struct AppConfig {
+    #[serde(default)]
+    pub offline: bool,
     pub jira: JiraConfig,
     #[serde(default)]
     pub cache: CacheConfig,
@@ -15,19 +22,35 @@ pub struct AppConfig {
     pub metrics: MetricsConfig,
     #[serde(default)]
     pub logging: LoggingConfig,
+    #[serde(default)]
+    pub render: RenderConfig,
+    #[serde(default)]
+    pub redaction: RedactionConfig,
+    #[serde(default)]
+    pub webhooks: WebhooksConfig,
+    #[serde(default)]
+    pub xattrs: XattrsConfig,
 }
 
 #[derive(Debug, Deserialize)]
 pub struct JiraConfig {
+    #[serde(default)]
     pub base_url: String,
+    #[serde(default)]
     pub email: String,
+    #[serde(default)]
     pub api_token: String,
     pub workspaces: HashMap<String, WorkspaceConfig>,
 }
 
-#[derive(Debug, Deserialize, Clone)]
+#[derive(Debug, Default, Deserialize, Clone)]
 pub struct WorkspaceConfig {
     pub jql: String,
+    #[serde(default)]
+    pub inline_comments: InlineComments,
+    pub weight: Option<u32>,
+    pub budget: Option<usize>,
+    pub interval_secs: Option<u64>,
 }
 
 #[derive(Debug, Deserialize)]
@@ -35,6 +58,37 @@ pub struct CacheConfig {
     pub db_path: String,
     #[serde(default = "default_cache_ttl_secs")]
     pub ttl_secs: u64,
+    #[serde(default = "default_cache_on_demand_deadline_ms")]
+    pub on_demand_deadline_ms: u64,
+    #[serde(default = "default_cache_memory_limit_mb")]
+    pub memory_limit_mb: u64,
+    pub max_db_size_mb: Option<u64>,
+    #[serde(default = "default_cache_tombstone_max_age_secs")]
+    pub tombstone_max_age_secs: u64,
+    #[serde(default = "default_cache_gc_interval_secs")]
+    pub gc_interval_secs: u64,
+    #[serde(default)]
+    pub encryption: Option<CacheEncryptionConfig>,
+}
+
+#[derive(Debug, Default, Clone, Deserialize)]
+pub struct CacheEncryptionConfig {
+    pub key_env: Option<String>,
+    pub key_file: Option<String>,
+    pub key_command: Option<String>,
+}
+
+impl CacheEncryptionConfig {
+    /// The single configured key source, if exactly one is set.
+    #[must_use]
+    pub fn key_source(&self) -> Option<KeySource> {
+        match (&self.key_env, &self.key_file, &self.key_command) {
+            (Some(var), None, None) => Some(KeySource::Env(var.clone())),
+            (None, Some(path), None) => Some(KeySource::File(PathBuf::from(path))),
+            (None, None, Some(command)) => Some(KeySource::Command(command.clone())),
+            _ => None,
+        }
+    }
 }
 
 impl Default for CacheConfig {
@@ -42,6 +96,12 @@ impl Default for CacheConfig {
         Self {
             db_path: String::new(),
             ttl_secs: default_cache_ttl_secs(),
+            on_demand_deadline_ms: default_cache_on_demand_deadline_ms(),
+            memory_limit_mb: default_cache_memory_limit_mb(),
+            max_db_size_mb: None,
+            tombstone_max_age_secs: default_cache_tombstone_max_age_secs(),
+            gc_interval_secs: default_cache_gc_interval_secs(),
+            encryption: None,
         }
     }
 }
@@ -52,6 +112,14 @@ pub struct SyncConfig {
     pub budget: usize,
     #[serde(default = "default_sync_interval_secs")]
     pub interval_secs: u64,
+    #[serde(default = "default_sync_reconcile_interval_secs")]
+    pub reconcile_interval_secs: u64,
+    #[serde(default = "default_sync_cursor_overlap_secs")]
+    pub cursor_overlap_secs: u64,
+    #[serde(default)]
+    pub allocation: AllocationStrategy,
+    #[serde(default = "default_sync_concurrency")]
+    pub concurrency: usize,
 }
 
 impl Default for SyncConfig {
@@ -59,6 +127,10 @@ impl Default for SyncConfig {
         Self {
             budget: default_sync_budget(),
             interval_secs: default_sync_interval_secs(),
+            reconcile_interval_secs: default_sync_reconcile_interval_secs(),
+            cursor_overlap_secs: default_sync_cursor_overlap_secs(),
+            allocation: AllocationStrategy::default(),
+            concurrency: default_sync_concurrency(),
         }
     }
 }
@@ -83,18 +155,69 @@ pub struct LoggingConfig {
     pub debug: bool,
 }
 
+#[derive(Debug, Default, Deserialize)]
+pub struct RenderConfig {
+    #[serde(default)]
+    pub template_path: Option<String>,
+}
+
+#[derive(Debug, Default, Clone, Deserialize)]
+pub struct RedactionConfig {
+    #[serde(default)]
+    pub presets: Vec<Preset>,
+    #[serde(default)]
+    pub patterns: Vec<RedactionPattern>,
+    #[serde(default)]
+    pub allowlist: Vec<String>,
+    #[serde(default)]
+    pub replacement: ReplacementStyle,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct RedactionPattern {
+    pub name: String,
+    pub regex: String,
+}
+
+#[derive(Debug, Default, Deserialize)]
+pub struct WebhooksConfig {
+    #[serde(default)]
+    pub listen: Option<String>,
+    #[serde(default)]
+    pub secret: Option<String>,
+    #[serde(default)]
+    pub disable_polling: bool,
+}
+
+#[derive(Debug, Default, Deserialize)]
+pub struct XattrsConfig {
+    #[serde(default)]
+    pub write_back: bool,
+}
+
 #[derive(Debug, Default)]
 pub struct AppConfigOverrides {
+    pub offline: Option<bool>,
     pub jira_base_url: Option<String>,
     pub jira_email: Option<String>,
     pub jira_api_token: Option<String>,
     pub jira_workspaces: Option<HashMap<String, WorkspaceConfig>>,
     pub cache_db_path: Option<String>,
     pub cache_ttl_secs: Option<u64>,
+    pub cache_on_demand_deadline_ms: Option<u64>,
+    pub cache_memory_limit_mb: Option<u64>,
+    pub cache_max_db_size_mb: Option<u64>,
     pub sync_budget: Option<usize>,
     pub sync_interval_secs: Option<u64>,
+    pub sync_reconcile_interval_secs: Option<u64>,
+    pub sync_cursor_overlap_secs: Option<u64>,
+    pub sync_allocation: Option<AllocationStrategy>,
+    pub sync_concurrency: Option<usize>,
     pub metrics_interval_secs: Option<u64>,
     pub logging_debug: Option<bool>,
+    pub render_template_path: Option<String>,
+    pub webhooks_listen: Option<String>,
+    pub xattrs_write_back: Option<bool>,
 }
 
 #[derive(Debug, thiserror::Error)]
@@ -123,6 +246,14 @@ pub fn load() -> Result<AppConfig, ConfigError> {
 }
 
 pub fn load_from(path: &std::path::Path) -> Result<AppConfig, ConfigError> {
+    let cfg = parse_from(path)?;
+    cfg.validate()?;
+    Ok(cfg)
+}
+
+/// Reads a config file without validating it, for callers that validate
+/// after applying overrides (e.g. `--offline` dropping the credential checks).
+pub fn parse_from(path: &std::path::Path) -> Result<AppConfig, ConfigError> {
     let path = path.to_path_buf();
     let raw = std::fs::read_to_string(&path).map_err(|source| {
         if source.kind() == std::io::ErrorKind::NotFound {
@@ -135,12 +266,10 @@ pub fn load_from(path: &std::path::Path) -> Result<AppConfig, ConfigError> {
         }
     })?;
 
-    let cfg = toml::from_str::<AppConfig>(&raw).map_err(|source| ConfigError::ParseFailed {
+    toml::from_str::<AppConfig>(&raw).map_err(|source| ConfigError::ParseFailed {
         path: path.clone(),
         source,
-    })?;
-    cfg.validate()?;
-    Ok(cfg)
+    })
 }
 
 pub fn resolve_config_path() -> Result<PathBuf, ConfigError> {
@@ -168,6 +297,9 @@ fn resolve_config_path_from_env(
 
 impl AppConfig {
     pub fn apply_overrides(&mut self, overrides: &AppConfigOverrides) -> Result<(), ConfigError> {
+        if let Some(value) = overrides.offline {
+            self.offline = value;
+        }
         if let Some(value) = &overrides.jira_base_url {
             self.jira.base_url = value.clone();
         }
@@ -186,35 +318,56 @@ impl AppConfig {
         if let Some(value) = overrides.cache_ttl_secs {
             self.cache.ttl_secs = value;
         }
+        if let Some(value) = overrides.cache_on_demand_deadline_ms {
+            self.cache.on_demand_deadline_ms = value;
+        }
+        if let Some(value) = overrides.cache_memory_limit_mb {
+            self.cache.memory_limit_mb = value;
+        }
+        if let Some(value) = overrides.cache_max_db_size_mb {
+            self.cache.max_db_size_mb = Some(value);
+        }
         if let Some(value) = overrides.sync_budget {
             self.sync.budget = value;
         }
         if let Some(value) = overrides.sync_interval_secs {
             self.sync.interval_secs = value;
         }
+        if let Some(value) = overrides.sync_reconcile_interval_secs {
+            self.sync.reconcile_interval_secs = value;
+        }
+        if let Some(value) = overrides.sync_cursor_overlap_secs {
+            self.sync.cursor_overlap_secs = value;
+        }
+        if let Some(value) = overrides.sync_allocation {
+            self.sync.allocation = value;
+        }
+        if let Some(value) = overrides.sync_concurrency {
+            self.sync.concurrency = value;
+        }
         if let Some(value) = overrides.metrics_interval_secs {
             self.metrics.interval_secs = value;
         }
         if let Some(value) = overrides.logging_debug {
             self.logging.debug = value;
         }
+        if let Some(value) = &overrides.render_template_path {
+            self.render.template_path = Some(value.clone());
+        }
+        if let Some(value) = &overrides.webhooks_listen {
+            self.webhooks.listen = Some(value.clone());
+        }
+        if let Some(value) = overrides.xattrs_write_back {
+            self.xattrs.write_back = value;
+        }
 
         self.validate()
     }
 
     fn validate(&self) -> Result<(), ConfigError> {
-        if self.jira.base_url.trim().is_empty() {
-            return Err(ConfigError::Invalid(
-                "jira.base_url must not be empty".into(),
-            ));
-        }
-        if self.jira.email.trim().is_empty() {
-            return Err(ConfigError::Invalid("jira.email must not be empty".into()));
-        }
-        if self.jira.api_token.trim().is_empty() {
-            return Err(ConfigError::Invalid(
-                "jira.api_token must not be empty".into(),
-            ));
+        // Offline mounts never talk to Jira, so credentials are optional.
+        if !self.offline {
+            self.validate_credentials()?;
         }
         if self.jira.workspaces.is_empty() {
             return Err(ConfigError::Invalid(
@@ -232,6 +385,21 @@ impl AppConfig {
                     "jira.workspaces.{name}.jql must not be empty"
                 )));
             }
+            if workspace.weight == Some(0) {
+                return Err(ConfigError::Invalid(format!(
+                    "jira.workspaces.{name}.weight must be > 0"
+                )));
+            }
+            if workspace.budget == Some(0) {
+                return Err(ConfigError::Invalid(format!(
+                    "jira.workspaces.{name}.budget must be > 0"
+                )));
+            }
+            if workspace.interval_secs == Some(0) {
+                return Err(ConfigError::Invalid(format!(
+                    "jira.workspaces.{name}.interval_secs must be > 0"
+                )));
+            }
         }
         if self.cache.db_path.trim().is_empty() {
             return Err(ConfigError::Invalid(
@@ -241,6 +409,44 @@ impl AppConfig {
         if self.cache.ttl_secs == 0 {
             return Err(ConfigError::Invalid("cache.ttl_secs must be > 0".into()));
         }
+        if self.cache.on_demand_deadline_ms == 0 {
+            return Err(ConfigError::Invalid(
+                "cache.on_demand_deadline_ms must be > 0".into(),
+            ));
+        }
+        if self.cache.memory_limit_mb == 0 {
+            return Err(ConfigError::Invalid(
+                "cache.memory_limit_mb must be > 0".into(),
+            ));
+        }
+        if self.cache.max_db_size_mb == Some(0) {
+            return Err(ConfigError::Invalid(
+                "cache.max_db_size_mb must be > 0".into(),
+            ));
+        }
+        if self.cache.gc_interval_secs == 0 {
+            return Err(ConfigError::Invalid(
+                "cache.gc_interval_secs must be > 0".into(),
+            ));
+        }
+        if let Some(encryption) = &self.cache.encryption {
+            let empty = [
+                &encryption.key_env,
+                &encryption.key_file,
+                &encryption.key_command,
+            ]
+            .into_iter()
+            .any(|value| {
+                value
+                    .as_deref()
+                    .is_some_and(|value| value.trim().is_empty())
+            });
+            if empty || encryption.key_source().is_none() {
+                return Err(ConfigError::Invalid(
+                    "cache.encryption needs exactly one of key_env, key_file or key_command".into(),
+                ));
+            }
+        }
         if self.sync.budget == 0 {
             return Err(ConfigError::Invalid("sync.budget must be > 0".into()));
         }
@@ -249,20 +455,104 @@ impl AppConfig {
                 "sync.interval_secs must be > 0".into(),
             ));
         }
+        if self.sync.concurrency == 0 {
+            return Err(ConfigError::Invalid("sync.concurrency must be > 0".into()));
+        }
+        if self.sync.reconcile_interval_secs == 0 {
+            return Err(ConfigError::Invalid(
+                "sync.reconcile_interval_secs must be > 0".into(),
+            ));
+        }
         if self.metrics.interval_secs == 0 {
             return Err(ConfigError::Invalid(
                 "metrics.interval_secs must be > 0".into(),
             ));
         }
+        if self
+            .render
+            .template_path
+            .as_deref()
+            .is_some_and(|path| path.trim().is_empty())
+        {
+            return Err(ConfigError::Invalid(
+                "render.template_path must not be empty when set".into(),
+            ));
+        }
+        if self
+            .redaction
+            .patterns
+            .iter()
+            .any(|pattern| pattern.name.trim().is_empty())
+        {
+            return Err(ConfigError::Invalid(
+                "redaction.patterns entries must have a name".into(),
+            ));
+        }
+        if let Some(listen) = self.webhooks.listen.as_deref() {
+            if listen.parse::<std::net::SocketAddr>().is_err() {
+                return Err(ConfigError::Invalid(format!(
+                    "webhooks.listen must be a socket address like 127.0.0.1:7788, got {listen:?}"
+                )));
+            }
+            if self
+                .webhooks
+                .secret
+                .as_deref()
+                .is_none_or(|secret| secret.trim().is_empty())
+            {
+                return Err(ConfigError::Invalid(
+                    "webhooks.secret must be set when webhooks.listen is set".into(),
+                ));
+            }
+        } else if self.webhooks.disable_polling {
+            return Err(ConfigError::Invalid(
+                "webhooks.disable_polling requires webhooks.listen".into(),
+            ));
+        }
+        Redactor::from_config(&self.redaction)
+            .map_err(|err| ConfigError::Invalid(format!("redaction: {err}")))?;
 
         Ok(())
     }
+
+    fn validate_credentials(&self) -> Result<(), ConfigError> {
+        if self.jira.base_url.trim().is_empty() {
+            return Err(ConfigError::Invalid(
+                "jira.base_url must not be empty".into(),
+            ));
+        }
+        if self.jira.email.trim().is_empty() {
+            return Err(ConfigError::Invalid("jira.email must not be empty".into()));
+        }
+        if self.jira.api_token.trim().is_empty() {
+            return Err(ConfigError::Invalid(
+                "jira.api_token must not be empty".into(),
+            ));
+        }
+        Ok(())
+    }
 }
 
 const fn default_cache_ttl_secs() -> u64 {
     30
 }
 
+const fn default_cache_on_demand_deadline_ms() -> u64 {
+    3000
+}
+
+const fn default_cache_memory_limit_mb() -> u64 {
+    256
+}
+
+const fn default_cache_tombstone_max_age_secs() -> u64 {
+    7 * 24 * 3600
+}
+
+const fn default_cache_gc_interval_secs() -> u64 {
+    3600
+}
+
 const fn default_sync_budget() -> usize {
     1000
 }
@@ -271,6 +561,18 @@ const fn default_sync_interval_secs() -> u64 {
     60
 }
 
+const fn default_sync_reconcile_interval_secs() -> u64 {
+    3600
+}
+
+const fn default_sync_cursor_overlap_secs() -> u64 {
+    120
+}
+
+const fn default_sync_concurrency() -> usize {
+    4
+}
+
 const fn default_metrics_interval_secs() -> u64 {
     60
 }
@@ -350,6 +652,165 @@ mod tests {
         assert!(matches!(err, ConfigError::Invalid(_)));
     }
 
+    #[test]
+    fn parses_inline_comments_modes() {
+        let raw = r#"
+            [jira]
+            base_url = "https://example.atlassian.net"
+            email = "you@example.com"
+            api_token = "token"
+
+            [jira.workspaces.all]
+            jql = "project = A"
+            inline_comments = "all"
+
+            [jira.workspaces.last]
+            jql = "project = B"
+            inline_comments = 5
+
+            [jira.workspaces.pointer]
+            jql = "project = C"
+
+            [cache]
+            db_path = "/tmp/jirafs-cache.db"
+        "#;
+
+        let cfg: AppConfig = toml::from_str(raw).expect("toml should parse");
+        let mode = |name: &str| cfg.jira.workspaces[name].inline_comments;
+        assert_eq!(mode("all"), InlineComments::All);
+        assert_eq!(mode("last"), InlineComments::Last(5));
+        assert_eq!(mode("pointer"), InlineComments::None);
+
+        let invalid = raw.replace("inline_comments = 5", "inline_comments = \"some\"");
+        assert!(toml::from_str::<AppConfig>(&invalid).is_err());
+    }
+
+    #[test]
+    fn parses_workspace_sync_overrides() {
+        let raw = r#"
+            [jira]
+            base_url = "https://example.atlassian.net"
+            email = "you@example.com"
+            api_token = "token"
+
+            [jira.workspaces.oncall]
+            jql = "project = ONC"
+            weight = 3
+            budget = 200
+            interval_secs = 30
+
+            [cache]
+            db_path = "/tmp/jirafs-cache.db"
+
+            [sync]
+            allocation = "activity"
+        "#;
+
+        let cfg: AppConfig = toml::from_str(raw).expect("toml should parse");
+        cfg.validate().expect("config should validate");
+        let oncall = &cfg.jira.workspaces["oncall"];
+        assert_eq!(oncall.weight, Some(3));
+        assert_eq!(oncall.budget, Some(200));
+        assert_eq!(oncall.interval_secs, Some(30));
+        assert_eq!(cfg.sync.allocation, AllocationStrategy::Activity);
+
+        let zero_weight: AppConfig =
+            toml::from_str(&raw.replace("weight = 3", "weight = 0")).expect("toml should parse");
+        assert!(zero_weight.validate().is_err());
+    }
+
+    #[test]
+    fn validates_rejects_invalid_redaction_patterns() {
+        let raw = r#"
+            [jira]
+            base_url = "https://example.atlassian.net"
+            email = "you@example.com"
+            api_token = "token"
+
+            [jira.workspaces.default]
+            jql = "project = PROJ"
+
+            [cache]
+            db_path = "/tmp/jirafs-cache.db"
+
+            [redaction]
+            presets = ["jwt", "emails"]
+            replacement = "labeled"
+
+            [[redaction.patterns]]
+            name = "broken"
+            regex = "(unclosed"
+        "#;
+
+        let cfg: AppConfig = toml::from_str(raw).expect("toml should parse");
+        assert_eq!(cfg.redaction.presets, vec![Preset::Jwt, Preset::Emails]);
+        assert_eq!(cfg.redaction.replacement, ReplacementStyle::Labeled);
+        let err = cfg.validate().expect_err("invalid regex should fail");
+        assert!(matches!(err, ConfigError::Invalid(message) if message.contains("broken")));
+    }
+
+    #[test]
+    fn offline_mode_does_not_require_credentials() {
+        let raw = r#"
+            [jira]
+            [jira.workspaces.default]
+            jql = "project = PROJ"
+
+            [cache]
+            db_path = "/tmp/jirafs.db"
+        "#;
+
+        let mut cfg: AppConfig = toml::from_str(raw).expect("toml should parse");
+        let err = cfg.validate().expect_err("online mode needs credentials");
+        assert!(matches!(err, ConfigError::Invalid(message) if message.contains("base_url")));
+
+        cfg.apply_overrides(&AppConfigOverrides {
+            offline: Some(true),
+            ..AppConfigOverrides::default()
+        })
+        .expect("offline config should validate");
+        assert!(cfg.offline);
+    }
+
+    #[test]
+    fn cache_encryption_needs_exactly_one_key_source() {
+        let base = r#"
+            [jira]
+            base_url = "https://example.atlassian.net"
+            email = "me@example.com"
+            api_token = "token"
+            [jira.workspaces.default]
+            jql = "project = PROJ"
+
+            [cache]
+            db_path = "/tmp/jirafs.db"
+        "#;
+        let parse = |encryption: &str| -> AppConfig {
+            toml::from_str(&format!("{base}\n[cache.encryption]\n{encryption}"))
+                .expect("toml should parse")
+        };
+
+        let cfg = parse(r#"key_file = "/etc/jirafs/cache.key""#);
+        cfg.validate().expect("one key source is valid");
+        assert_eq!(
+            cfg.cache.encryption.and_then(|e| e.key_source()),
+            Some(KeySource::File(PathBuf::from("/etc/jirafs/cache.key")))
+        );
+
+        for encryption in [
+            "",
+            "key_env = \"JIRAFS_CACHE_KEY\"\nkey_command = \"pass jirafs\"",
+            "key_env = \" \"",
+        ] {
+            let err = parse(encryption)
+                .validate()
+                .expect_err("needs exactly one key source");
+            assert!(
+                matches!(err, ConfigError::Invalid(message) if message.contains("cache.encryption"))
+            );
+        }
+    }
+
     #[test]
     fn config_example_parses() {
         let raw = include_str!("../config.example.toml");
@@ -363,6 +824,7 @@ mod tests {
         let mut cfg: AppConfig = toml::from_str(raw).expect("example config should parse");
 
         let overrides = AppConfigOverrides {
+            offline: None,
             jira_base_url: Some("https://override.atlassian.net".into()),
             jira_email: Some("override@example.com".into()),
             jira_api_token: Some("override-token".into()),
@@ -370,16 +832,29 @@ mod tests {
                 "ops".to_string(),
                 WorkspaceConfig {
                     jql: "project = OPS ORDER BY updated DESC".to_string(),
+                    inline_comments: InlineComments::Last(3),
+                    ..WorkspaceConfig::default()
                 },
             )])),
             cache_db_path: Some("/tmp/override.db".into()),
             cache_ttl_secs: Some(15),
+            cache_on_demand_deadline_ms: Some(1500),
+            cache_memory_limit_mb: Some(64),
+            cache_max_db_size_mb: Some(512),
             sync_budget: Some(250),
             sync_interval_secs: Some(30),
+            sync_reconcile_interval_secs: Some(900),
+            sync_cursor_overlap_secs: Some(300),
+            sync_allocation: Some(AllocationStrategy::RoundRobin),
+            sync_concurrency: Some(8),
             metrics_interval_secs: Some(20),
             logging_debug: Some(true),
+            render_template_path: Some("/tmp/ticket.md".into()),
+            webhooks_listen: Some("127.0.0.1:7799".into()),
+            xattrs_write_back: Some(true),
         };
 
+        cfg.webhooks.secret = Some("shared-secret".into());
         cfg.apply_overrides(&overrides)
             .expect("overrides should validate");
 
@@ -394,11 +869,67 @@ mod tests {
                 .map(|workspace| workspace.jql.as_str()),
             Some("project = OPS ORDER BY updated DESC")
         );
+        assert_eq!(
+            cfg.jira
+                .workspaces
+                .get("ops")
+                .map(|workspace| workspace.inline_comments),
+            Some(InlineComments::Last(3))
+        );
         assert_eq!(cfg.cache.db_path, "/tmp/override.db");
         assert_eq!(cfg.cache.ttl_secs, 15);
+        assert_eq!(cfg.cache.on_demand_deadline_ms, 1500);
+        assert_eq!(cfg.cache.memory_limit_mb, 64);
+        assert_eq!(cfg.cache.max_db_size_mb, Some(512));
         assert_eq!(cfg.sync.budget, 250);
         assert_eq!(cfg.sync.interval_secs, 30);
+        assert_eq!(cfg.sync.reconcile_interval_secs, 900);
+        assert_eq!(cfg.sync.cursor_overlap_secs, 300);
+        assert_eq!(cfg.sync.allocation, AllocationStrategy::RoundRobin);
+        assert_eq!(cfg.sync.concurrency, 8);
         assert_eq!(cfg.metrics.interval_secs, 20);
         assert!(cfg.logging.debug);
+        assert_eq!(cfg.render.template_path.as_deref(), Some("/tmp/ticket.md"));
+        assert_eq!(cfg.webhooks.listen.as_deref(), Some("127.0.0.1:7799"));
+        assert!(cfg.xattrs.write_back);
+    }
+
+    #[test]
+    fn validates_webhook_settings() {
+        let raw = r#"
+            [jira]
+            base_url = "https://example.atlassian.net"
+            email = "you@example.com"
+            api_token = "token"
+
+            [jira.workspaces.default]
+            jql = "project = PROJ"
+
+            [cache]
+            db_path = "/tmp/jirafs-cache.db"
+
+            [webhooks]
+            listen = "127.0.0.1:7788"
+            secret = "shared-secret"
+            disable_polling = true
+        "#;
+
+        let cfg: AppConfig = toml::from_str(raw).expect("toml should parse");
+        cfg.validate().expect("webhook config should validate");
+        assert!(cfg.webhooks.disable_polling);
+
+        let missing_secret: AppConfig =
+            toml::from_str(&raw.replace("secret = \"shared-secret\"", ""))
+                .expect("toml should parse");
+        assert!(missing_secret.validate().is_err());
+
+        let bad_listen: AppConfig =
+            toml::from_str(&raw.replace("127.0.0.1:7788", "localhost")).expect("toml should parse");
+        assert!(bad_listen.validate().is_err());
+
+        let polling_only: AppConfig =
+            toml::from_str(&raw.replace("listen = \"127.0.0.1:7788\"", ""))
+                .expect("toml should parse");
+        assert!(polling_only.validate().is_err());
     }
 }
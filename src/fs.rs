@@ -1,24 +1,36 @@
 use std::collections::HashMap;
 use std::ffi::OsStr;
 use std::io;
-use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::mpsc;
 use std::sync::Arc;
 use std::sync::MutexGuard;
-use std::time::{Duration, UNIX_EPOCH};
+use std::sync::OnceLock;
+use std::thread::JoinHandle;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use regex::Regex;
+use sha2::{Digest, Sha256};
 
 use fuser::{
-    Errno, FileAttr, FileHandle, FileType, Filesystem, FopenFlags, Generation, INodeNo,
-    OpenAccMode, OpenFlags, ReplyAttr, ReplyData, ReplyDirectory, ReplyEntry, ReplyOpen,
-    ReplyWrite, Request, TimeOrNow,
+    Errno, FileAttr, FileHandle, FileType, Filesystem, FopenFlags, Generation, INodeNo, Notifier,
+    OpenAccMode, OpenFlags, ReplyAttr, ReplyData, ReplyDirectory, ReplyEmpty, ReplyEntry,
+    ReplyOpen, ReplyWrite, ReplyXattr, Request, TimeOrNow,
 };
 
-use crate::cache::InMemoryCache;
-use crate::jira::JiraClient;
+use crate::cache::{CacheChange, InMemoryCache};
+use crate::config::WorkspaceConfig;
+use crate::jira::{parse_jira_timestamp, JiraClient};
 use crate::logging;
+use crate::on_demand::OnDemandFetcher;
 use crate::sync_state::SyncState;
-use crate::warmup::sync_issues;
+use crate::warmup::{sync_issues, SyncOptions};
 
-const TTL: Duration = Duration::from_secs(1);
+// Issue and listing changes are pushed to the kernel by
+// `spawn_kernel_invalidator`, so content attributes can be cached for long.
+const TTL: Duration = Duration::from_secs(300);
+// `.sync_meta` files change on every read and are never invalidated.
+const META_TTL: Duration = Duration::from_secs(1);
 
 const INO_SYNC_META: INodeNo = INodeNo(0x1000);
 const INO_LAST_SYNC: INodeNo = INodeNo(0x1001);
@@ -26,7 +38,24 @@ const INO_LAST_FULL_SYNC: INodeNo = INodeNo(0x1005);
 const INO_SECONDS_TO_NEXT: INodeNo = INodeNo(0x1002);
 const INO_MANUAL_REFRESH: INodeNo = INodeNo(0x1003);
 const INO_FULL_REFRESH: INodeNo = INodeNo(0x1004);
+const INO_BACKFILL_REMAINING: INodeNo = INodeNo(0x1006);
+const INO_REFRESH_ISSUES: INodeNo = INodeNo(0x1007);
+const INO_MODE: INodeNo = INodeNo(0x1008);
 const INO_WORKSPACES: INodeNo = INodeNo(0x2000);
+const INO_SEARCH: INodeNo = INodeNo(0x3000);
+
+const SEARCH_RESULT_LIMIT: usize = 50;
+// Inode scope for issues listed under `.search`; `/` keeps it apart from
+// every workspace name.
+const SEARCH_SCOPE: &str = "/search";
+
+const XATTR_STATUS: &str = "user.jira.status";
+const XATTR_ASSIGNEE: &str = "user.jira.assignee";
+const XATTR_ASSIGNEE_ID: &str = "user.jira.assignee_id";
+const XATTR_UPDATED: &str = "user.jira.updated";
+const XATTR_URL: &str = "user.jira.url";
+const XATTR_LABELS: &str = "user.jira.labels";
+const XATTR_CONTENT_SHA256: &str = "user.jira.content_sha256";
 
 #[derive(Debug, Clone, Copy)]
 enum IssueFileKind {
@@ -40,6 +69,8 @@ enum Node {
     SyncMeta,
     Workspaces,
     Workspace { name: String },
+    Search,
+    SearchQuery { query: String },
     Issue { key: String, kind: IssueFileKind },
     SyncMetaFile,
 }
@@ -49,27 +80,81 @@ struct FsState {
     nodes: HashMap<INodeNo, Node>,
 }
 
+/// Issue bytes captured at `open`, so every read through one handle sees
+/// the same version even if sync rewrites the issue meanwhile.
+#[derive(Debug, Default)]
+struct HandleTable {
+    next: AtomicU64,
+    snapshots: std::sync::Mutex<HashMap<u64, Arc<Vec<u8>>>>,
+}
+
+impl HandleTable {
+    fn insert(&self, bytes: Vec<u8>) -> FileHandle {
+        // Handle 0 stays reserved for files read without a snapshot.
+        let fh = self.next.fetch_add(1, Ordering::Relaxed) + 1;
+        self.guard().insert(fh, Arc::new(bytes));
+        FileHandle(fh)
+    }
+
+    fn get(&self, fh: FileHandle) -> Option<Arc<Vec<u8>>> {
+        self.guard().get(&fh.0).cloned()
+    }
+
+    fn remove(&self, fh: FileHandle) {
+        self.guard().remove(&fh.0);
+    }
+
+    fn guard(&self) -> MutexGuard<'_, HashMap<u64, Arc<Vec<u8>>>> {
+        match self.snapshots.lock() {
+            Ok(guard) => guard,
+            Err(poisoned) => {
+                logging::warn("recovering poisoned mutex: fs handles");
+                poisoned.into_inner()
+            }
+        }
+    }
+}
+
 #[derive(Debug)]
 pub struct JiraFuseFs {
     uid: u32,
     gid: u32,
-    workspaces: Vec<(String, String)>,
-    jira: Arc<JiraClient>,
+    workspaces: Vec<(String, WorkspaceConfig)>,
+    jira: Option<Arc<JiraClient>>,
     cache: Arc<InMemoryCache>,
-    sync_budget: usize,
+    sync_options: SyncOptions,
     sync_state: Arc<SyncState>,
     initial_sync_started: AtomicBool,
     state: std::sync::Mutex<FsState>,
+    on_demand: Option<OnDemandFetcher>,
+    handles: Arc<HandleTable>,
+    xattr_write_back: bool,
 }
 
 impl JiraFuseFs {
+    /// Lets `setxattr` on labels and assignee write the change back to Jira.
+    #[must_use]
+    pub fn with_xattr_write_back(mut self, enabled: bool) -> Self {
+        self.xattr_write_back = enabled;
+        self
+    }
+
+    /// Fetches uncached issues from Jira when they are read.
+    #[must_use]
+    pub fn with_on_demand(mut self, fetcher: OnDemandFetcher) -> Self {
+        self.on_demand = Some(fetcher);
+        self
+    }
+
+    /// Builds the filesystem. Without a Jira client it mounts offline:
+    /// everything is served from the cache and sync triggers are read-only.
     pub fn new(
         uid: u32,
         gid: u32,
-        workspaces: Vec<(String, String)>,
-        jira: Arc<JiraClient>,
+        workspaces: Vec<(String, WorkspaceConfig)>,
+        jira: Option<Arc<JiraClient>>,
         cache: Arc<InMemoryCache>,
-        sync_budget: usize,
+        sync_options: SyncOptions,
         sync_state: Arc<SyncState>,
     ) -> Self {
         let mut nodes = HashMap::new();
@@ -81,22 +166,68 @@ impl JiraFuseFs {
             workspaces,
             jira,
             cache,
-            sync_budget,
+            sync_options,
             sync_state,
             initial_sync_started: AtomicBool::new(false),
             state: std::sync::Mutex::new(FsState { nodes }),
+            on_demand: None,
+            handles: Arc::new(HandleTable::default()),
+            xattr_write_back: false,
+        }
+    }
+
+    /// Checks a `setxattr` against the writable names and the
+    /// `XATTR_CREATE`/`XATTR_REPLACE` flags, returning the client to write
+    /// it with.
+    fn xattr_writer(
+        &self,
+        key: &str,
+        kind: IssueFileKind,
+        name: &str,
+        flags: i32,
+    ) -> Result<Arc<JiraClient>, Errno> {
+        if name != XATTR_LABELS && name != XATTR_ASSIGNEE_ID {
+            return Err(Errno::ENOTSUP);
+        }
+        let exists = self
+            .issue_xattrs(key, kind)
+            .iter()
+            .any(|(xattr, _)| *xattr == name);
+        if flags & libc::XATTR_CREATE != 0 && exists {
+            return Err(Errno::EEXIST);
+        }
+        if flags & libc::XATTR_REPLACE != 0 && !exists {
+            return Err(Errno::NO_XATTR);
         }
+        self.jira
+            .clone()
+            .filter(|_| self.xattr_write_back)
+            .ok_or(Errno::EROFS)
+    }
+
+    /// Snapshots an issue file into a new handle.
+    ///
+    /// Handles must be opened with `FOPEN_DIRECT_IO`: otherwise the kernel
+    /// serves them from the page cache shared by every handle on the inode and
+    /// clamps reads at the current size, not the snapshot's.
+    fn open_issue_snapshot(&self, key: &str, kind: IssueFileKind) -> (FileHandle, FopenFlags) {
+        let fh = self
+            .handles
+            .insert(issue_file_bytes(&self.cache, key, kind));
+        (fh, FopenFlags::FOPEN_DIRECT_IO)
     }
 
     fn spawn_initial_sync(&self) {
         if self.initial_sync_started.swap(true, Ordering::Relaxed) {
             return;
         }
+        let Some(jira) = self.jira.clone() else {
+            return;
+        };
 
-        let jira = Arc::clone(&self.jira);
         let cache = Arc::clone(&self.cache);
         let workspaces = self.workspaces.clone();
-        let sync_budget = self.sync_budget;
+        let sync_options = self.sync_options.clone();
         let sync_state = Arc::clone(&self.sync_state);
 
         std::thread::spawn(move || {
@@ -105,8 +236,18 @@ impl JiraFuseFs {
             }
 
             logging::info("starting initial sync after mount...");
-            let sync_result = sync_issues(&jira, &cache, &workspaces, sync_budget, false);
+            let sync_result = sync_issues(
+                &jira,
+                &cache,
+                &sync_state,
+                &workspaces,
+                None,
+                &sync_options,
+                false,
+            );
 
+            let names: Vec<String> = workspaces.iter().map(|(name, _)| name.clone()).collect();
+            sync_state.mark_workspaces_synced(&names);
             sync_state.mark_sync_complete();
             sync_state.mark_sync_end();
 
@@ -152,6 +293,43 @@ impl JiraFuseFs {
         }
     }
 
+    /// Directory attributes for a workspace, dated by its last listing change.
+    fn workspace_attr(&self, ino: INodeNo, workspace: &str) -> FileAttr {
+        let mut attr = self.dir_attr(ino);
+        if let Some(modified) = self.cache.workspace_modified(workspace) {
+            attr.atime = modified;
+            attr.mtime = modified;
+            attr.ctime = modified;
+        }
+        attr
+    }
+
+    /// File attributes for an issue file, dated by its Jira timestamps:
+    /// `updated` for `KEY.md`, the latest comment for `KEY.comments.md`,
+    /// and `created` as the birth time of both.
+    fn issue_attr(&self, ino: INodeNo, issue_key: &str, kind: IssueFileKind) -> FileAttr {
+        let size = self.issue_sidecar_size(issue_key, kind);
+        let mut attr = self.file_attr(ino, size, false);
+        let Some(timestamps) = self.cache.issue_timestamps(issue_key) else {
+            return attr;
+        };
+        let created = jira_time(timestamps.created.as_deref());
+        let modified = match kind {
+            IssueFileKind::Main => jira_time(timestamps.updated.as_deref()),
+            IssueFileKind::CommentsMarkdown => jira_time(timestamps.comments_updated.as_deref()),
+        }
+        .or(created);
+        if let Some(modified) = modified {
+            attr.atime = modified;
+            attr.mtime = modified;
+            attr.ctime = modified;
+        }
+        if let Some(created) = created {
+            attr.crtime = created;
+        }
+        attr
+    }
+
     fn file_attr(&self, ino: INodeNo, size: u64, writable: bool) -> FileAttr {
         FileAttr {
             ino,
@@ -190,12 +368,10 @@ impl JiraFuseFs {
         if ino == INO_WORKSPACES {
             return Some(Node::Workspaces);
         }
-        if ino == INO_LAST_SYNC
-            || ino == INO_LAST_FULL_SYNC
-            || ino == INO_SECONDS_TO_NEXT
-            || ino == INO_MANUAL_REFRESH
-            || ino == INO_FULL_REFRESH
-        {
+        if ino == INO_SEARCH {
+            return Some(Node::Search);
+        }
+        if is_sync_meta_file(ino) {
             return Some(Node::SyncMetaFile);
         }
 
@@ -216,6 +392,15 @@ impl JiraFuseFs {
         }
     }
 
+    /// Issue keys matching a `.search` query, best first.
+    fn search_results(&self, query: &str) -> Vec<String> {
+        self.cache
+            .search_issues(query, SEARCH_RESULT_LIMIT)
+            .into_iter()
+            .map(|hit| hit.key)
+            .collect()
+    }
+
     fn issue_exists_in_workspace(&self, workspace: &str, issue_key: &str) -> Result<bool, Errno> {
         let issues = self.workspace_issues(workspace)?;
         Ok(issues.iter().any(|i| i.key == issue_key))
@@ -229,16 +414,41 @@ impl JiraFuseFs {
         Ok(Vec::new())
     }
 
-    fn issue_bytes(&self, issue_key: &str) -> Result<Vec<u8>, Errno> {
-        self.cache
-            .get_issue_markdown_stale_safe(issue_key, || Err(Errno::EAGAIN))
-            .or_else(|_| {
-                Ok(format!(
-                    "# {}\n\nNot yet available in local cache. Wait for sync interval or trigger manual refresh via `.sync_meta/manual_refresh`.\n",
-                    issue_key
-                )
-                .into_bytes())
-            })
+    /// Whether sync or an earlier on-demand fetch has stored this issue.
+    fn is_issue_cached(&self, issue_key: &str) -> bool {
+        self.cache.cached_issue_len(issue_key).is_some()
+            || self.cache.persistent_issue_len(issue_key).is_some()
+    }
+
+    /// Extended attributes for an issue file, in listing order. Fields Jira
+    /// left empty are omitted.
+    fn issue_xattrs(&self, issue_key: &str, kind: IssueFileKind) -> Vec<(&'static str, Vec<u8>)> {
+        let mut xattrs = Vec::new();
+        if let Some(attributes) = self.cache.issue_attributes(issue_key) {
+            if let Some(status) = attributes.status {
+                xattrs.push((XATTR_STATUS, status.into_bytes()));
+            }
+            if let Some(assignee) = attributes.assignee {
+                xattrs.push((XATTR_ASSIGNEE, assignee.into_bytes()));
+            }
+            if let Some(assignee_id) = attributes.assignee_id {
+                xattrs.push((XATTR_ASSIGNEE_ID, assignee_id.into_bytes()));
+            }
+            if let Some(updated) = self
+                .cache
+                .issue_timestamps(issue_key)
+                .and_then(|timestamps| timestamps.updated)
+            {
+                xattrs.push((XATTR_UPDATED, updated.into_bytes()));
+            }
+            xattrs.push((XATTR_URL, attributes.url.into_bytes()));
+            xattrs.push((XATTR_LABELS, attributes.labels.join(",").into_bytes()));
+        }
+        if self.is_issue_cached(issue_key) {
+            let digest = Sha256::digest(issue_file_bytes(&self.cache, issue_key, kind));
+            xattrs.push((XATTR_CONTENT_SHA256, hex::encode(digest).into_bytes()));
+        }
+        xattrs
     }
 
     fn issue_main_size(&self, issue_key: &str) -> u64 {
@@ -248,17 +458,6 @@ impl JiraFuseFs {
             .unwrap_or(0)
     }
 
-    fn issue_comments_markdown_bytes(&self, issue_key: &str) -> Vec<u8> {
-        if let Some(bytes) = self.cache.persistent_comments_md(issue_key) {
-            return bytes;
-        }
-        format!(
-            "# {} comments\n\nComments sidecar is only populated during sync.\n",
-            issue_key
-        )
-        .into_bytes()
-    }
-
     fn issue_sidecar_size(&self, issue_key: &str, kind: IssueFileKind) -> u64 {
         match kind {
             IssueFileKind::Main => self.issue_main_size(issue_key),
@@ -269,7 +468,30 @@ impl JiraFuseFs {
         }
     }
 
+    fn is_offline(&self) -> bool {
+        self.jira.is_none()
+    }
+
+    fn meta_file_writable(&self, ino: INodeNo) -> bool {
+        is_writable_meta_file(ino) && !self.is_offline()
+    }
+
     fn sync_meta_file_content(&self, ino: INodeNo) -> Vec<u8> {
+        if ino == INO_MODE {
+            return if self.is_offline() {
+                b"offline\n".to_vec()
+            } else {
+                b"online\n".to_vec()
+            };
+        }
+        if self.is_offline() {
+            if ino == INO_LAST_SYNC || ino == INO_LAST_FULL_SYNC || ino == INO_SECONDS_TO_NEXT {
+                return b"offline; serving the persistent cache without syncing\n".to_vec();
+            }
+            if is_writable_meta_file(ino) {
+                return b"offline; sync triggers are disabled\n".to_vec();
+            }
+        }
         if ino == INO_LAST_SYNC {
             if let Some(last) = self.sync_state.last_sync() {
                 let secs = last.elapsed().as_secs();
@@ -304,6 +526,16 @@ impl JiraFuseFs {
                 return b"write '1' or 'true' to trigger full upsert sync\n".to_vec();
             }
         }
+        if ino == INO_BACKFILL_REMAINING {
+            return format!("{}\n", self.cache.backfill_remaining()).into_bytes();
+        }
+        if ino == INO_REFRESH_ISSUES {
+            let pending = self.sync_state.pending_issue_refreshes();
+            if pending.is_empty() {
+                return b"write issue keys, one per line, to refresh them now\n".to_vec();
+            }
+            return format!("refresh queued: {}\n", pending.join(", ")).into_bytes();
+        }
         b"unknown\n".to_vec()
     }
 }
@@ -324,15 +556,64 @@ impl Filesystem for JiraFuseFs {
                 reply.entry(&TTL, &self.dir_attr(INO_WORKSPACES), Generation(0));
                 return;
             }
+            if name == OsStr::new(".search") {
+                reply.entry(&TTL, &self.dir_attr(INO_SEARCH), Generation(0));
+                return;
+            }
             reply.error(Errno::ENOENT);
             return;
         }
 
+        if parent == INO_SEARCH {
+            let query = decode_search_name(&name.to_string_lossy());
+            if query.trim().is_empty() {
+                reply.error(Errno::ENOENT);
+                return;
+            }
+            let ino = inode_for_search(&query);
+            self.upsert_node(ino, Node::SearchQuery { query });
+            // Results change with every sync, so the entry is not cached.
+            reply.entry(&Duration::ZERO, &self.dir_attr(ino), Generation(0));
+            return;
+        }
+
+        if let Some(Node::SearchQuery { query }) = self.node_for_inode(parent) {
+            let issue_key = match name.to_str().and_then(parse_issue_file_name) {
+                Some((issue_key, IssueFileKind::Main)) => issue_key,
+                _ => {
+                    reply.error(Errno::ENOENT);
+                    return;
+                }
+            };
+            if !self
+                .search_results(&query)
+                .iter()
+                .any(|key| key == issue_key)
+            {
+                reply.error(Errno::ENOENT);
+                return;
+            }
+            let ino = inode_for_issue_kind(SEARCH_SCOPE, issue_key, IssueFileKind::Main);
+            self.upsert_node(
+                ino,
+                Node::Issue {
+                    key: issue_key.to_string(),
+                    kind: IssueFileKind::Main,
+                },
+            );
+            reply.entry(
+                &TTL,
+                &self.issue_attr(ino, issue_key, IssueFileKind::Main),
+                Generation(0),
+            );
+            return;
+        }
+
         if parent == INO_SYNC_META {
             if name == OsStr::new("last_sync") {
                 let content = self.sync_meta_file_content(INO_LAST_SYNC);
                 reply.entry(
-                    &TTL,
+                    &META_TTL,
                     &self.file_attr(INO_LAST_SYNC, content.len() as u64, false),
                     Generation(0),
                 );
@@ -341,7 +622,7 @@ impl Filesystem for JiraFuseFs {
             if name == OsStr::new("last_full_sync") {
                 let content = self.sync_meta_file_content(INO_LAST_FULL_SYNC);
                 reply.entry(
-                    &TTL,
+                    &META_TTL,
                     &self.file_attr(INO_LAST_FULL_SYNC, content.len() as u64, false),
                     Generation(0),
                 );
@@ -350,7 +631,7 @@ impl Filesystem for JiraFuseFs {
             if name == OsStr::new("seconds_to_next_sync") {
                 let content = self.sync_meta_file_content(INO_SECONDS_TO_NEXT);
                 reply.entry(
-                    &TTL,
+                    &META_TTL,
                     &self.file_attr(INO_SECONDS_TO_NEXT, content.len() as u64, false),
                     Generation(0),
                 );
@@ -359,8 +640,12 @@ impl Filesystem for JiraFuseFs {
             if name == OsStr::new("manual_refresh") {
                 let content = self.sync_meta_file_content(INO_MANUAL_REFRESH);
                 reply.entry(
-                    &TTL,
-                    &self.file_attr(INO_MANUAL_REFRESH, content.len() as u64, true),
+                    &META_TTL,
+                    &self.file_attr(
+                        INO_MANUAL_REFRESH,
+                        content.len() as u64,
+                        self.meta_file_writable(INO_MANUAL_REFRESH),
+                    ),
                     Generation(0),
                 );
                 return;
@@ -368,8 +653,43 @@ impl Filesystem for JiraFuseFs {
             if name == OsStr::new("full_refresh") {
                 let content = self.sync_meta_file_content(INO_FULL_REFRESH);
                 reply.entry(
-                    &TTL,
-                    &self.file_attr(INO_FULL_REFRESH, content.len() as u64, true),
+                    &META_TTL,
+                    &self.file_attr(
+                        INO_FULL_REFRESH,
+                        content.len() as u64,
+                        self.meta_file_writable(INO_FULL_REFRESH),
+                    ),
+                    Generation(0),
+                );
+                return;
+            }
+            if name == OsStr::new("backfill_remaining") {
+                let content = self.sync_meta_file_content(INO_BACKFILL_REMAINING);
+                reply.entry(
+                    &META_TTL,
+                    &self.file_attr(INO_BACKFILL_REMAINING, content.len() as u64, false),
+                    Generation(0),
+                );
+                return;
+            }
+            if name == OsStr::new("mode") {
+                let content = self.sync_meta_file_content(INO_MODE);
+                reply.entry(
+                    &META_TTL,
+                    &self.file_attr(INO_MODE, content.len() as u64, false),
+                    Generation(0),
+                );
+                return;
+            }
+            if name == OsStr::new("refresh_issues") {
+                let content = self.sync_meta_file_content(INO_REFRESH_ISSUES);
+                reply.entry(
+                    &META_TTL,
+                    &self.file_attr(
+                        INO_REFRESH_ISSUES,
+                        content.len() as u64,
+                        self.meta_file_writable(INO_REFRESH_ISSUES),
+                    ),
                     Generation(0),
                 );
                 return;
@@ -391,7 +711,7 @@ impl Filesystem for JiraFuseFs {
                         name: workspace.to_string(),
                     },
                 );
-                reply.entry(&TTL, &self.dir_attr(ino), Generation(0));
+                reply.entry(&TTL, &self.workspace_attr(ino, workspace), Generation(0));
                 return;
             }
             reply.error(Errno::ENOENT);
@@ -403,16 +723,7 @@ impl Filesystem for JiraFuseFs {
             return;
         };
 
-        let Some(file_name) = name.to_str() else {
-            reply.error(Errno::ENOENT);
-            return;
-        };
-
-        let (issue_key, kind) = if let Some(value) = file_name.strip_suffix(".comments.md") {
-            (value, IssueFileKind::CommentsMarkdown)
-        } else if let Some(value) = file_name.strip_suffix(".md") {
-            (value, IssueFileKind::Main)
-        } else {
+        let Some((issue_key, kind)) = name.to_str().and_then(parse_issue_file_name) else {
             reply.error(Errno::ENOENT);
             return;
         };
@@ -427,34 +738,33 @@ impl Filesystem for JiraFuseFs {
                         kind,
                     },
                 );
-                let size = self.issue_sidecar_size(issue_key, kind);
-                reply.entry(&TTL, &self.file_attr(ino, size, false), Generation(0));
+                // Uncached issues are fetched on open, not here: `ls -l` and
+                // shell completion look up every entry without reading any.
+                reply.entry(&TTL, &self.issue_attr(ino, issue_key, kind), Generation(0));
             }
             Ok(false) => reply.error(Errno::ENOENT),
             Err(err) => reply.error(err),
         }
     }
 
-    fn getattr(&self, _req: &Request, ino: INodeNo, _fh: Option<FileHandle>, reply: ReplyAttr) {
+    fn getattr(&self, _req: &Request, ino: INodeNo, fh: Option<FileHandle>, reply: ReplyAttr) {
         if ino == INodeNo::ROOT {
             reply.attr(&TTL, &self.dir_attr(ino));
             return;
         }
 
-        if ino == INO_SYNC_META || ino == INO_WORKSPACES {
+        if ino == INO_SYNC_META || ino == INO_WORKSPACES || ino == INO_SEARCH {
             reply.attr(&TTL, &self.dir_attr(ino));
             return;
         }
 
-        if ino == INO_LAST_SYNC
-            || ino == INO_LAST_FULL_SYNC
-            || ino == INO_SECONDS_TO_NEXT
-            || ino == INO_MANUAL_REFRESH
-            || ino == INO_FULL_REFRESH
-        {
+        if is_sync_meta_file(ino) {
             let content = self.sync_meta_file_content(ino);
-            let writable = ino == INO_MANUAL_REFRESH || ino == INO_FULL_REFRESH;
-            reply.attr(&TTL, &self.file_attr(ino, content.len() as u64, writable));
+            let writable = self.meta_file_writable(ino);
+            reply.attr(
+                &META_TTL,
+                &self.file_attr(ino, content.len() as u64, writable),
+            );
             return;
         }
 
@@ -469,16 +779,23 @@ impl Filesystem for JiraFuseFs {
                     name: workspace.clone(),
                 },
             );
-            reply.attr(&TTL, &self.dir_attr(ino));
+            reply.attr(&TTL, &self.workspace_attr(ino, workspace));
             return;
         }
 
         match self.node_for_inode(ino) {
             Some(Node::Issue { key, kind }) => {
-                let size = self.issue_sidecar_size(&key, kind);
-                reply.attr(&TTL, &self.file_attr(ino, size, false));
+                let mut attr = self.issue_attr(ino, &key, kind);
+                // An open handle keeps reporting the size of what it will read.
+                if let Some(snapshot) = fh.and_then(|fh| self.handles.get(fh)) {
+                    attr.size = snapshot.len() as u64;
+                }
+                reply.attr(&TTL, &attr);
+            }
+            Some(Node::Workspace { name }) => {
+                reply.attr(&TTL, &self.workspace_attr(ino, &name));
             }
-            Some(Node::Workspace { .. }) => reply.attr(&TTL, &self.dir_attr(ino)),
+            Some(Node::SearchQuery { .. }) => reply.attr(&Duration::ZERO, &self.dir_attr(ino)),
             _ => reply.error(Errno::ENOENT),
         }
     }
@@ -501,6 +818,7 @@ impl Filesystem for JiraFuseFs {
                     FileType::Directory,
                     "workspaces".to_string(),
                 ),
+                (INO_SEARCH, FileType::Directory, ".search".to_string()),
             ];
 
             for (idx, (entry_ino, kind, name)) in entries.iter().enumerate().skip(offset as usize) {
@@ -541,6 +859,17 @@ impl Filesystem for JiraFuseFs {
                     FileType::RegularFile,
                     "full_refresh".to_string(),
                 ),
+                (
+                    INO_BACKFILL_REMAINING,
+                    FileType::RegularFile,
+                    "backfill_remaining".to_string(),
+                ),
+                (
+                    INO_REFRESH_ISSUES,
+                    FileType::RegularFile,
+                    "refresh_issues".to_string(),
+                ),
+                (INO_MODE, FileType::RegularFile, "mode".to_string()),
             ];
 
             for (idx, (entry_ino, kind, name)) in entries.iter().enumerate().skip(offset as usize) {
@@ -578,6 +907,47 @@ impl Filesystem for JiraFuseFs {
             return;
         }
 
+        if ino == INO_SEARCH {
+            // Queries are looked up by name and never listed.
+            let entries = [(ino, "."), (INodeNo::ROOT, "..")];
+            for (idx, (entry_ino, name)) in entries.iter().enumerate().skip(offset as usize) {
+                if reply.add(*entry_ino, (idx + 1) as u64, FileType::Directory, name) {
+                    break;
+                }
+            }
+            reply.ok();
+            return;
+        }
+
+        if let Some(Node::SearchQuery { query }) = self.node_for_inode(ino) {
+            let mut entries: Vec<(INodeNo, FileType, String)> = vec![
+                (ino, FileType::Directory, ".".to_string()),
+                (INO_SEARCH, FileType::Directory, "..".to_string()),
+            ];
+            for key in self.search_results(&query) {
+                let issue_ino = inode_for_issue_kind(SEARCH_SCOPE, &key, IssueFileKind::Main);
+                entries.push((
+                    issue_ino,
+                    FileType::RegularFile,
+                    issue_file_name(&key, IssueFileKind::Main),
+                ));
+                self.upsert_node(
+                    issue_ino,
+                    Node::Issue {
+                        key,
+                        kind: IssueFileKind::Main,
+                    },
+                );
+            }
+            for (idx, (entry_ino, kind, name)) in entries.iter().enumerate().skip(offset as usize) {
+                if reply.add(*entry_ino, (idx + 1) as u64, *kind, name) {
+                    break;
+                }
+            }
+            reply.ok();
+            return;
+        }
+
         let Some(workspace) = self.workspace_for_inode(ino) else {
             reply.error(Errno::ENOENT);
             return;
@@ -617,12 +987,12 @@ impl Filesystem for JiraFuseFs {
             entries.push((
                 issue_ino,
                 FileType::RegularFile,
-                format!("{}.md", issue.key),
+                issue_file_name(&issue.key, IssueFileKind::Main),
             ));
             entries.push((
                 comments_md_ino,
                 FileType::RegularFile,
-                format!("{}.comments.md", issue.key),
+                issue_file_name(&issue.key, IssueFileKind::CommentsMarkdown),
             ));
         }
 
@@ -635,7 +1005,7 @@ impl Filesystem for JiraFuseFs {
     }
 
     fn open(&self, _req: &Request, ino: INodeNo, flags: OpenFlags, reply: ReplyOpen) {
-        let is_writable_file = ino == INO_MANUAL_REFRESH || ino == INO_FULL_REFRESH;
+        let is_writable_file = self.meta_file_writable(ino);
 
         if flags.acc_mode() != OpenAccMode::O_RDONLY && !is_writable_file {
             reply.error(Errno::EROFS);
@@ -643,10 +1013,28 @@ impl Filesystem for JiraFuseFs {
         }
 
         match self.node_for_inode(ino) {
-            Some(Node::Issue { .. }) | Some(Node::SyncMetaFile) => {
-                reply.opened(FileHandle(0), FopenFlags::empty())
+            Some(Node::Issue { key, kind }) => {
+                if let Some(fetcher) = &self.on_demand {
+                    if !self.is_issue_cached(&key) {
+                        // Uncached issues report size 0, so read them past it.
+                        // Reply from a waiter thread so the session thread keeps serving.
+                        let cache = Arc::clone(&self.cache);
+                        let handles = Arc::clone(&self.handles);
+                        let issue_key = key.clone();
+                        fetcher.fetch_then(&key, move |_| {
+                            let fh = handles.insert(issue_file_bytes(&cache, &issue_key, kind));
+                            reply.opened(fh, FopenFlags::FOPEN_DIRECT_IO);
+                        });
+                        return;
+                    }
+                }
+                let (fh, flags) = self.open_issue_snapshot(&key, kind);
+                reply.opened(fh, flags)
             }
+            Some(Node::SyncMetaFile) => reply.opened(FileHandle(0), FopenFlags::empty()),
             Some(Node::Workspace { .. })
+            | Some(Node::Search)
+            | Some(Node::SearchQuery { .. })
             | Some(Node::SyncMeta)
             | Some(Node::Workspaces)
             | Some(Node::Root) => reply.error(Errno::EISDIR),
@@ -658,19 +1046,19 @@ impl Filesystem for JiraFuseFs {
         &self,
         _req: &Request,
         ino: INodeNo,
-        _fh: FileHandle,
+        fh: FileHandle,
         offset: u64,
         size: u32,
         _flags: OpenFlags,
         _lock_owner: Option<fuser::LockOwner>,
         reply: ReplyData,
     ) {
-        if ino == INO_LAST_SYNC
-            || ino == INO_LAST_FULL_SYNC
-            || ino == INO_SECONDS_TO_NEXT
-            || ino == INO_MANUAL_REFRESH
-            || ino == INO_FULL_REFRESH
-        {
+        if let Some(snapshot) = self.handles.get(fh) {
+            reply_slice(reply, &snapshot, offset, size);
+            return;
+        }
+
+        if is_sync_meta_file(ino) {
             let data = self.sync_meta_file_content(ino);
             let start = offset as usize;
             if start >= data.len() {
@@ -687,26 +1075,95 @@ impl Filesystem for JiraFuseFs {
             return;
         };
 
-        let data = match kind {
-            IssueFileKind::Main => self.issue_bytes(&key),
-            IssueFileKind::CommentsMarkdown => Ok(self.issue_comments_markdown_bytes(&key)),
+        let data = issue_file_bytes(&self.cache, &key, kind);
+        reply_slice(reply, &data, offset, size);
+    }
+
+    fn getxattr(&self, _req: &Request, ino: INodeNo, name: &OsStr, size: u32, reply: ReplyXattr) {
+        let Some(Node::Issue { key, kind }) = self.node_for_inode(ino) else {
+            reply.error(Errno::NO_XATTR);
+            return;
         };
+        match self
+            .issue_xattrs(&key, kind)
+            .into_iter()
+            .find(|(xattr, _)| name == OsStr::new(xattr))
+        {
+            Some((_, value)) => reply_xattr(reply, size, &value),
+            None => reply.error(Errno::NO_XATTR),
+        }
+    }
 
-        let data = match data {
-            Ok(bytes) => bytes,
+    fn listxattr(&self, _req: &Request, ino: INodeNo, size: u32, reply: ReplyXattr) {
+        let names: Vec<u8> = match self.node_for_inode(ino) {
+            Some(Node::Issue { key, kind }) => self
+                .issue_xattrs(&key, kind)
+                .into_iter()
+                .flat_map(|(name, _)| name.bytes().chain([0]))
+                .collect(),
+            _ => Vec::new(),
+        };
+        reply_xattr(reply, size, &names);
+    }
+
+    fn setxattr(
+        &self,
+        _req: &Request,
+        ino: INodeNo,
+        name: &OsStr,
+        value: &[u8],
+        flags: i32,
+        _position: u32,
+        reply: ReplyEmpty,
+    ) {
+        let Some(Node::Issue { key, kind }) = self.node_for_inode(ino) else {
+            reply.error(Errno::ENOTSUP);
+            return;
+        };
+        let name = name.to_string_lossy().into_owned();
+        let jira = match self.xattr_writer(&key, kind, &name, flags) {
+            Ok(jira) => jira,
             Err(err) => {
                 reply.error(err);
                 return;
             }
         };
 
-        let start = offset as usize;
-        if start >= data.len() {
-            reply.data(&[]);
-            return;
-        }
-        let end = start.saturating_add(size as usize).min(data.len());
-        reply.data(&data[start..end]);
+        let value = String::from_utf8_lossy(value).trim().to_string();
+        let sync_state = Arc::clone(&self.sync_state);
+        // Jira round-trips run off the session thread.
+        std::thread::spawn(move || {
+            let result = if name == XATTR_LABELS {
+                jira.set_issue_labels(&key, &parse_labels(&value))
+            } else {
+                jira.set_issue_assignee(&key, Some(value.as_str()).filter(|id| !id.is_empty()))
+            };
+            match result {
+                Ok(()) => {
+                    logging::info(format!("wrote {} back to Jira for {}", name, key));
+                    sync_state.request_issue_refresh([key]);
+                    reply.ok();
+                }
+                Err(err) => {
+                    logging::warn(format!("failed to write {} for {}: {}", name, key, err));
+                    reply.error(Errno::EIO);
+                }
+            }
+        });
+    }
+
+    fn release(
+        &self,
+        _req: &Request,
+        _ino: INodeNo,
+        fh: FileHandle,
+        _flags: OpenFlags,
+        _lock_owner: Option<fuser::LockOwner>,
+        _flush: bool,
+        reply: ReplyEmpty,
+    ) {
+        self.handles.remove(fh);
+        reply.ok();
     }
 
     fn write(
@@ -721,11 +1178,24 @@ impl Filesystem for JiraFuseFs {
         _lock_owner: Option<fuser::LockOwner>,
         reply: ReplyWrite,
     ) {
-        if ino != INO_MANUAL_REFRESH && ino != INO_FULL_REFRESH {
+        if !self.meta_file_writable(ino) {
             reply.error(Errno::EROFS);
             return;
         }
 
+        if ino == INO_REFRESH_ISSUES {
+            let keys = parse_issue_keys(&String::from_utf8_lossy(data));
+            if !keys.is_empty() {
+                logging::info(format!(
+                    "issue refresh requested via .sync_meta/refresh_issues: {}",
+                    keys.join(", ")
+                ));
+                self.sync_state.request_issue_refresh(keys);
+            }
+            reply.written(data.len() as u32);
+            return;
+        }
+
         if offset != 0 {
             reply.error(Errno::EINVAL);
             return;
@@ -756,7 +1226,7 @@ impl Filesystem for JiraFuseFs {
         _gid: Option<u32>,
         _size: Option<u64>,
         _atime: Option<TimeOrNow>,
-        _mtime: Option<TimeOrNow>,
+        mtime: Option<TimeOrNow>,
         _ctime: Option<std::time::SystemTime>,
         _fh: Option<FileHandle>,
         _crtime: Option<std::time::SystemTime>,
@@ -765,15 +1235,228 @@ impl Filesystem for JiraFuseFs {
         _flags: Option<fuser::BsdFileFlags>,
         reply: ReplyAttr,
     ) {
-        if ino == INO_MANUAL_REFRESH || ino == INO_FULL_REFRESH {
+        if self.meta_file_writable(ino) {
             let content = self.sync_meta_file_content(ino);
-            reply.attr(&TTL, &self.file_attr(ino, content.len() as u64, true));
+            reply.attr(&META_TTL, &self.file_attr(ino, content.len() as u64, true));
+            return;
+        }
+
+        // `touch` on an issue file asks for a fresh copy of just that issue.
+        if let (Some(_), Some(Node::Issue { key, kind }), false) =
+            (mtime, self.node_for_inode(ino), self.is_offline())
+        {
+            logging::info(format!("issue refresh requested via touch: {}", key));
+            self.sync_state.request_issue_refresh([key.clone()]);
+            reply.attr(&TTL, &self.issue_attr(ino, &key, kind));
             return;
         }
         reply.error(Errno::EROFS);
     }
 }
 
+fn issue_file_bytes(cache: &InMemoryCache, issue_key: &str, kind: IssueFileKind) -> Vec<u8> {
+    match kind {
+        IssueFileKind::Main => cache
+            .get_issue_markdown_stale_safe(issue_key, || Err(()))
+            .unwrap_or_else(|_| {
+                format!(
+                    "# {}\n\nNot yet available in local cache and Jira did not answer in time. Read again shortly, trigger manual refresh via `.sync_meta/manual_refresh`, or `touch` this file.\n",
+                    issue_key
+                )
+                .into_bytes()
+            }),
+        IssueFileKind::CommentsMarkdown => {
+            cache.persistent_comments_md(issue_key).unwrap_or_else(|| {
+                format!(
+                    "# {} comments\n\nComments sidecar is only populated during sync.\n",
+                    issue_key
+                )
+                .into_bytes()
+            })
+        }
+    }
+}
+
+fn reply_xattr(reply: ReplyXattr, size: u32, value: &[u8]) {
+    if size == 0 {
+        reply.size(value.len() as u32);
+    } else if value.len() > size as usize {
+        reply.error(Errno::ERANGE);
+    } else {
+        reply.data(value);
+    }
+}
+
+/// Splits a `user.jira.labels` value on commas and whitespace.
+fn parse_labels(value: &str) -> Vec<String> {
+    value
+        .split(|c: char| c == ',' || c.is_whitespace())
+        .filter(|label| !label.is_empty())
+        .map(ToString::to_string)
+        .collect()
+}
+
+fn jira_time(raw: Option<&str>) -> Option<SystemTime> {
+    raw.and_then(parse_jira_timestamp).map(SystemTime::from)
+}
+
+fn reply_slice(reply: ReplyData, data: &[u8], offset: u64, size: u32) {
+    reply.data(slice_at(data, offset, size));
+}
+
+fn slice_at(data: &[u8], offset: u64, size: u32) -> &[u8] {
+    let start = offset as usize;
+    if start >= data.len() {
+        return &[];
+    }
+    let end = start.saturating_add(size as usize).min(data.len());
+    &data[start..end]
+}
+
+/// Forwards cache changes to the kernel so cached pages, attributes and
+/// directory entries are dropped as soon as sync rewrites them.
+///
+/// Runs until every sender of `changes` is gone.
+pub fn spawn_kernel_invalidator(
+    notifier: Notifier,
+    workspaces: Vec<String>,
+    changes: mpsc::Receiver<CacheChange>,
+) -> JoinHandle<()> {
+    std::thread::spawn(move || {
+        for change in changes {
+            match change {
+                CacheChange::Issues(keys) => {
+                    for workspace in &workspaces {
+                        for key in &keys {
+                            for kind in [IssueFileKind::Main, IssueFileKind::CommentsMarkdown] {
+                                let ino = inode_for_issue_kind(workspace, key, kind);
+                                report_inval(notifier.inval_inode(ino, 0, 0));
+                            }
+                        }
+                    }
+                    for key in &keys {
+                        let ino = inode_for_issue_kind(SEARCH_SCOPE, key, IssueFileKind::Main);
+                        report_inval(notifier.inval_inode(ino, 0, 0));
+                    }
+                }
+                CacheChange::Listing {
+                    workspace,
+                    added,
+                    removed,
+                } => {
+                    if !workspaces.contains(&workspace) {
+                        continue;
+                    }
+                    let parent = inode_for_workspace(&workspace);
+                    report_inval(notifier.inval_inode(parent, 0, 0));
+                    for key in &added {
+                        for kind in [IssueFileKind::Main, IssueFileKind::CommentsMarkdown] {
+                            let name = issue_file_name(key, kind);
+                            report_inval(notifier.inval_entry(parent, OsStr::new(&name)));
+                        }
+                    }
+                    for key in &removed {
+                        for kind in [IssueFileKind::Main, IssueFileKind::CommentsMarkdown] {
+                            let name = issue_file_name(key, kind);
+                            let child = inode_for_issue_kind(&workspace, key, kind);
+                            report_inval(notifier.delete(parent, child, OsStr::new(&name)));
+                        }
+                    }
+                }
+            }
+        }
+    })
+}
+
+fn report_inval(result: io::Result<()>) {
+    if let Err(err) = result {
+        logging::debug(format!("kernel cache invalidation failed: {}", err));
+    }
+}
+
+fn issue_file_name(issue_key: &str, kind: IssueFileKind) -> String {
+    match kind {
+        IssueFileKind::Main => format!("{}.md", issue_key),
+        IssueFileKind::CommentsMarkdown => format!("{}.comments.md", issue_key),
+    }
+}
+
+fn parse_issue_file_name(file_name: &str) -> Option<(&str, IssueFileKind)> {
+    if let Some(issue_key) = file_name.strip_suffix(".comments.md") {
+        Some((issue_key, IssueFileKind::CommentsMarkdown))
+    } else {
+        file_name
+            .strip_suffix(".md")
+            .map(|issue_key| (issue_key, IssueFileKind::Main))
+    }
+}
+
+/// Decodes `%XX` escapes in a `.search` directory name; anything else,
+/// including malformed escapes, is kept as typed.
+fn decode_search_name(name: &str) -> String {
+    let bytes = name.as_bytes();
+    let mut decoded = Vec::with_capacity(bytes.len());
+    let mut idx = 0;
+    while idx < bytes.len() {
+        let escaped = (bytes[idx] == b'%')
+            .then(|| bytes.get(idx + 1..idx + 3))
+            .flatten()
+            .and_then(|hex| u8::from_str_radix(std::str::from_utf8(hex).ok()?, 16).ok());
+        match escaped {
+            Some(byte) => {
+                decoded.push(byte);
+                idx += 3;
+            }
+            None => {
+                decoded.push(bytes[idx]);
+                idx += 1;
+            }
+        }
+    }
+    String::from_utf8_lossy(&decoded).into_owned()
+}
+
+fn is_sync_meta_file(ino: INodeNo) -> bool {
+    ino == INO_LAST_SYNC
+        || ino == INO_LAST_FULL_SYNC
+        || ino == INO_SECONDS_TO_NEXT
+        || ino == INO_MANUAL_REFRESH
+        || ino == INO_FULL_REFRESH
+        || ino == INO_BACKFILL_REMAINING
+        || ino == INO_REFRESH_ISSUES
+        || ino == INO_MODE
+}
+
+fn is_writable_meta_file(ino: INodeNo) -> bool {
+    ino == INO_MANUAL_REFRESH || ino == INO_FULL_REFRESH || ino == INO_REFRESH_ISSUES
+}
+
+/// Extracts Jira issue keys from whitespace- or comma-separated text.
+fn parse_issue_keys(text: &str) -> Vec<String> {
+    static KEY_RE: OnceLock<Regex> = OnceLock::new();
+    let key_re =
+        KEY_RE.get_or_init(|| Regex::new(r"^[A-Z][A-Z0-9_]*-[0-9]+$").expect("valid key regex"));
+
+    let mut keys: Vec<String> = Vec::new();
+    for token in text.split(|c: char| c.is_whitespace() || c == ',') {
+        let key = token.trim().to_ascii_uppercase();
+        if key.is_empty() {
+            continue;
+        }
+        if !key_re.is_match(&key) {
+            logging::warn(format!(
+                "ignoring invalid issue key in refresh request: {}",
+                token
+            ));
+            continue;
+        }
+        if !keys.contains(&key) {
+            keys.push(key);
+        }
+    }
+    keys
+}
+
 pub fn inode_for_workspace(workspace: &str) -> INodeNo {
     INodeNo(namespace_hash(0x11, workspace.as_bytes()))
 }
@@ -785,6 +1468,10 @@ pub fn inode_for_issue(workspace: &str, issue_key: &str) -> INodeNo {
     INodeNo(namespace_hash(0x22, &bytes))
 }
 
+fn inode_for_search(query: &str) -> INodeNo {
+    INodeNo(namespace_hash(0x33, query.as_bytes()))
+}
+
 fn inode_for_issue_kind(workspace: &str, issue_key: &str, kind: IssueFileKind) -> INodeNo {
     match kind {
         IssueFileKind::Main => inode_for_issue(workspace, issue_key),
@@ -832,6 +1519,14 @@ mod tests {
         assert_ne!(inode_for_workspace("alpha"), inode_for_workspace("beta"));
     }
 
+    #[test]
+    fn parses_refresh_issue_keys() {
+        assert_eq!(
+            parse_issue_keys("PROJ-1\nproj-2, OPS-10\n\nnot a key\nPROJ-1\n"),
+            vec!["PROJ-1", "PROJ-2", "OPS-10"]
+        );
+    }
+
     #[test]
     fn issue_inode_is_deterministic_and_namespaced() {
         let a = inode_for_issue("default", "PROJ-1");
@@ -841,4 +1536,165 @@ mod tests {
         assert_ne!(a, c);
         assert_ne!(a, inode_for_workspace("default"));
     }
+
+    #[test]
+    fn handle_snapshots_are_stable_until_release() {
+        let handles = HandleTable::default();
+        let first = handles.insert(b"old version".to_vec());
+        let second = handles.insert(b"new version, longer".to_vec());
+
+        assert_ne!(first, FileHandle(0));
+        assert_ne!(first, second);
+        assert_eq!(
+            handles.get(first).as_deref(),
+            Some(&b"old version".to_vec())
+        );
+        handles.remove(first);
+        assert!(handles.get(first).is_none());
+        assert_eq!(handles.get(second).map(|bytes| bytes.len()), Some(19));
+    }
+
+    #[test]
+    fn open_handle_reads_its_snapshot_after_issue_shrinks() {
+        let cache = Arc::new(
+            InMemoryCache::with_persistence(
+                Duration::from_secs(30),
+                Duration::from_secs(30),
+                std::path::Path::new(":memory:"),
+                Arc::new(crate::metrics::Metrics::new()),
+            )
+            .expect("cache"),
+        );
+        let fs = JiraFuseFs::new(
+            0,
+            0,
+            Vec::new(),
+            None,
+            Arc::clone(&cache),
+            SyncOptions::default(),
+            Arc::new(SyncState::new(Duration::from_secs(60))),
+        );
+        let write = |markdown: &[u8]| {
+            cache.upsert_issues_batch(&[crate::cache::IssueCacheRow {
+                key: "ST-1".to_string(),
+                markdown: markdown.to_vec(),
+                ..crate::cache::IssueCacheRow::default()
+            }]);
+        };
+
+        write(b"# ST-1\n\nA long description that sync later trims.\n");
+        let (old, flags) = fs.open_issue_snapshot("ST-1", IssueFileKind::Main);
+        assert!(flags.contains(FopenFlags::FOPEN_DIRECT_IO));
+        write(b"# ST-1\n");
+        let (new, _) = fs.open_issue_snapshot("ST-1", IssueFileKind::Main);
+
+        let old_bytes = fs.handles.get(old).expect("old handle");
+        assert_eq!(slice_at(&old_bytes, 8, 6), b"A long");
+        let new_bytes = fs.handles.get(new).expect("new handle");
+        assert_eq!(slice_at(&new_bytes, 8, 6), b"");
+        assert_eq!(new_bytes.as_slice(), b"# ST-1\n");
+    }
+
+    #[test]
+    fn assignee_xattr_is_read_and_written_by_account_id() {
+        let cache = Arc::new(
+            InMemoryCache::with_persistence(
+                Duration::from_secs(30),
+                Duration::from_secs(30),
+                std::path::Path::new(":memory:"),
+                Arc::new(crate::metrics::Metrics::new()),
+            )
+            .expect("cache"),
+        );
+        let row = |key: &str, assignee: Option<(&str, &str)>| crate::cache::IssueCacheRow {
+            key: key.to_string(),
+            markdown: b"# issue\n".to_vec(),
+            attributes: Some(crate::cache::IssueAttributes {
+                assignee: assignee.map(|(name, _)| name.to_string()),
+                assignee_id: assignee.map(|(_, id)| id.to_string()),
+                url: format!("https://example.atlassian.net/browse/{key}"),
+                ..crate::cache::IssueAttributes::default()
+            }),
+            ..crate::cache::IssueCacheRow::default()
+        };
+        cache.upsert_issues_batch(&[
+            row("ST-1", Some(("Ada Lovelace", "5b10ac8d82e05b22cc7d4ef5"))),
+            row("ST-2", None),
+        ]);
+        let jira = Arc::new(
+            JiraClient::new("http://127.0.0.1:9".into(), "e".into(), "t".into()).expect("client"),
+        );
+        let fs = JiraFuseFs::new(
+            0,
+            0,
+            Vec::new(),
+            Some(jira),
+            cache,
+            SyncOptions::default(),
+            Arc::new(SyncState::new(Duration::from_secs(60))),
+        )
+        .with_xattr_write_back(true);
+        let main = IssueFileKind::Main;
+        let errno = |result: Result<Arc<JiraClient>, Errno>| result.err().map(i32::from);
+
+        let xattrs = fs.issue_xattrs("ST-1", main);
+        let value = |name: &str| {
+            xattrs
+                .iter()
+                .find(|(xattr, _)| *xattr == name)
+                .map(|(_, value)| value.clone())
+        };
+        assert_eq!(value(XATTR_ASSIGNEE), Some(b"Ada Lovelace".to_vec()));
+        assert_eq!(
+            value(XATTR_ASSIGNEE_ID),
+            Some(b"5b10ac8d82e05b22cc7d4ef5".to_vec())
+        );
+
+        // The display name is read-only; writes go through the accountId.
+        assert_eq!(
+            errno(fs.xattr_writer("ST-1", main, XATTR_ASSIGNEE, 0)),
+            Some(libc::ENOTSUP)
+        );
+        assert_eq!(
+            errno(fs.xattr_writer("ST-1", main, XATTR_ASSIGNEE_ID, 0)),
+            None
+        );
+        assert_eq!(
+            errno(fs.xattr_writer("ST-1", main, XATTR_ASSIGNEE_ID, libc::XATTR_CREATE)),
+            Some(libc::EEXIST)
+        );
+        assert_eq!(
+            errno(fs.xattr_writer("ST-1", main, XATTR_ASSIGNEE_ID, libc::XATTR_REPLACE)),
+            None
+        );
+        assert_eq!(
+            errno(fs.xattr_writer("ST-2", main, XATTR_ASSIGNEE_ID, libc::XATTR_REPLACE)),
+            Some(i32::from(Errno::NO_XATTR))
+        );
+        assert_eq!(
+            errno(fs.xattr_writer("ST-2", main, XATTR_ASSIGNEE_ID, libc::XATTR_CREATE)),
+            None
+        );
+        assert_eq!(
+            errno(fs.xattr_writer("ST-2", main, XATTR_LABELS, libc::XATTR_REPLACE)),
+            None
+        );
+    }
+
+    #[test]
+    fn decodes_search_directory_names() {
+        assert_eq!(decode_search_name("login%20timeout"), "login timeout");
+        assert_eq!(decode_search_name("sso%2Fsaml"), "sso/saml");
+        assert_eq!(decode_search_name("100% done%2"), "100% done%2");
+        assert_eq!(decode_search_name("plain words"), "plain words");
+    }
+
+    #[test]
+    fn parses_label_xattr_values() {
+        assert_eq!(
+            parse_labels("backend, needs-review\tq3  "),
+            vec!["backend", "needs-review", "q3"]
+        );
+        assert!(parse_labels(" , ").is_empty());
+    }
 }
@@ -1,13 +1,16 @@
-use std::sync::{Arc, Condvar, Mutex};
+use std::sync::{Arc, Condvar, Mutex, OnceLock};
 use std::thread;
 use std::time::Duration;
 
+use chrono::{DateTime, Utc};
+use chrono_tz::Tz;
 use reqwest::blocking::{Client, Response};
 use serde::Deserialize;
 use serde_json::Value;
 
 use crate::logging;
 use crate::metrics::Metrics;
+use crate::parallel;
 
 #[derive(Debug, Clone)]
 /// Lightweight issue reference returned by listing APIs.
@@ -22,15 +25,21 @@ pub struct JiraIdentity {
     pub account_id: Option<String>,
     pub display_name: Option<String>,
     pub email_address: Option<String>,
+    pub time_zone: Option<String>,
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Default)]
 /// Render-ready Jira comment payload.
 pub struct IssueComment {
     pub id: Option<String>,
     pub author_display_name: Option<String>,
     pub body: Value,
     pub created: Option<String>,
+    pub updated: Option<String>,
+    pub update_author_display_name: Option<String>,
+    pub visibility: Option<String>,
+    pub jsd_public: Option<bool>,
+    pub parent_id: Option<String>,
 }
 
 #[derive(Debug, Clone)]
@@ -50,6 +59,8 @@ pub struct IssueData {
     pub status: Option<String>,
     pub priority: Option<String>,
     pub assignee: Option<String>,
+    /// Jira accountId of the assignee, the identifier assignee writes take.
+    pub assignee_account_id: Option<String>,
     pub reporter: Option<String>,
     pub labels: Vec<String>,
     pub created: Option<String>,
@@ -85,6 +96,17 @@ pub enum JiraError {
     InvalidBaseUrl(String),
 }
 
+impl JiraError {
+    /// HTTP status Jira answered with, for errors that carry one.
+    #[must_use]
+    pub fn http_status(&self) -> Option<reqwest::StatusCode> {
+        match self {
+            Self::Http { status, .. } => Some(*status),
+            _ => None,
+        }
+    }
+}
+
 #[derive(Debug)]
 struct Limiter {
     max: usize,
@@ -134,6 +156,7 @@ pub struct JiraClient {
     max_retries: usize,
     limiter: Arc<Limiter>,
     metrics: Arc<Metrics>,
+    account_time_zone: Arc<OnceLock<Tz>>,
 }
 
 impl JiraClient {
@@ -165,9 +188,24 @@ impl JiraClient {
             max_retries: 3,
             limiter: Arc::new(Limiter::new(4)),
             metrics,
+            account_time_zone: Arc::new(OnceLock::new()),
         })
     }
 
+    /// Caps concurrent in-flight Jira requests across every caller of this client.
+    ///
+    /// Call before cloning or sharing the client; clones share one limiter.
+    #[must_use]
+    pub fn with_max_in_flight(mut self, max: usize) -> Self {
+        self.limiter = Arc::new(Limiter::new(max));
+        self
+    }
+
+    /// Returns the maximum number of concurrent in-flight requests.
+    pub fn max_in_flight(&self) -> usize {
+        self.limiter.max
+    }
+
     fn request_with_retry<F>(&self, mut send: F) -> Result<Response, JiraError>
     where
         F: FnMut() -> Result<Response, reqwest::Error>,
@@ -220,6 +258,21 @@ impl JiraClient {
     /// # Errors
     /// Returns [`JiraError`] when request transport, HTTP status, or decode fails.
     pub fn list_issue_refs_for_jql(&self, jql: &str) -> Result<Vec<IssueRef>, JiraError> {
+        let all = self.probe_issue_refs(jql)?;
+        if all.is_empty() {
+            logging::warn(format!(
+                "jira returned zero issues for jql '{}'; verify JQL and Browse permissions",
+                jql.trim()
+            ));
+        }
+        Ok(all)
+    }
+
+    /// Lists issue refs for a JQL query that may legitimately match nothing.
+    ///
+    /// # Errors
+    /// Returns [`JiraError`] when request transport, HTTP status, or decode fails.
+    pub fn probe_issue_refs(&self, jql: &str) -> Result<Vec<IssueRef>, JiraError> {
         let mut start_at: usize = 0;
         let mut next_page_token: Option<String> = None;
         let max_results: usize = 50;
@@ -312,13 +365,6 @@ impl JiraClient {
             }
         }
 
-        if all.is_empty() {
-            logging::warn(format!(
-                "jira returned zero issues for jql '{}'; verify JQL and Browse permissions",
-                scoped_jql
-            ));
-        }
-
         Ok(all)
     }
 
@@ -346,21 +392,7 @@ impl JiraClient {
         }
 
         let payload: IssueResponse = response.json()?;
-        let comments = payload
-            .fields
-            .comment
-            .map(|c| {
-                c.comments
-                    .into_iter()
-                    .map(|comment| IssueComment {
-                        id: comment.id,
-                        author_display_name: comment.author.and_then(|a| a.display_name),
-                        body: comment.body,
-                        created: comment.created,
-                    })
-                    .collect()
-            })
-            .unwrap_or_default();
+        let comments = self.complete_comments(issue_key, payload.fields.comment);
 
         let project = issue_key
             .split_once('-')
@@ -375,6 +407,11 @@ impl JiraClient {
             summary: payload.fields.summary,
             status: payload.fields.status.and_then(|s| s.name),
             priority: payload.fields.priority.and_then(|p| p.name),
+            assignee_account_id: payload
+                .fields
+                .assignee
+                .as_ref()
+                .and_then(|a| a.account_id.clone()),
             assignee: payload.fields.assignee.and_then(|a| a.display_name),
             reporter: payload.fields.reporter.and_then(|a| a.display_name),
             labels: payload.fields.labels,
@@ -457,22 +494,18 @@ impl JiraClient {
             let page_issues = payload.take_issues();
             let page_count = page_issues.len();
 
-            for issue in page_issues {
-                let comments = issue
-                    .fields
-                    .comment
-                    .map(|c| {
-                        c.comments
-                            .into_iter()
-                            .map(|comment| IssueComment {
-                                id: comment.id,
-                                author_display_name: comment.author.and_then(|a| a.display_name),
-                                body: comment.body,
-                                created: comment.created,
-                            })
-                            .collect()
-                    })
-                    .unwrap_or_default();
+            // Issues with more comments than the embedded page each need a
+            // follow-up request; fetch those concurrently within the limiter.
+            let workers = if page_issues
+                .iter()
+                .any(|issue| needs_comment_pages(issue.fields.comment.as_ref()))
+            {
+                self.limiter.max
+            } else {
+                1
+            };
+            all.extend(parallel::map_concurrent(page_issues, workers, |issue| {
+                let comments = self.complete_comments(&issue.key, issue.fields.comment);
 
                 let issue_key = issue.key.clone();
                 let project = issue_key
@@ -482,13 +515,18 @@ impl JiraClient {
                 let (blocks, blocked_by, relates_to) =
                     categorize_links(issue.fields.issue_links.clone());
 
-                all.push(IssueData {
+                IssueData {
                     key: issue_key.clone(),
                     project,
                     issue_type: issue.fields.issue_type.and_then(|t| t.name),
                     summary: issue.fields.summary,
                     status: issue.fields.status.and_then(|s| s.name),
                     priority: issue.fields.priority.and_then(|p| p.name),
+                    assignee_account_id: issue
+                        .fields
+                        .assignee
+                        .as_ref()
+                        .and_then(|a| a.account_id.clone()),
                     assignee: issue.fields.assignee.and_then(|a| a.display_name),
                     reporter: issue.fields.reporter.and_then(|a| a.display_name),
                     labels: issue.fields.labels,
@@ -512,8 +550,8 @@ impl JiraClient {
                         .collect(),
                     description: issue.fields.description.unwrap_or(Value::Null),
                     comments,
-                });
-            }
+                }
+            }));
 
             if let Some(token) = payload.next_page_token {
                 if token.is_empty() || payload.is_last == Some(true) {
@@ -539,6 +577,141 @@ impl JiraClient {
         Ok(all)
     }
 
+    /// Lists an issue's comments oldest-first, starting at `start_at`.
+    ///
+    /// # Errors
+    /// Returns [`JiraError`] when request transport, HTTP status, or decode fails.
+    pub fn list_issue_comments(
+        &self,
+        issue_key: &str,
+        start_at: usize,
+    ) -> Result<Vec<IssueComment>, JiraError> {
+        let url = format!("{}/rest/api/3/issue/{}/comment", self.base_url, issue_key);
+        let mut all = Vec::new();
+        let mut start_at = start_at;
+
+        loop {
+            let response = self.request_with_retry(|| {
+                self.http
+                    .get(&url)
+                    .basic_auth(&self.email, Some(&self.api_token))
+                    .query(&[
+                        ("startAt", start_at.to_string()),
+                        ("maxResults", "100".to_string()),
+                        ("orderBy", "created".to_string()),
+                    ])
+                    .send()
+            })?;
+
+            if !response.status().is_success() {
+                let status = response.status();
+                let body = response.text().unwrap_or_default();
+                return Err(JiraError::Http { status, body });
+            }
+
+            let body = response.text()?;
+            let page: CommentContainer =
+                serde_json::from_str(&body).map_err(|source| JiraError::Decode {
+                    source,
+                    body: body.chars().take(500).collect(),
+                })?;
+
+            let page_count = page.comments.len();
+            all.extend(page.comments.into_iter().map(map_comment));
+            start_at += page_count;
+
+            if page_count == 0 || page.total.is_none_or(|total| start_at >= total) {
+                break;
+            }
+        }
+
+        Ok(all)
+    }
+
+    /// Maps the embedded comment page, fetching the rest when Jira truncated it.
+    fn complete_comments(
+        &self,
+        issue_key: &str,
+        container: Option<CommentContainer>,
+    ) -> Vec<IssueComment> {
+        let Some(container) = container else {
+            return Vec::new();
+        };
+        let embedded = container.comments.len();
+        let total = container.total.unwrap_or(embedded);
+        let offset = container.start_at.unwrap_or(0);
+        let mut comments: Vec<IssueComment> =
+            container.comments.into_iter().map(map_comment).collect();
+        if offset == 0 && total <= embedded {
+            return comments;
+        }
+
+        // A page that does not start at zero cannot be extended in place.
+        let resume_at = if offset == 0 { embedded } else { 0 };
+        match self.list_issue_comments(issue_key, resume_at) {
+            Ok(rest) => {
+                if offset != 0 {
+                    comments.clear();
+                }
+                comments.extend(rest);
+                logging::debug(format!(
+                    "fetched {} of {} comments for {}",
+                    comments.len(),
+                    total,
+                    issue_key
+                ));
+            }
+            Err(err) => {
+                logging::warn(format!(
+                    "failed to fetch remaining comments for {} ({} of {} embedded): {}",
+                    issue_key, embedded, total, err
+                ));
+            }
+        }
+        comments
+    }
+
+    /// Replaces the labels on one issue.
+    ///
+    /// # Errors
+    /// Returns [`JiraError`] when request transport or HTTP status fails.
+    pub fn set_issue_labels(&self, issue_key: &str, labels: &[String]) -> Result<(), JiraError> {
+        let url = format!("{}/rest/api/3/issue/{}", self.base_url, issue_key);
+        let body = serde_json::json!({ "fields": { "labels": labels } });
+        self.put_json(&url, &body)
+    }
+
+    /// Assigns one issue to an account, or unassigns it with `None`.
+    ///
+    /// # Errors
+    /// Returns [`JiraError`] when request transport or HTTP status fails.
+    pub fn set_issue_assignee(
+        &self,
+        issue_key: &str,
+        account_id: Option<&str>,
+    ) -> Result<(), JiraError> {
+        let url = format!("{}/rest/api/3/issue/{}/assignee", self.base_url, issue_key);
+        let body = serde_json::json!({ "accountId": account_id });
+        self.put_json(&url, &body)
+    }
+
+    fn put_json(&self, url: &str, body: &Value) -> Result<(), JiraError> {
+        let response = self.request_with_retry(|| {
+            self.http
+                .put(url)
+                .basic_auth(&self.email, Some(&self.api_token))
+                .json(body)
+                .send()
+        })?;
+
+        if !response.status().is_success() {
+            let status = response.status();
+            let body = response.text().unwrap_or_default();
+            return Err(JiraError::Http { status, body });
+        }
+        Ok(())
+    }
+
     /// Fetches the authenticated Jira user.
     ///
     /// # Errors
@@ -566,9 +739,44 @@ impl JiraClient {
             account_id: payload.account_id,
             display_name: payload.display_name,
             email_address: payload.email_address,
+            time_zone: payload.time_zone,
         })
     }
 
+    /// Returns the authenticated user's profile timezone, which Jira uses to
+    /// interpret date literals in JQL.
+    ///
+    /// The zone is fetched once and reused. When `/myself` fails or reports an
+    /// unknown zone, UTC is returned and the lookup is retried on the next call.
+    pub fn account_time_zone(&self) -> Tz {
+        if let Some(tz) = self.account_time_zone.get() {
+            return *tz;
+        }
+
+        match self.get_myself() {
+            Ok(identity) => {
+                let name = identity.time_zone.unwrap_or_default();
+                match name.parse::<Tz>() {
+                    Ok(tz) => *self.account_time_zone.get_or_init(|| tz),
+                    Err(_) => {
+                        logging::warn(format!(
+                            "unknown jira account timezone '{}'; using UTC",
+                            name
+                        ));
+                        Tz::UTC
+                    }
+                }
+            }
+            Err(err) => {
+                logging::warn(format!(
+                    "failed to load jira account timezone; using UTC: {}",
+                    err
+                ));
+                Tz::UTC
+            }
+        }
+    }
+
     /// Lists project keys visible to the authenticated Jira user.
     ///
     /// # Errors
@@ -623,6 +831,20 @@ fn normalize_base_url(raw: &str) -> Result<String, JiraError> {
     Ok(parsed.as_str().trim_end_matches('/').to_string())
 }
 
+/// Parses a Jira timestamp such as `2026-02-21T01:00:00.000+0000` or RFC 3339.
+pub fn parse_jira_timestamp(raw: &str) -> Option<DateTime<Utc>> {
+    let value = raw.trim();
+    if value.is_empty() {
+        return None;
+    }
+
+    DateTime::parse_from_rfc3339(value)
+        .or_else(|_| DateTime::parse_from_str(value, "%Y-%m-%dT%H:%M:%S%.f%z"))
+        .or_else(|_| DateTime::parse_from_str(value, "%Y-%m-%dT%H:%M:%S%z"))
+        .ok()
+        .map(|ts| ts.with_timezone(&Utc))
+}
+
 fn is_retryable(status: reqwest::StatusCode) -> bool {
     status == reqwest::StatusCode::TOO_MANY_REQUESTS || status.is_server_error()
 }
@@ -785,19 +1007,69 @@ struct PriorityObj {
 #[serde(rename_all = "camelCase")]
 struct UserObj {
     display_name: Option<String>,
+    account_id: Option<String>,
 }
 
 #[derive(Debug, Deserialize, Clone)]
+#[serde(rename_all = "camelCase")]
 struct CommentContainer {
+    #[serde(default)]
     comments: Vec<CommentObj>,
+    #[serde(default)]
+    total: Option<usize>,
+    #[serde(default)]
+    start_at: Option<usize>,
 }
 
 #[derive(Debug, Deserialize, Clone)]
+#[serde(rename_all = "camelCase")]
 struct CommentObj {
     id: Option<String>,
     author: Option<UserObj>,
     body: Value,
     created: Option<String>,
+    updated: Option<String>,
+    update_author: Option<UserObj>,
+    visibility: Option<VisibilityObj>,
+    jsd_public: Option<bool>,
+    parent_id: Option<Value>,
+}
+
+#[derive(Debug, Deserialize, Clone)]
+struct VisibilityObj {
+    #[serde(rename = "type")]
+    kind: Option<String>,
+    value: Option<String>,
+}
+
+fn needs_comment_pages(container: Option<&CommentContainer>) -> bool {
+    container.is_some_and(|container| {
+        container.start_at.unwrap_or(0) != 0
+            || container.total.unwrap_or(container.comments.len()) > container.comments.len()
+    })
+}
+
+fn map_comment(comment: CommentObj) -> IssueComment {
+    IssueComment {
+        id: comment.id,
+        author_display_name: comment.author.and_then(|a| a.display_name),
+        body: comment.body,
+        created: comment.created,
+        updated: comment.updated,
+        update_author_display_name: comment.update_author.and_then(|a| a.display_name),
+        visibility: comment.visibility.and_then(|v| match (v.kind, v.value) {
+            (Some(kind), Some(value)) => Some(format!("{kind}:{value}")),
+            (None, Some(value)) => Some(value),
+            _ => None,
+        }),
+        jsd_public: comment.jsd_public,
+        // Jira sends parentId as a number on some deployments and a string on others.
+        parent_id: comment.parent_id.and_then(|id| match id {
+            Value::String(id) => Some(id),
+            Value::Number(id) => Some(id.to_string()),
+            _ => None,
+        }),
+    }
 }
 
 #[derive(Debug, Deserialize, Clone)]
@@ -884,6 +1156,7 @@ struct MyselfResponse {
     account_id: Option<String>,
     display_name: Option<String>,
     email_address: Option<String>,
+    time_zone: Option<String>,
 }
 
 #[derive(Debug, Deserialize)]
@@ -900,7 +1173,7 @@ struct ProjectInfo {
 #[cfg(test)]
 mod tests {
     use super::*;
-    use httpmock::Method::GET;
+    use httpmock::Method::{GET, PUT};
     use httpmock::MockServer;
 
     #[test]
@@ -947,6 +1220,86 @@ mod tests {
         assert_eq!(items[1].key, "PROJ-2");
     }
 
+    #[test]
+    fn writes_labels_and_assignee() {
+        let server = MockServer::start();
+        let labels = server.mock(|when, then| {
+            when.method(PUT)
+                .path("/rest/api/3/issue/PROJ-1")
+                .json_body_obj(&serde_json::json!({"fields": {"labels": ["backend", "urgent"]}}));
+            then.status(204);
+        });
+        let assignee = server.mock(|when, then| {
+            when.method(PUT)
+                .path("/rest/api/3/issue/PROJ-1/assignee")
+                .json_body_obj(&serde_json::json!({"accountId": null}));
+            then.status(204);
+        });
+
+        let client = JiraClient::new(server.base_url(), "e".into(), "t".into()).expect("client");
+        client
+            .set_issue_labels("PROJ-1", &["backend".to_string(), "urgent".to_string()])
+            .expect("labels should update");
+        client
+            .set_issue_assignee("PROJ-1", None)
+            .expect("unassign should succeed");
+
+        labels.assert();
+        assignee.assert();
+    }
+
+    #[test]
+    fn fetches_comments_beyond_embedded_page() {
+        let server = MockServer::start();
+
+        let _search = server.mock(|when, then| {
+            when.method(GET).path("/rest/api/3/search/jql");
+            then.status(200).json_body_obj(&serde_json::json!({
+                "isLast": true,
+                "issues": [{
+                    "key": "PROJ-1",
+                    "fields": {
+                        "updated": "2026-02-21T00:00:00.000+0000",
+                        "comment": {
+                            "startAt": 0,
+                            "maxResults": 1,
+                            "total": 3,
+                            "comments": [{"id": "1", "body": "first"}]
+                        }
+                    }
+                }]
+            }));
+        });
+
+        let comment_page = server.mock(|when, then| {
+            when.method(GET)
+                .path("/rest/api/3/issue/PROJ-1/comment")
+                .query_param("startAt", "1");
+            then.status(200).json_body_obj(&serde_json::json!({
+                "startAt": 1,
+                "maxResults": 100,
+                "total": 3,
+                "comments": [
+                    {"id": "2", "body": "second"},
+                    {"id": "3", "body": "latest"}
+                ]
+            }));
+        });
+
+        let client = JiraClient::new(server.base_url(), "e".into(), "t".into()).expect("client");
+        let issues = client
+            .search_issues_bulk("project = PROJ", 50)
+            .expect("search should succeed");
+
+        comment_page.assert();
+        let ids: Vec<_> = issues[0]
+            .comments
+            .iter()
+            .map(|comment| comment.id.as_deref().unwrap_or_default())
+            .collect();
+        assert_eq!(ids, vec!["1", "2", "3"]);
+    }
+
     #[test]
     fn retries_on_429_then_succeeds() {
         use tiny_http::{Header, Response, Server, StatusCode};
@@ -13,9 +13,17 @@ pub mod jira;
 pub mod logging;
 /// Runtime metrics counters.
 pub mod metrics;
+/// Background Jira fetches for issues read before sync cached them.
+pub mod on_demand;
+/// Scoped worker helpers for concurrent Jira fetches.
+pub(crate) mod parallel;
+/// Configurable secret redaction shared by rendering and logging.
+pub mod redaction;
 /// Markdown and sidecar renderers for Jira payloads.
 pub mod render;
 /// Sync scheduling and trigger state.
 pub mod sync_state;
 /// Startup seeding and sync routines.
 pub mod warmup;
+/// Optional Jira webhook listener for push-based refresh.
+pub mod webhooks;
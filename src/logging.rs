@@ -45,7 +45,7 @@ fn redacted(message: &str) -> String {
     let masked_escaped_toml = escaped_toml_token_re.replace_all(&masked_toml, "$1***REDACTED***$3");
     let masked_cli = cli_token_re.replace_all(&masked_escaped_toml, "$1***REDACTED***");
     let masked_json = json_token_re.replace_all(&masked_cli, "$1***REDACTED***$3");
-    masked_json.into_owned()
+    crate::redaction::redact(&masked_json)
 }
 
 pub fn debug(message: impl AsRef<str>) {
@@ -1,4 +1,4 @@
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap};
 use std::ffi::OsString;
 use std::path::{Path, PathBuf};
 use std::process::{Command, Stdio};
@@ -6,33 +6,72 @@ use std::sync::Arc;
 use std::time::Duration;
 
 use fuser::{Config, MountOption};
-use jirafs::cache::InMemoryCache;
-use jirafs::config::AppConfigOverrides;
-use jirafs::fs::JiraFuseFs;
+use jirafs::cache::persistent::PersistentCache;
+use jirafs::cache::{CacheKey, GcPolicy, InMemoryCache, KeySource};
+use jirafs::config::{AppConfig, AppConfigOverrides, CacheConfig, WorkspaceConfig};
+use jirafs::fs::{spawn_kernel_invalidator, JiraFuseFs};
 use jirafs::jira::JiraClient;
 use jirafs::logging;
 use jirafs::metrics::{spawn_metrics_logger, Metrics};
+use jirafs::on_demand::OnDemandFetcher;
+use jirafs::redaction::{self, Redactor};
+use jirafs::render::template::IssueTemplate;
 use jirafs::sync_state::SyncState;
-use jirafs::warmup::sync_issues;
+use jirafs::warmup::{reconcile_workspaces, refresh_issues, sync_issues, SyncOptions};
+use jirafs::webhooks;
 
 const USAGE: &str = "usage: cargo run -- [flags] <mountpoint>\n\
+       cargo run -- [flags] redact-check\n\
+       cargo run -- [flags] search <terms>...\n\
+       cargo run -- [flags] cache gc\n\
+       cargo run -- [flags] cache rekey --new-key-<env|file|command> <value>\n\
+commands:\n\
+  redact-check  report what the configured redaction rules match in cached issues\n\
+  search        full-text search cached issues and comments\n\
+  cache gc      expire, evict and vacuum the persistent cache now\n\
+  cache rekey   re-encrypt the persistent cache under a new key\n\
 flags:\n\
   -c, --config <path>\n\
   -h, --help\n\
+  --offline\n\
   --jira-base-url <url>\n\
   --jira-email <email>\n\
   --jira-api-token <token>\n\
   --jira-workspace <name=jql> (repeatable)\n\
   --cache-db-path <path>\n\
   --cache-ttl-secs <u64>\n\
+  --cache-on-demand-deadline-ms <u64>\n\
+  --cache-memory-limit-mb <u64>\n\
+  --cache-max-db-size-mb <u64>\n\
   --sync-budget <usize>\n\
   --sync-interval-secs <u64>\n\
+  --sync-reconcile-interval-secs <u64>\n\
+  --sync-cursor-overlap-secs <u64>\n\
+  --sync-allocation <weighted|round_robin|activity>\n\
+  --sync-concurrency <usize>\n\
   --metrics-interval-secs <u64>\n\
-  --logging-debug <true|false>";
+  --logging-debug <true|false>\n\
+  --render-template-path <path>\n\
+  --webhooks-listen <addr>\n\
+  --xattrs-write-back <true|false>\n\
+  --new-key-env <var>          (cache rekey)\n\
+  --new-key-file <path>        (cache rekey)\n\
+  --new-key-command <command>  (cache rekey)";
+
+const SEARCH_LIMIT: usize = 20;
+
+#[derive(Debug, PartialEq, Eq)]
+enum CliCommand {
+    Mount(PathBuf),
+    RedactCheck,
+    Search(String),
+    CacheGc,
+    CacheRekey(KeySource),
+}
 
 #[derive(Debug)]
 struct CliArgs {
-    mountpoint: PathBuf,
+    command: CliCommand,
     config_path: Option<PathBuf>,
     overrides: AppConfigOverrides,
 }
@@ -42,11 +81,19 @@ fn parse_cli_args(args: impl IntoIterator<Item = OsString>) -> Result<Option<Cli
     let _program = iter.next();
 
     let mut mountpoint = None;
+    let mut redact_check = false;
+    let mut search_terms: Option<Vec<String>> = None;
+    let mut cache_action: Option<Option<String>> = None;
+    let mut new_key = None;
     let mut config_path = None;
     let mut overrides = AppConfigOverrides::default();
 
     while let Some(arg) = iter.next() {
         let arg_text = arg.to_string_lossy();
+        let no_command_yet = mountpoint.is_none()
+            && !redact_check
+            && search_terms.is_none()
+            && cache_action.is_none();
         match arg_text.as_ref() {
             "-h" | "--help" => {
                 return Ok(None);
@@ -54,6 +101,9 @@ fn parse_cli_args(args: impl IntoIterator<Item = OsString>) -> Result<Option<Cli
             "-c" | "--config" => {
                 config_path = Some(PathBuf::from(next_value(&mut iter, "--config")?));
             }
+            "--offline" => {
+                overrides.offline = Some(true);
+            }
             "--jira-base-url" => {
                 overrides.jira_base_url = Some(next_string(&mut iter, "--jira-base-url")?);
             }
@@ -69,7 +119,13 @@ fn parse_cli_args(args: impl IntoIterator<Item = OsString>) -> Result<Option<Cli
                 overrides
                     .jira_workspaces
                     .get_or_insert_with(HashMap::new)
-                    .insert(name, jirafs::config::WorkspaceConfig { jql });
+                    .insert(
+                        name,
+                        WorkspaceConfig {
+                            jql,
+                            ..WorkspaceConfig::default()
+                        },
+                    );
             }
             "--cache-db-path" => {
                 overrides.cache_db_path = Some(next_string(&mut iter, "--cache-db-path")?);
@@ -78,6 +134,24 @@ fn parse_cli_args(args: impl IntoIterator<Item = OsString>) -> Result<Option<Cli
                 overrides.cache_ttl_secs =
                     Some(parse_u64(&next_string(&mut iter, "--cache-ttl-secs")?)?);
             }
+            "--cache-on-demand-deadline-ms" => {
+                overrides.cache_on_demand_deadline_ms = Some(parse_u64(&next_string(
+                    &mut iter,
+                    "--cache-on-demand-deadline-ms",
+                )?)?);
+            }
+            "--cache-memory-limit-mb" => {
+                overrides.cache_memory_limit_mb = Some(parse_u64(&next_string(
+                    &mut iter,
+                    "--cache-memory-limit-mb",
+                )?)?);
+            }
+            "--cache-max-db-size-mb" => {
+                overrides.cache_max_db_size_mb = Some(parse_u64(&next_string(
+                    &mut iter,
+                    "--cache-max-db-size-mb",
+                )?)?);
+            }
             "--sync-budget" => {
                 overrides.sync_budget =
                     Some(parse_usize(&next_string(&mut iter, "--sync-budget")?)?);
@@ -86,6 +160,26 @@ fn parse_cli_args(args: impl IntoIterator<Item = OsString>) -> Result<Option<Cli
                 overrides.sync_interval_secs =
                     Some(parse_u64(&next_string(&mut iter, "--sync-interval-secs")?)?);
             }
+            "--sync-reconcile-interval-secs" => {
+                overrides.sync_reconcile_interval_secs = Some(parse_u64(&next_string(
+                    &mut iter,
+                    "--sync-reconcile-interval-secs",
+                )?)?);
+            }
+            "--sync-cursor-overlap-secs" => {
+                overrides.sync_cursor_overlap_secs = Some(parse_u64(&next_string(
+                    &mut iter,
+                    "--sync-cursor-overlap-secs",
+                )?)?);
+            }
+            "--sync-allocation" => {
+                overrides.sync_allocation =
+                    Some(next_string(&mut iter, "--sync-allocation")?.parse()?);
+            }
+            "--sync-concurrency" => {
+                overrides.sync_concurrency =
+                    Some(parse_usize(&next_string(&mut iter, "--sync-concurrency")?)?);
+            }
             "--metrics-interval-secs" => {
                 overrides.metrics_interval_secs = Some(parse_u64(&next_string(
                     &mut iter,
@@ -96,6 +190,32 @@ fn parse_cli_args(args: impl IntoIterator<Item = OsString>) -> Result<Option<Cli
                 overrides.logging_debug =
                     Some(parse_bool(&next_string(&mut iter, "--logging-debug")?)?);
             }
+            "--render-template-path" => {
+                overrides.render_template_path =
+                    Some(next_string(&mut iter, "--render-template-path")?);
+            }
+            "--webhooks-listen" => {
+                overrides.webhooks_listen = Some(next_string(&mut iter, "--webhooks-listen")?);
+            }
+            "--xattrs-write-back" => {
+                overrides.xattrs_write_back =
+                    Some(parse_bool(&next_string(&mut iter, "--xattrs-write-back")?)?);
+            }
+            "--new-key-env" => {
+                new_key = Some(KeySource::Env(next_string(&mut iter, "--new-key-env")?));
+            }
+            "--new-key-file" => {
+                new_key = Some(KeySource::File(PathBuf::from(next_value(
+                    &mut iter,
+                    "--new-key-file",
+                )?)));
+            }
+            "--new-key-command" => {
+                new_key = Some(KeySource::Command(next_string(
+                    &mut iter,
+                    "--new-key-command",
+                )?));
+            }
             "--" => {
                 if mountpoint.is_none() {
                     let value = iter
@@ -110,7 +230,34 @@ fn parse_cli_args(args: impl IntoIterator<Item = OsString>) -> Result<Option<Cli
             value if value.starts_with('-') => {
                 return Err(format!("unknown flag: {value}\n{USAGE}"));
             }
+            "redact-check" if no_command_yet => {
+                redact_check = true;
+            }
+            "search" if no_command_yet => {
+                search_terms = Some(Vec::new());
+            }
+            "cache" if no_command_yet => {
+                cache_action = Some(None);
+            }
             _ => {
+                if let Some(terms) = search_terms.as_mut() {
+                    terms.push(arg_text.into_owned());
+                    continue;
+                }
+                if let Some(action @ None) = cache_action.as_mut() {
+                    *action = Some(arg_text.into_owned());
+                    continue;
+                }
+                if cache_action.is_some() {
+                    return Err(format!(
+                        "unexpected extra positional argument: {arg_text}\n{USAGE}"
+                    ));
+                }
+                if redact_check {
+                    return Err(format!(
+                        "redact-check does not take a mountpoint: {arg_text}\n{USAGE}"
+                    ));
+                }
                 if mountpoint.is_some() {
                     return Err(format!(
                         "unexpected extra positional argument: {arg_text}\n{USAGE}"
@@ -121,9 +268,35 @@ fn parse_cli_args(args: impl IntoIterator<Item = OsString>) -> Result<Option<Cli
         }
     }
 
-    let mountpoint = mountpoint.ok_or_else(|| format!("missing mountpoint\n{USAGE}"))?;
+    let rekey = cache_action.as_ref().and_then(Option::as_deref) == Some("rekey");
+    if new_key.is_some() && !rekey {
+        return Err(format!(
+            "--new-key-* flags only apply to cache rekey\n{USAGE}"
+        ));
+    }
+    let command = if redact_check {
+        CliCommand::RedactCheck
+    } else if let Some(terms) = search_terms {
+        if terms.is_empty() {
+            return Err(format!("search needs at least one term\n{USAGE}"));
+        }
+        CliCommand::Search(terms.join(" "))
+    } else if let Some(action) = cache_action {
+        match action.as_deref() {
+            Some("gc") => CliCommand::CacheGc,
+            Some("rekey") => CliCommand::CacheRekey(new_key.ok_or_else(|| {
+                format!(
+                    "cache rekey needs --new-key-env, --new-key-file or --new-key-command\n{USAGE}"
+                )
+            })?),
+            Some(other) => return Err(format!("unknown cache command: {other}\n{USAGE}")),
+            None => return Err(format!("missing cache command\n{USAGE}")),
+        }
+    } else {
+        CliCommand::Mount(mountpoint.ok_or_else(|| format!("missing mountpoint\n{USAGE}"))?)
+    };
     Ok(Some(CliArgs {
-        mountpoint,
+        command,
         config_path,
         overrides,
     }))
@@ -181,9 +354,10 @@ fn parse_workspace_override(value: &str) -> Result<(String, String), String> {
 fn spawn_periodic_sync(
     jira: Arc<JiraClient>,
     cache: Arc<InMemoryCache>,
-    workspaces: Vec<(String, String)>,
-    sync_budget: usize,
+    workspaces: Vec<(String, WorkspaceConfig)>,
+    sync_options: SyncOptions,
     sync_state: Arc<SyncState>,
+    polling: bool,
 ) -> std::thread::JoinHandle<()> {
     std::thread::spawn(move || {
         let check_interval = Duration::from_secs(1);
@@ -192,7 +366,13 @@ fn spawn_periodic_sync(
 
             let manual_full_triggered = sync_state.check_and_clear_manual_full_trigger();
             let manual_triggered = sync_state.check_and_clear_manual_trigger();
-            let time_for_sync = sync_state.seconds_until_next_sync() == 0;
+            // With polling off, only manual triggers start a sync.
+            let due = if polling {
+                sync_state.due_workspaces()
+            } else {
+                Vec::new()
+            };
+            let time_for_sync = !due.is_empty();
 
             if (manual_full_triggered || manual_triggered || time_for_sync)
                 && sync_state.mark_sync_start()
@@ -212,14 +392,30 @@ fn spawn_periodic_sync(
                     }
                 }
 
+                // Manual triggers cover every workspace; periodic runs only those due.
+                let selected = if manual_full_triggered || manual_triggered {
+                    workspaces.iter().map(|(name, _)| name.clone()).collect()
+                } else {
+                    due
+                };
                 let result = sync_issues(
                     &jira,
                     &cache,
+                    &sync_state,
                     &workspaces,
-                    sync_budget,
+                    Some(&selected),
+                    &sync_options,
+                    manual_full_triggered,
+                );
+                let reconcile = reconcile_workspaces(
+                    &jira,
+                    &cache,
+                    &workspaces,
+                    &sync_options,
                     manual_full_triggered,
                 );
 
+                sync_state.mark_workspaces_synced(&selected);
                 sync_state.mark_sync_complete();
                 if manual_full_triggered {
                     sync_state.mark_full_sync_complete();
@@ -239,6 +435,64 @@ fn spawn_periodic_sync(
                         logging::warn(format!("sync error: {}", err));
                     }
                 }
+                if reconcile.workspaces_reconciled > 0 {
+                    logging::info(format!(
+                        "reconcile complete: workspaces={} removed={} tombstoned={} errors={}",
+                        reconcile.workspaces_reconciled,
+                        reconcile.refs_removed,
+                        reconcile.tombstoned.len(),
+                        reconcile.errors.len()
+                    ));
+                }
+            }
+        }
+    })
+}
+
+/// Serves targeted issue refreshes as soon as they are requested.
+///
+/// Runs beside the periodic sync and does not take the sync lock, so a
+/// long sync never delays a single-issue refresh. Whichever of the two
+/// writes last, a copy older than the cached one is dropped.
+fn spawn_issue_refresher(
+    jira: Arc<JiraClient>,
+    cache: Arc<InMemoryCache>,
+    workspaces: Vec<(String, WorkspaceConfig)>,
+    sync_options: SyncOptions,
+    sync_state: Arc<SyncState>,
+) -> std::thread::JoinHandle<()> {
+    std::thread::spawn(move || loop {
+        let keys = sync_state.wait_for_issue_refresh(Duration::from_secs(60));
+        if keys.is_empty() {
+            continue;
+        }
+
+        logging::info(format!("refreshing issues: {}", keys.join(", ")));
+        let result = refresh_issues(&jira, &cache, &workspaces, &sync_options, &keys);
+        logging::info(format!(
+            "issue refresh complete: cached={} errors={}",
+            result.issues_cached,
+            result.errors.len()
+        ));
+    })
+}
+
+fn spawn_cache_gc(
+    cache: Arc<InMemoryCache>,
+    policy: GcPolicy,
+    interval: Duration,
+) -> std::thread::JoinHandle<()> {
+    std::thread::spawn(move || loop {
+        std::thread::sleep(interval);
+        if let Some(report) = cache.gc(&policy) {
+            if !report.expired.is_empty() || !report.evicted.is_empty() {
+                logging::info(format!(
+                    "cache gc: expired={} evicted={} bytes {} -> {}",
+                    report.expired.len(),
+                    report.evicted.len(),
+                    report.bytes_before,
+                    report.bytes_after
+                ));
             }
         }
     })
@@ -257,11 +511,26 @@ fn mount_options() -> Vec<MountOption> {
     options
 }
 
+fn load_config(cli: &mut CliArgs) -> Result<AppConfig, Box<dyn std::error::Error>> {
+    // Validation waits for the overrides, since `--offline` relaxes it.
+    let mut app_config = match cli.config_path.as_deref() {
+        Some(config_path) => jirafs::config::parse_from(config_path)?,
+        None => jirafs::config::parse_from(&jirafs::config::resolve_config_path()?)?,
+    };
+
+    // Only a mount talks to Jira; the other commands work on the local cache.
+    if !matches!(cli.command, CliCommand::Mount(_)) {
+        cli.overrides.offline = Some(true);
+    }
+    app_config.apply_overrides(&cli.overrides)?;
+    Ok(app_config)
+}
+
 fn run() -> Result<(), Box<dyn std::error::Error>> {
     let cli = parse_cli_args(std::env::args_os())
         .map_err(|err| -> Box<dyn std::error::Error> { err.into() })?;
 
-    let cli = match cli {
+    let mut cli = match cli {
         Some(cli) => cli,
         None => {
             eprintln!("{USAGE}");
@@ -269,14 +538,9 @@ fn run() -> Result<(), Box<dyn std::error::Error>> {
         }
     };
 
-    let mut app_config = if let Some(config_path) = cli.config_path.as_deref() {
-        jirafs::config::load_from(config_path)?
-    } else {
-        jirafs::config::load()?
-    };
-
-    app_config.apply_overrides(&cli.overrides)?;
+    let app_config = load_config(&mut cli)?;
     logging::init(app_config.logging.debug);
+    let redactor = Redactor::from_config(&app_config.redaction)?;
 
     if let Some(config_path) = cli.config_path.as_deref() {
         logging::info(format!(
@@ -285,7 +549,14 @@ fn run() -> Result<(), Box<dyn std::error::Error>> {
         ));
     }
 
-    let mountpoint_path = cli.mountpoint;
+    let mountpoint_path = match cli.command {
+        CliCommand::Mount(mountpoint) => mountpoint,
+        CliCommand::RedactCheck => return run_redact_check(&redactor, &app_config.cache),
+        CliCommand::Search(terms) => return run_search(&terms, &app_config.cache),
+        CliCommand::CacheGc => return run_cache_gc(&app_config.cache),
+        CliCommand::CacheRekey(new_key) => return run_cache_rekey(&app_config.cache, &new_key),
+    };
+    redaction::init(redactor);
     if !mountpoint_path.exists() {
         std::fs::create_dir_all(&mountpoint_path)?;
         logging::info(format!(
@@ -294,19 +565,39 @@ fn run() -> Result<(), Box<dyn std::error::Error>> {
         ));
     }
 
-    let mut workspaces: Vec<(String, String)> = app_config
+    let mut workspaces: Vec<(String, WorkspaceConfig)> = app_config
         .jira
         .workspaces
         .iter()
-        .map(|(name, workspace)| (name.clone(), workspace.jql.clone()))
+        .map(|(name, workspace)| (name.clone(), workspace.clone()))
         .collect();
     workspaces.sort_by(|a, b| a.0.cmp(&b.0));
     let ttl_secs = app_config.cache.ttl_secs;
     let metrics_interval_secs = app_config.metrics.interval_secs;
     let sync_budget = app_config.sync.budget;
     let sync_interval_secs = app_config.sync.interval_secs;
+    let sync_concurrency = app_config.sync.concurrency;
+    let on_demand_deadline = Duration::from_millis(app_config.cache.on_demand_deadline_ms);
+    let xattr_write_back = app_config.xattrs.write_back;
     let metrics = Arc::new(Metrics::new());
 
+    let template = match app_config.render.template_path.as_deref() {
+        Some(path) => {
+            let template = IssueTemplate::load(Path::new(path))?;
+            logging::info(format!("using issue template {}", path));
+            Some(Arc::new(template))
+        }
+        None => None,
+    };
+    let sync_options = SyncOptions {
+        budget: sync_budget,
+        template,
+        reconcile_interval: Duration::from_secs(app_config.sync.reconcile_interval_secs),
+        cursor_overlap: Duration::from_secs(app_config.sync.cursor_overlap_secs),
+        allocation: app_config.sync.allocation,
+        concurrency: app_config.sync.concurrency,
+    };
+
     logging::info(format!(
         "starting jirafs workspaces={} ttl={}s sync_budget={} sync_interval={}s",
         workspaces
@@ -324,24 +615,41 @@ fn run() -> Result<(), Box<dyn std::error::Error>> {
         Duration::from_secs(metrics_interval_secs.max(1)),
     );
 
-    let jira = Arc::new(JiraClient::new_with_metrics(
-        app_config.jira.base_url,
-        app_config.jira.email,
-        app_config.jira.api_token,
-        Arc::clone(&metrics),
-    )?);
-    logging::info(format!("using jira base url {}", jira.base_url));
+    let jira = if app_config.offline {
+        logging::info("offline mode: serving the persistent cache without contacting Jira");
+        None
+    } else {
+        let jira = JiraClient::new_with_metrics(
+            app_config.jira.base_url,
+            app_config.jira.email,
+            app_config.jira.api_token,
+            Arc::clone(&metrics),
+        )?
+        .with_max_in_flight(app_config.sync.concurrency);
+        logging::info(format!("using jira base url {}", jira.base_url));
+        Some(Arc::new(jira))
+    };
 
+    let persistent = open_persistent_cache(&app_config.cache)?;
     logging::info(format!(
-        "persistent cache enabled at {}",
-        app_config.cache.db_path
+        "persistent cache enabled at {}{}",
+        app_config.cache.db_path,
+        if persistent.is_encrypted() {
+            " (encrypted)"
+        } else {
+            ""
+        }
     ));
-    let cache = Arc::new(InMemoryCache::with_persistence(
-        Duration::from_secs(ttl_secs),
-        Duration::from_secs(ttl_secs),
-        Path::new(&app_config.cache.db_path),
-        Arc::clone(&metrics),
-    )?);
+    let memory_limit_bytes = app_config.cache.memory_limit_mb.saturating_mul(1024 * 1024);
+    let cache = Arc::new(
+        InMemoryCache::with_persistent_cache(
+            Duration::from_secs(ttl_secs),
+            Duration::from_secs(ttl_secs),
+            persistent,
+            Arc::clone(&metrics),
+        )
+        .with_memory_limit(usize::try_from(memory_limit_bytes).unwrap_or(usize::MAX)),
+    );
 
     let mut hydrated_workspaces = 0usize;
     for (workspace, _) in &workspaces {
@@ -356,18 +664,79 @@ fn run() -> Result<(), Box<dyn std::error::Error>> {
         "hydrated {} workspace listings from persistent cache",
         hydrated_workspaces
     ));
+    if jira.is_none() && hydrated_workspaces == 0 {
+        logging::warn(format!(
+            "offline mount has no cached listings in {}; workspaces will be empty",
+            app_config.cache.db_path
+        ));
+    }
 
-    let sync_state = Arc::new(SyncState::new(Duration::from_secs(sync_interval_secs)));
-    logging::info("initial sync will start right after mount");
-    sync_state.mark_sync_complete();
-
-    let _sync_thread = spawn_periodic_sync(
-        Arc::clone(&jira),
-        Arc::clone(&cache),
-        workspaces.clone(),
-        sync_budget,
-        Arc::clone(&sync_state),
+    let workspace_intervals: Vec<(String, Duration)> = workspaces
+        .iter()
+        .map(|(name, config)| {
+            let secs = config.interval_secs.unwrap_or(sync_interval_secs);
+            (name.clone(), Duration::from_secs(secs))
+        })
+        .collect();
+    let sync_state = Arc::new(
+        SyncState::new(Duration::from_secs(sync_interval_secs))
+            .with_workspace_intervals(workspace_intervals),
     );
+    let workspace_names: Vec<String> = workspaces.iter().map(|(name, _)| name.clone()).collect();
+    if jira.is_some() {
+        logging::info("initial sync will start right after mount");
+        sync_state.mark_sync_complete();
+        sync_state.mark_workspaces_synced(&workspace_names);
+    }
+
+    let _sync_thread = jira.as_ref().map(|jira| {
+        spawn_periodic_sync(
+            Arc::clone(jira),
+            Arc::clone(&cache),
+            workspaces.clone(),
+            sync_options.clone(),
+            Arc::clone(&sync_state),
+            !app_config.webhooks.disable_polling,
+        )
+    });
+    if jira.is_some() && app_config.webhooks.disable_polling {
+        logging::info("periodic polling disabled; relying on webhooks and manual syncs");
+    }
+    let _refresh_thread = jira.as_ref().map(|jira| {
+        spawn_issue_refresher(
+            Arc::clone(jira),
+            Arc::clone(&cache),
+            workspaces.clone(),
+            sync_options.clone(),
+            Arc::clone(&sync_state),
+        )
+    });
+
+    // Offline mounts cannot re-fetch what gc would remove.
+    let _gc_thread = jira.as_ref().map(|_| {
+        spawn_cache_gc(
+            Arc::clone(&cache),
+            gc_policy(&app_config.cache),
+            Duration::from_secs(app_config.cache.gc_interval_secs),
+        )
+    });
+
+    let _webhook_thread = match (
+        app_config.webhooks.listen.as_deref(),
+        app_config.webhooks.secret.clone(),
+    ) {
+        (Some(_), Some(_)) if jira.is_none() => {
+            logging::info("offline mode: not starting the webhook listener");
+            None
+        }
+        (Some(listen), Some(secret)) => Some(webhooks::spawn(
+            listen,
+            secret,
+            Arc::clone(&sync_state),
+            Arc::clone(&cache),
+        )?),
+        _ => None,
+    };
 
     logging::info(format!(
         "mounting filesystem at {}",
@@ -377,19 +746,36 @@ fn run() -> Result<(), Box<dyn std::error::Error>> {
     let uid = unsafe { libc::geteuid() };
     let gid = unsafe { libc::getegid() };
     let mount_once = || -> std::io::Result<()> {
-        let fs = JiraFuseFs::new(
+        let mut fs = JiraFuseFs::new(
             uid,
             gid,
             workspaces.clone(),
-            Arc::clone(&jira),
+            jira.clone(),
             Arc::clone(&cache),
-            sync_budget,
+            sync_options.clone(),
             Arc::clone(&sync_state),
-        );
+        )
+        .with_xattr_write_back(xattr_write_back);
+        if let Some(jira) = &jira {
+            fs = fs.with_on_demand(OnDemandFetcher::new(
+                Arc::clone(jira),
+                Arc::clone(&cache),
+                workspaces.clone(),
+                sync_options.clone(),
+                sync_concurrency,
+                on_demand_deadline,
+            ));
+        }
 
         let mut config = Config::default();
         config.mount_options.extend(mount_options());
-        fuser::mount2(fs, &mountpoint_path, &config)
+        let session = fuser::spawn_mount2(fs, &mountpoint_path, &config)?;
+        let _invalidator = spawn_kernel_invalidator(
+            session.notifier(),
+            workspace_names.clone(),
+            cache.subscribe_changes(),
+        );
+        session.join()
     };
 
     match mount_once() {
@@ -415,6 +801,112 @@ fn run() -> Result<(), Box<dyn std::error::Error>> {
     Ok(())
 }
 
+/// Opens the persistent cache, loading its key when encryption is configured.
+fn open_persistent_cache(
+    config: &CacheConfig,
+) -> Result<PersistentCache, Box<dyn std::error::Error>> {
+    let key = config
+        .encryption
+        .as_ref()
+        .and_then(|encryption| encryption.key_source())
+        .map(|source| CacheKey::load(&source))
+        .transpose()?;
+    Ok(PersistentCache::open(
+        Path::new(&config.db_path),
+        key.as_ref(),
+    )?)
+}
+
+fn run_redact_check(
+    redactor: &Redactor,
+    config: &CacheConfig,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let db_path = &config.db_path;
+    let cache = open_persistent_cache(config)?;
+    let report = redactor.check_cache(&cache)?;
+
+    println!("rules: {}", redactor.rule_names().join(", "));
+    println!(
+        "scanned {} cached documents in {}; {} with matches",
+        report.documents_scanned,
+        db_path,
+        report.findings.len()
+    );
+    for (name, counts) in &report.findings {
+        println!("  {}: {}", name, format_rule_counts(counts));
+    }
+    if report.totals.is_empty() {
+        println!("totals: none");
+    } else {
+        println!("totals: {}", format_rule_counts(&report.totals));
+    }
+    Ok(())
+}
+
+fn run_search(terms: &str, config: &CacheConfig) -> Result<(), Box<dyn std::error::Error>> {
+    let cache = open_persistent_cache(config)?;
+    if cache.is_encrypted() {
+        return Err(
+            "search is unavailable: the persistent cache is encrypted and keeps no full-text index"
+                .into(),
+        );
+    }
+    let hits = cache.search_issues(terms, SEARCH_LIMIT)?;
+    if hits.is_empty() {
+        println!("no cached issues match {terms:?}");
+    }
+    for hit in hits {
+        let snippet = hit.snippet.split_whitespace().collect::<Vec<_>>().join(" ");
+        println!("{}\t{}", hit.key, snippet);
+    }
+    Ok(())
+}
+
+fn run_cache_gc(config: &CacheConfig) -> Result<(), Box<dyn std::error::Error>> {
+    let cache = open_persistent_cache(config)?;
+    let report = cache.gc(&gc_policy(config))?;
+    println!(
+        "{}: expired {} tombstoned issues, evicted {}; {} -> {} bytes",
+        config.db_path,
+        report.expired.len(),
+        report.evicted.len(),
+        report.bytes_before,
+        report.bytes_after
+    );
+    Ok(())
+}
+
+fn run_cache_rekey(
+    config: &CacheConfig,
+    new_key: &KeySource,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let mut cache = open_persistent_cache(config)?;
+    let key = CacheKey::load(new_key)?;
+    let rewritten = cache.rekey(&key)?;
+    println!(
+        "{}: re-encrypted {} cached documents; point [cache.encryption] at the new key before the next start",
+        config.db_path, rewritten
+    );
+    Ok(())
+}
+
+fn gc_policy(config: &CacheConfig) -> GcPolicy {
+    GcPolicy {
+        max_db_bytes: config
+            .max_db_size_mb
+            .map(|mb| mb.saturating_mul(1024 * 1024)),
+        tombstone_max_age: Duration::from_secs(config.tombstone_max_age_secs),
+    }
+}
+
+fn format_rule_counts(counts: &BTreeMap<String, usize>) -> String {
+    counts
+        .iter()
+        .map(|(rule, count)| format!("{rule}={count}"))
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
 fn cleanup_mountpoint(mountpoint: &Path) -> bool {
     #[cfg(target_os = "linux")]
     {
@@ -498,13 +990,14 @@ mod tests {
             OsString::from("250"),
             OsString::from("--logging-debug"),
             OsString::from("true"),
+            OsString::from("--offline"),
             OsString::from("/tmp/mount"),
         ];
 
         let cli = parse_cli_args(args)
             .expect("cli should parse")
             .expect("expected run arguments");
-        assert_eq!(cli.mountpoint, PathBuf::from("/tmp/mount"));
+        assert_eq!(cli.command, CliCommand::Mount(PathBuf::from("/tmp/mount")));
         assert_eq!(cli.config_path, Some(PathBuf::from("/tmp/custom.toml")));
         assert_eq!(
             cli.overrides.jira_base_url,
@@ -512,6 +1005,7 @@ mod tests {
         );
         assert_eq!(cli.overrides.sync_budget, Some(250));
         assert_eq!(cli.overrides.logging_debug, Some(true));
+        assert_eq!(cli.overrides.offline, Some(true));
     }
 
     #[test]
@@ -546,6 +1040,158 @@ mod tests {
         );
     }
 
+    #[test]
+    fn cli_parses_redact_check_command() {
+        let args = vec![
+            OsString::from("jirafs"),
+            OsString::from("redact-check"),
+            OsString::from("-c"),
+            OsString::from("/tmp/custom.toml"),
+        ];
+
+        let cli = parse_cli_args(args)
+            .expect("cli should parse")
+            .expect("expected run arguments");
+        assert_eq!(cli.command, CliCommand::RedactCheck);
+        assert_eq!(cli.config_path, Some(PathBuf::from("/tmp/custom.toml")));
+
+        let mount = parse_cli_args(vec![
+            OsString::from("jirafs"),
+            OsString::from("--"),
+            OsString::from("redact-check"),
+        ])
+        .expect("cli should parse")
+        .expect("expected run arguments");
+        assert_eq!(
+            mount.command,
+            CliCommand::Mount(PathBuf::from("redact-check"))
+        );
+    }
+
+    #[test]
+    fn cli_parses_search_terms() {
+        let cli = parse_cli_args(vec![
+            OsString::from("jirafs"),
+            OsString::from("search"),
+            OsString::from("login"),
+            OsString::from("-c"),
+            OsString::from("/tmp/custom.toml"),
+            OsString::from("timeout"),
+        ])
+        .expect("cli should parse")
+        .expect("expected run arguments");
+        assert_eq!(cli.command, CliCommand::Search("login timeout".into()));
+        assert_eq!(cli.config_path, Some(PathBuf::from("/tmp/custom.toml")));
+
+        let err = parse_cli_args(vec![OsString::from("jirafs"), OsString::from("search")])
+            .expect_err("terms are required");
+        assert!(err.contains("at least one term"));
+    }
+
+    #[test]
+    fn cli_parses_cache_gc_command() {
+        let cli = parse_cli_args(vec![
+            OsString::from("jirafs"),
+            OsString::from("cache"),
+            OsString::from("gc"),
+            OsString::from("--cache-max-db-size-mb"),
+            OsString::from("512"),
+        ])
+        .expect("cli should parse")
+        .expect("expected run arguments");
+        assert_eq!(cli.command, CliCommand::CacheGc);
+        assert_eq!(cli.overrides.cache_max_db_size_mb, Some(512));
+
+        let err = parse_cli_args(vec![
+            OsString::from("jirafs"),
+            OsString::from("cache"),
+            OsString::from("shrink"),
+        ])
+        .expect_err("unknown cache command");
+        assert!(err.contains("unknown cache command"));
+    }
+
+    #[test]
+    fn cli_parses_cache_rekey_command() {
+        let cli = parse_cli_args(vec![
+            OsString::from("jirafs"),
+            OsString::from("cache"),
+            OsString::from("rekey"),
+            OsString::from("--new-key-file"),
+            OsString::from("/etc/jirafs/new.key"),
+        ])
+        .expect("cli should parse")
+        .expect("expected run arguments");
+        assert_eq!(
+            cli.command,
+            CliCommand::CacheRekey(KeySource::File(PathBuf::from("/etc/jirafs/new.key")))
+        );
+
+        let err = parse_cli_args(vec![
+            OsString::from("jirafs"),
+            OsString::from("cache"),
+            OsString::from("rekey"),
+        ])
+        .expect_err("new key is required");
+        assert!(err.contains("--new-key-env"));
+
+        let err = parse_cli_args(vec![
+            OsString::from("jirafs"),
+            OsString::from("--new-key-env"),
+            OsString::from("KEY"),
+            OsString::from("/tmp/mnt"),
+        ])
+        .expect_err("only for rekey");
+        assert!(err.contains("only apply to cache rekey"));
+    }
+
+    #[test]
+    fn search_runs_without_jira_credentials() {
+        let dir = std::env::temp_dir().join(format!("jirafs-cli-search-{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        std::fs::create_dir_all(&dir).expect("temp dir");
+        let config_path = dir.join("config.toml");
+        std::fs::write(
+            &config_path,
+            format!(
+                r#"
+                [jira]
+                base_url = "https://example.atlassian.net"
+                email = "you@example.com"
+                api_token = ""
+                [jira.workspaces.default]
+                jql = "project = PROJ"
+
+                [cache]
+                db_path = "{}"
+                "#,
+                dir.join("cache.db").display()
+            ),
+        )
+        .expect("write config");
+
+        let mut cli = parse_cli_args(vec![
+            OsString::from("jirafs"),
+            OsString::from("search"),
+            OsString::from("login"),
+            OsString::from("-c"),
+            config_path.clone().into_os_string(),
+        ])
+        .expect("cli should parse")
+        .expect("expected run arguments");
+        let app_config = load_config(&mut cli).expect("search needs no credentials");
+        run_search("login", &app_config.cache).expect("search runs");
+
+        let mut mount = CliArgs {
+            command: CliCommand::Mount(dir.join("mnt")),
+            config_path: Some(config_path),
+            overrides: AppConfigOverrides::default(),
+        };
+        assert!(load_config(&mut mount).is_err());
+
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
     #[test]
     fn cli_help_flag_returns_help_result() {
         let args = vec![OsString::from("jirafs"), OsString::from("--help")];
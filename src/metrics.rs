@@ -10,6 +10,8 @@ pub struct Metrics {
     stale_served: AtomicU64,
     api_requests: AtomicU64,
     retries: AtomicU64,
+    memory_hits: AtomicU64,
+    memory_evictions: AtomicU64,
 }
 
 impl Metrics {
@@ -37,6 +39,16 @@ impl Metrics {
         self.retries.fetch_add(1, Ordering::Relaxed);
     }
 
+    /// Counts an issue read served from the in-memory cache.
+    pub fn inc_memory_hit(&self) {
+        self.memory_hits.fetch_add(1, Ordering::Relaxed);
+    }
+
+    /// Counts issues dropped from memory to stay under the memory limit.
+    pub fn add_memory_evictions(&self, count: u64) {
+        self.memory_evictions.fetch_add(count, Ordering::Relaxed);
+    }
+
     pub fn snapshot(&self) -> (u64, u64, u64, u64, u64) {
         (
             self.cache_hits.load(Ordering::Relaxed),
@@ -46,15 +58,24 @@ impl Metrics {
             self.retries.load(Ordering::Relaxed),
         )
     }
+
+    /// Returns `(memory_hits, memory_evictions)`.
+    pub fn memory_snapshot(&self) -> (u64, u64) {
+        (
+            self.memory_hits.load(Ordering::Relaxed),
+            self.memory_evictions.load(Ordering::Relaxed),
+        )
+    }
 }
 
 pub fn spawn_metrics_logger(metrics: Arc<Metrics>, interval: Duration) {
     thread::spawn(move || loop {
         thread::sleep(interval);
         let (hits, misses, stale, api, retries) = metrics.snapshot();
+        let (memory_hits, evictions) = metrics.memory_snapshot();
         eprintln!(
-            "metrics cache_hit={} cache_miss={} stale_served={} api_requests={} retries={}",
-            hits, misses, stale, api, retries
+            "metrics cache_hit={} cache_miss={} stale_served={} api_requests={} retries={} memory_hit={} memory_evictions={}",
+            hits, misses, stale, api, retries, memory_hits, evictions
         );
     });
 }
@@ -0,0 +1,204 @@
+use std::collections::HashMap;
+use std::sync::mpsc;
+use std::sync::{Arc, Mutex, MutexGuard};
+use std::thread;
+use std::time::Duration;
+
+use crate::cache::InMemoryCache;
+use crate::config::WorkspaceConfig;
+use crate::jira::JiraClient;
+use crate::logging;
+use crate::warmup::{fetch_issue, SyncOptions};
+
+type Waiters = HashMap<String, Vec<mpsc::Sender<bool>>>;
+
+/// Fetches issues that are read before sync has cached them.
+///
+/// Fetches run on a fixed pool of workers and concurrent requests for the
+/// same key share one Jira call. Callers get their answer through a callback
+/// on a short-lived waiter thread, so the FUSE session thread never blocks.
+#[derive(Debug)]
+pub struct OnDemandFetcher {
+    jobs: Mutex<mpsc::Sender<String>>,
+    waiters: Arc<Mutex<Waiters>>,
+    deadline: Duration,
+}
+
+impl OnDemandFetcher {
+    /// Starts `workers` fetch threads.
+    pub fn new(
+        jira: Arc<JiraClient>,
+        cache: Arc<InMemoryCache>,
+        workspaces: Vec<(String, WorkspaceConfig)>,
+        options: SyncOptions,
+        workers: usize,
+        deadline: Duration,
+    ) -> Self {
+        let (tx, rx) = mpsc::channel::<String>();
+        let rx = Arc::new(Mutex::new(rx));
+        let waiters: Arc<Mutex<Waiters>> = Arc::new(Mutex::new(HashMap::new()));
+        let context = Arc::new((jira, cache, workspaces, options));
+
+        for _ in 0..workers.max(1) {
+            let rx = Arc::clone(&rx);
+            let waiters = Arc::clone(&waiters);
+            let context = Arc::clone(&context);
+            thread::spawn(move || loop {
+                let next = lock_or_recover(&rx, "on-demand queue").recv();
+                let Ok(key) = next else {
+                    break;
+                };
+                let (jira, cache, workspaces, options) = &*context;
+                let fetched = match fetch_issue(jira, cache, workspaces, options, &key) {
+                    Ok(()) => {
+                        logging::debug(format!("fetched uncached issue {} on demand", key));
+                        true
+                    }
+                    Err(err) => {
+                        logging::warn(format!("on-demand fetch failed for {}: {}", key, err));
+                        false
+                    }
+                };
+                let pending = lock_or_recover(&waiters, "on-demand waiters").remove(&key);
+                for waiter in pending.unwrap_or_default() {
+                    let _ = waiter.send(fetched);
+                }
+            });
+        }
+
+        Self {
+            jobs: Mutex::new(tx),
+            waiters,
+            deadline,
+        }
+    }
+
+    /// Fetches `key` and calls `done` once it is cached, or with `false`
+    /// when the fetch fails or the deadline passes first.
+    pub fn fetch_then<F>(&self, key: &str, done: F)
+    where
+        F: FnOnce(bool) + Send + 'static,
+    {
+        let rx = self.enqueue(key);
+        let deadline = self.deadline;
+        let key = key.to_string();
+        thread::spawn(move || {
+            let fetched = rx.recv_timeout(deadline).unwrap_or_else(|_| {
+                logging::debug(format!(
+                    "on-demand fetch for {} missed its {}ms deadline",
+                    key,
+                    deadline.as_millis()
+                ));
+                false
+            });
+            done(fetched);
+        });
+    }
+
+    fn enqueue(&self, key: &str) -> mpsc::Receiver<bool> {
+        let (tx, rx) = mpsc::channel();
+        let first = {
+            let mut waiters = lock_or_recover(&self.waiters, "on-demand waiters");
+            let pending = waiters.entry(key.to_string()).or_default();
+            pending.push(tx);
+            pending.len() == 1
+        };
+        if first {
+            let _ = lock_or_recover(&self.jobs, "on-demand jobs").send(key.to_string());
+        }
+        rx
+    }
+}
+
+fn lock_or_recover<'a, T>(mutex: &'a Mutex<T>, name: &str) -> MutexGuard<'a, T> {
+    match mutex.lock() {
+        Ok(guard) => guard,
+        Err(poisoned) => {
+            logging::warn(format!("recovering poisoned mutex: {}", name));
+            poisoned.into_inner()
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::metrics::Metrics;
+    use httpmock::prelude::*;
+    use std::path::Path;
+    use std::time::Instant;
+
+    fn fetcher(server: &MockServer, deadline: Duration) -> (OnDemandFetcher, Arc<InMemoryCache>) {
+        let jira =
+            Arc::new(JiraClient::new(server.base_url(), "e".into(), "t".into()).expect("client"));
+        let cache = Arc::new(
+            InMemoryCache::with_persistence(
+                Duration::from_secs(30),
+                Duration::from_secs(30),
+                Path::new(":memory:"),
+                Arc::new(Metrics::new()),
+            )
+            .expect("cache"),
+        );
+        let fetcher = OnDemandFetcher::new(
+            jira,
+            Arc::clone(&cache),
+            Vec::new(),
+            SyncOptions::default(),
+            2,
+            deadline,
+        );
+        (fetcher, cache)
+    }
+
+    #[test]
+    fn fetches_missing_issue_once_for_concurrent_readers() {
+        let server = MockServer::start();
+        let issue = server.mock(|when, then| {
+            when.method(GET).path("/rest/api/3/issue/OD-1");
+            then.status(200)
+                .delay(Duration::from_millis(100))
+                .json_body_obj(&serde_json::json!({
+                    "key": "OD-1",
+                    "fields": {"summary": "Fetched on demand"}
+                }));
+        });
+        let (fetcher, cache) = fetcher(&server, Duration::from_secs(5));
+
+        let (tx, rx) = mpsc::channel();
+        for _ in 0..3 {
+            let tx = tx.clone();
+            fetcher.fetch_then("OD-1", move |fetched| {
+                let _ = tx.send(fetched);
+            });
+        }
+        for _ in 0..3 {
+            assert!(rx.recv_timeout(Duration::from_secs(5)).expect("answered"));
+        }
+        issue.assert_hits(1);
+        let markdown = cache
+            .get_issue_markdown_stale_safe("OD-1", || Err(()))
+            .expect("cached");
+        assert!(String::from_utf8_lossy(&markdown).contains("Fetched on demand"));
+    }
+
+    #[test]
+    fn gives_up_at_the_deadline() {
+        let server = MockServer::start();
+        server.mock(|when, then| {
+            when.method(GET).path("/rest/api/3/issue/OD-2");
+            then.status(200)
+                .delay(Duration::from_millis(800))
+                .json_body_obj(&serde_json::json!({"key": "OD-2", "fields": {}}));
+        });
+        let (fetcher, _cache) = fetcher(&server, Duration::from_millis(100));
+
+        let started = Instant::now();
+        let (tx, rx) = mpsc::channel();
+        fetcher.fetch_then("OD-2", move |fetched| {
+            let _ = tx.send(fetched);
+        });
+        assert!(!rx.recv_timeout(Duration::from_secs(5)).expect("answered"));
+        assert!(started.elapsed() < Duration::from_millis(700));
+    }
+}
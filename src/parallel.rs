@@ -0,0 +1,103 @@
+use std::sync::{mpsc, Mutex, MutexGuard};
+use std::thread;
+
+use crate::logging;
+
+/// Runs `work` over `items` on up to `workers` scoped threads.
+///
+/// Each result is handed to `on_result` on the calling thread as soon as it
+/// completes, so callers can keep cache and cursor writes single-threaded.
+/// With one worker, or a single item, everything runs inline.
+pub(crate) fn for_each_concurrent<T, R, W, C>(
+    items: Vec<T>,
+    workers: usize,
+    work: W,
+    mut on_result: C,
+) where
+    T: Send,
+    R: Send,
+    W: Fn(T) -> R + Sync,
+    C: FnMut(R),
+{
+    let workers = workers.max(1).min(items.len());
+    if workers <= 1 {
+        for item in items {
+            on_result(work(item));
+        }
+        return;
+    }
+
+    let queue = Mutex::new(items.into_iter());
+    let (tx, rx) = mpsc::channel();
+    thread::scope(|scope| {
+        for _ in 0..workers {
+            let tx = tx.clone();
+            let queue = &queue;
+            let work = &work;
+            scope.spawn(move || loop {
+                let next = lock_or_recover(queue, "parallel queue").next();
+                let Some(item) = next else {
+                    break;
+                };
+                if tx.send(work(item)).is_err() {
+                    break;
+                }
+            });
+        }
+        drop(tx);
+        for result in rx {
+            on_result(result);
+        }
+    });
+}
+
+/// Maps `items` through `work` on up to `workers` threads, preserving order.
+pub(crate) fn map_concurrent<T, R, W>(items: Vec<T>, workers: usize, work: W) -> Vec<R>
+where
+    T: Send,
+    R: Send,
+    W: Fn(T) -> R + Sync,
+{
+    let mut slots: Vec<Option<R>> = (0..items.len()).map(|_| None).collect();
+    for_each_concurrent(
+        items.into_iter().enumerate().collect(),
+        workers,
+        |(idx, item)| (idx, work(item)),
+        |(idx, result)| slots[idx] = Some(result),
+    );
+    slots.into_iter().flatten().collect()
+}
+
+fn lock_or_recover<'a, T>(mutex: &'a Mutex<T>, name: &'static str) -> MutexGuard<'a, T> {
+    match mutex.lock() {
+        Ok(guard) => guard,
+        Err(poisoned) => {
+            logging::warn(format!("recovering poisoned mutex: {}", name));
+            poisoned.into_inner()
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::time::Duration;
+
+    #[test]
+    fn runs_work_concurrently_and_preserves_order() {
+        let in_flight = AtomicUsize::new(0);
+        let peak = AtomicUsize::new(0);
+        let out = map_concurrent((0..8).collect(), 4, |n: usize| {
+            let now = in_flight.fetch_add(1, Ordering::SeqCst) + 1;
+            peak.fetch_max(now, Ordering::SeqCst);
+            thread::sleep(Duration::from_millis(20));
+            in_flight.fetch_sub(1, Ordering::SeqCst);
+            n * 2
+        });
+
+        assert_eq!(out, vec![0, 2, 4, 6, 8, 10, 12, 14]);
+        let peak = peak.load(Ordering::SeqCst);
+        assert!(peak > 1 && peak <= 4, "peak concurrency {peak}");
+    }
+}
@@ -1,96 +1,268 @@
-use std::sync::OnceLock;
+pub mod template;
 
-use chrono::{DateTime, SecondsFormat, Utc};
-use regex::Regex;
+use std::collections::HashMap;
+
+use chrono::SecondsFormat;
+use serde::Deserialize;
 use serde_json::Value;
 
-use crate::jira::IssueData;
+use crate::jira::{parse_jira_timestamp, IssueComment, IssueData};
+use crate::redaction;
+use template::{IssueTemplate, TemplateContext, TemplateValue};
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, Deserialize)]
+#[serde(try_from = "InlineCommentsSetting")]
+/// How many comments are embedded in `KEY.md` under `## Comments`.
+///
+/// Ordered from least to most inclusive so the widest mode wins when an issue
+/// belongs to several workspaces.
+pub enum InlineComments {
+    /// Only a pointer to the `KEY.comments.md` sidecar.
+    #[default]
+    None,
+    /// The most recent N comments.
+    Last(usize),
+    /// Every comment.
+    All,
+}
+
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum InlineCommentsSetting {
+    Count(usize),
+    Keyword(String),
+}
+
+impl TryFrom<InlineCommentsSetting> for InlineComments {
+    type Error = String;
+
+    fn try_from(value: InlineCommentsSetting) -> Result<Self, Self::Error> {
+        match value {
+            InlineCommentsSetting::Count(0) => Ok(Self::None),
+            InlineCommentsSetting::Count(count) => Ok(Self::Last(count)),
+            InlineCommentsSetting::Keyword(keyword) => {
+                match keyword.trim().to_ascii_lowercase().as_str() {
+                    "none" => Ok(Self::None),
+                    "all" => Ok(Self::All),
+                    _ => Err(format!(
+                        "invalid inline_comments value '{keyword}': expected \"none\", \"all\", or a number"
+                    )),
+                }
+            }
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default)]
+/// Options controlling how `KEY.md` is rendered.
+pub struct RenderOptions<'a> {
+    pub template: Option<&'a IssueTemplate>,
+    pub inline_comments: InlineComments,
+}
 
 pub fn render_issue_markdown(issue: &IssueData) -> String {
-    let summary = redact_secrets(issue.summary.as_deref().unwrap_or("(no summary)"));
-    let status = canonical_status(issue.status.as_deref());
-    let issue_type = canonical_type(issue.issue_type.as_deref());
-    let priority = canonical_priority(issue.priority.as_deref());
-    let assignee = redact_secrets(issue.assignee.as_deref().unwrap_or("unassigned"));
-    let reporter = redact_secrets(issue.reporter.as_deref().unwrap_or("unknown"));
-    let labels = issue
-        .labels
-        .iter()
-        .map(|label| redact_secrets(label))
-        .collect::<Vec<_>>();
-    let created_at = normalize_iso_utc(issue.created.as_deref());
-    let updated_at = normalize_iso_utc(issue.updated.as_deref());
-    let due_at = normalize_iso_utc(issue.due_at.as_deref());
-    let description = adf_to_markdown(&issue.description);
-    let (acceptance_criteria, implementation_notes) = split_acceptance_criteria(&description);
+    render_issue_markdown_with(issue, &RenderOptions::default())
+}
 
-    let mut out = String::new();
-    out.push_str("---\n");
-    out.push_str(&format!("id: {}\n", issue.key));
-    out.push_str(&format!("project: {}\n", issue.project));
-    out.push_str(&format!("type: {}\n", issue_type));
-    out.push_str(&format!("status: {}\n", status));
-    out.push_str(&format!("priority: {}\n", priority));
-    out.push_str(&format!("assignee: {}\n", yaml_quote(&assignee)));
-    out.push_str(&format!("reporter: {}\n", yaml_quote(&reporter)));
-    out.push_str(&format!("labels: {}\n", yaml_array(&labels)));
-    out.push_str(&format!("created_at: {}\n", yaml_opt(&created_at)));
-    out.push_str(&format!("updated_at: {}\n", yaml_opt(&updated_at)));
-    out.push_str(&format!("parent: {}\n", yaml_opt(&issue.parent)));
-    out.push_str(&format!("epic: {}\n", yaml_opt(&issue.epic)));
-    out.push_str(&format!("blocks: {}\n", yaml_array(&issue.blocks)));
-    out.push_str(&format!("blocked_by: {}\n", yaml_array(&issue.blocked_by)));
-    out.push_str(&format!("relates_to: {}\n", yaml_array(&issue.relates_to)));
-    out.push_str(&format!("due_at: {}\n", yaml_opt(&due_at)));
-    out.push_str("version: 2\n");
-    out.push_str(&format!("source_url: {}\n", yaml_quote(&issue.source_url)));
-    out.push_str("---\n\n");
-
-    out.push_str("## Summary\n\n");
-    out.push_str(&summary);
-    out.push_str("\n\n");
-
-    out.push_str("## Acceptance Criteria\n\n");
-    if acceptance_criteria.is_empty() {
-        out.push_str("- [ ] TBD\n\n");
-    } else {
-        for line in acceptance_criteria {
-            out.push_str(&line);
+/// Renders issue markdown, using the template and inline comment mode from `options`.
+pub fn render_issue_markdown_with(issue: &IssueData, options: &RenderOptions<'_>) -> String {
+    let view = IssueView::from_issue(issue, options.inline_comments);
+    match options.template {
+        Some(template) => template.render(&view.template_context()),
+        None => view.render_default_layout(),
+    }
+}
+
+/// Redacted, normalized issue fields shared by the default layout and user templates.
+struct IssueView {
+    key: String,
+    project: String,
+    issue_type: &'static str,
+    status: &'static str,
+    priority: &'static str,
+    assignee: String,
+    reporter: String,
+    labels: Vec<String>,
+    created_at: Option<String>,
+    updated_at: Option<String>,
+    parent: Option<String>,
+    epic: Option<String>,
+    blocks: Vec<String>,
+    blocked_by: Vec<String>,
+    relates_to: Vec<String>,
+    due_at: Option<String>,
+    source_url: String,
+    summary: String,
+    description: String,
+    acceptance_criteria: Vec<String>,
+    implementation_notes: String,
+    attachments: Vec<String>,
+    comments: String,
+    comment_count: usize,
+}
+
+impl IssueView {
+    fn from_issue(issue: &IssueData, inline_comments: InlineComments) -> Self {
+        let description = adf_to_markdown(&issue.description);
+        let (acceptance_criteria, implementation_notes) = split_acceptance_criteria(&description);
+
+        Self {
+            key: issue.key.clone(),
+            project: issue.project.clone(),
+            issue_type: canonical_type(issue.issue_type.as_deref()),
+            status: canonical_status(issue.status.as_deref()),
+            priority: canonical_priority(issue.priority.as_deref()),
+            assignee: redact_secrets(issue.assignee.as_deref().unwrap_or("unassigned")),
+            reporter: redact_secrets(issue.reporter.as_deref().unwrap_or("unknown")),
+            labels: issue
+                .labels
+                .iter()
+                .map(|label| redact_secrets(label))
+                .collect(),
+            created_at: normalize_iso_utc(issue.created.as_deref()),
+            updated_at: normalize_iso_utc(issue.updated.as_deref()),
+            parent: issue.parent.clone(),
+            epic: issue.epic.clone(),
+            blocks: issue.blocks.clone(),
+            blocked_by: issue.blocked_by.clone(),
+            relates_to: issue.relates_to.clone(),
+            due_at: normalize_iso_utc(issue.due_at.as_deref()),
+            source_url: issue.source_url.clone(),
+            summary: redact_secrets(issue.summary.as_deref().unwrap_or("(no summary)")),
+            description,
+            acceptance_criteria,
+            implementation_notes,
+            attachments: issue
+                .attachments
+                .iter()
+                .map(|attachment| {
+                    format!(
+                        "- attachment: {} ({})",
+                        redact_secrets(&attachment.filename),
+                        attachment.id
+                    )
+                })
+                .collect(),
+            comments: render_inline_comments(issue, inline_comments),
+            comment_count: issue.comments.len(),
+        }
+    }
+
+    fn render_default_layout(&self) -> String {
+        let mut out = String::new();
+        out.push_str("---\n");
+        out.push_str(&format!("id: {}\n", self.key));
+        out.push_str(&format!("project: {}\n", self.project));
+        out.push_str(&format!("type: {}\n", self.issue_type));
+        out.push_str(&format!("status: {}\n", self.status));
+        out.push_str(&format!("priority: {}\n", self.priority));
+        out.push_str(&format!("assignee: {}\n", yaml_quote(&self.assignee)));
+        out.push_str(&format!("reporter: {}\n", yaml_quote(&self.reporter)));
+        out.push_str(&format!("labels: {}\n", yaml_array(&self.labels)));
+        out.push_str(&format!("created_at: {}\n", yaml_opt(&self.created_at)));
+        out.push_str(&format!("updated_at: {}\n", yaml_opt(&self.updated_at)));
+        out.push_str(&format!("parent: {}\n", yaml_opt(&self.parent)));
+        out.push_str(&format!("epic: {}\n", yaml_opt(&self.epic)));
+        out.push_str(&format!("blocks: {}\n", yaml_array(&self.blocks)));
+        out.push_str(&format!("blocked_by: {}\n", yaml_array(&self.blocked_by)));
+        out.push_str(&format!("relates_to: {}\n", yaml_array(&self.relates_to)));
+        out.push_str(&format!("due_at: {}\n", yaml_opt(&self.due_at)));
+        out.push_str("version: 2\n");
+        out.push_str(&format!("source_url: {}\n", yaml_quote(&self.source_url)));
+        out.push_str("---\n\n");
+
+        out.push_str("## Summary\n\n");
+        out.push_str(&self.summary);
+        out.push_str("\n\n");
+
+        out.push_str("## Acceptance Criteria\n\n");
+        out.push_str(&self.acceptance_criteria_text());
+        out.push_str("\n\n");
+
+        out.push_str("## Implementation Notes\n\n");
+        out.push_str(&self.implementation_notes_text());
+        out.push('\n');
+        if !self.attachments.is_empty() {
             out.push('\n');
+            for attachment in &self.attachments {
+                out.push_str(attachment);
+                out.push('\n');
+            }
         }
         out.push('\n');
-    }
 
-    out.push_str("## Implementation Notes\n\n");
-    if implementation_notes.trim().is_empty() {
-        out.push_str("(none)\n");
-    } else {
-        out.push_str(implementation_notes.trim());
+        out.push_str("## Test Evidence\n\n");
+        out.push_str("(none yet)\n\n");
+
+        out.push_str("## Comments\n\n");
+        out.push_str(&self.comments);
         out.push('\n');
+
+        out
     }
-    if !issue.attachments.is_empty() {
-        out.push('\n');
-        for attachment in &issue.attachments {
-            out.push_str(&format!(
-                "- attachment: {} ({})\n",
-                redact_secrets(&attachment.filename),
-                attachment.id
-            ));
+
+    fn acceptance_criteria_text(&self) -> String {
+        if self.acceptance_criteria.is_empty() {
+            "- [ ] TBD".to_string()
+        } else {
+            self.acceptance_criteria.join("\n")
         }
     }
-    out.push('\n');
 
-    out.push_str("## Test Evidence\n\n");
-    out.push_str("(none yet)\n\n");
-
-    out.push_str("## Comments\n\n");
-    out.push_str(&format!(
-        "{} comment(s). See `{}.comments.md`.\n",
-        issue.comments.len(),
-        issue.key
-    ));
+    fn implementation_notes_text(&self) -> String {
+        if self.implementation_notes.trim().is_empty() {
+            "(none)".to_string()
+        } else {
+            self.implementation_notes.trim().to_string()
+        }
+    }
 
-    out
+    fn template_context(&self) -> TemplateContext {
+        let mut context = TemplateContext::default();
+        context.insert("key", TemplateValue::Text(self.key.clone()));
+        context.insert("project", TemplateValue::Text(self.project.clone()));
+        context.insert("type", TemplateValue::Text(self.issue_type.to_string()));
+        context.insert("status", TemplateValue::Text(self.status.to_string()));
+        context.insert("priority", TemplateValue::Text(self.priority.to_string()));
+        context.insert("assignee", TemplateValue::Text(self.assignee.clone()));
+        context.insert("reporter", TemplateValue::Text(self.reporter.clone()));
+        context.insert("labels", TemplateValue::List(self.labels.clone()));
+        context.insert(
+            "created_at",
+            TemplateValue::Optional(self.created_at.clone()),
+        );
+        context.insert(
+            "updated_at",
+            TemplateValue::Optional(self.updated_at.clone()),
+        );
+        context.insert("parent", TemplateValue::Optional(self.parent.clone()));
+        context.insert("epic", TemplateValue::Optional(self.epic.clone()));
+        context.insert("blocks", TemplateValue::List(self.blocks.clone()));
+        context.insert("blocked_by", TemplateValue::List(self.blocked_by.clone()));
+        context.insert("relates_to", TemplateValue::List(self.relates_to.clone()));
+        context.insert("due_at", TemplateValue::Optional(self.due_at.clone()));
+        context.insert("source_url", TemplateValue::Text(self.source_url.clone()));
+        context.insert("summary", TemplateValue::Text(self.summary.clone()));
+        context.insert("description", TemplateValue::Text(self.description.clone()));
+        context.insert(
+            "acceptance_criteria",
+            TemplateValue::Text(self.acceptance_criteria_text()),
+        );
+        context.insert(
+            "implementation_notes",
+            TemplateValue::Text(self.implementation_notes_text()),
+        );
+        context.insert(
+            "attachments",
+            TemplateValue::Text(self.attachments.join("\n")),
+        );
+        context.insert("comments", TemplateValue::Text(self.comments.clone()));
+        context.insert(
+            "comment_count",
+            TemplateValue::Text(self.comment_count.to_string()),
+        );
+        context
+    }
 }
 
 pub fn render_issue_comments_markdown(issue: &IssueData) -> String {
@@ -101,29 +273,171 @@ pub fn render_issue_comments_markdown(issue: &IssueData) -> String {
         return out;
     }
 
-    for (idx, comment) in issue.comments.iter().enumerate() {
-        let author = redact_secrets(comment.author_display_name.as_deref().unwrap_or("unknown"));
-        let created =
-            normalize_iso_utc(comment.created.as_deref()).unwrap_or_else(|| "unknown".to_string());
-        let body = adf_to_markdown(&comment.body);
-        out.push_str(&format!("## {}\n\n", idx + 1));
+    push_comment_entries(&mut out, &issue.comments, 1, "##");
+    out
+}
+
+fn render_inline_comments(issue: &IssueData, mode: InlineComments) -> String {
+    let total = issue.comments.len();
+    let shown = match mode {
+        InlineComments::None => {
+            return format!("{} comment(s). See `{}.comments.md`.", total, issue.key);
+        }
+        InlineComments::Last(count) => count.min(total),
+        InlineComments::All => total,
+    };
+    if total == 0 {
+        return "(no comments)".to_string();
+    }
+
+    let mut out = String::new();
+    if shown < total {
         out.push_str(&format!(
-            "- id: {}\n",
-            comment.id.clone().unwrap_or_default()
+            "Showing last {} of {} comment(s). Full thread in `{}.comments.md`.\n\n",
+            shown, total, issue.key
         ));
-        out.push_str(&format!("- author: {}\n", author));
-        out.push_str(&format!("- created_at: {}\n\n", created));
-        if body.trim().is_empty() {
-            out.push_str("(empty comment)\n\n");
-        } else {
-            out.push_str(body.trim());
-            out.push_str("\n\n");
+    }
+    let first = total - shown;
+    push_comment_entries(&mut out, &issue.comments[first..], first + 1, "###");
+    out.trim_end().to_string()
+}
+
+/// Appends numbered comment entries, starting at `first_number`, under `heading`-level titles.
+///
+/// Entries are numbered chronologically but replies are listed under their parent and
+/// quoted one `> ` level per reply depth.
+fn push_comment_entries(
+    out: &mut String,
+    comments: &[IssueComment],
+    first_number: usize,
+    heading: &str,
+) {
+    for (idx, depth) in thread_order(comments) {
+        let comment = &comments[idx];
+        let entry = render_comment_entry(comment, first_number + idx, heading);
+        if depth == 0 {
+            out.push_str(&entry);
+            continue;
+        }
+
+        let prefix = "> ".repeat(depth);
+        for line in entry.lines() {
+            if line.is_empty() {
+                out.push_str(prefix.trim_end());
+            } else {
+                out.push_str(&prefix);
+                out.push_str(line);
+            }
+            out.push('\n');
         }
+        out.push('\n');
+    }
+}
+
+fn render_comment_entry(comment: &IssueComment, number: usize, heading: &str) -> String {
+    let author = redact_secrets(comment.author_display_name.as_deref().unwrap_or("unknown"));
+    let created =
+        normalize_iso_utc(comment.created.as_deref()).unwrap_or_else(|| "unknown".to_string());
+    let updated = normalize_iso_utc(comment.updated.as_deref());
+    let body = adf_to_markdown(&comment.body);
+    let internal = comment.jsd_public == Some(false);
+
+    let mut visibility = Vec::new();
+    if internal {
+        visibility.push("internal".to_string());
+    }
+    if let Some(restriction) = &comment.visibility {
+        visibility.push(format!("restricted to {}", redact_secrets(restriction)));
     }
 
+    let mut out = String::new();
+    if visibility.is_empty() {
+        out.push_str(&format!("{} {}\n\n", heading, number));
+    } else {
+        out.push_str(&format!("{} {} [internal]\n\n", heading, number));
+    }
+    out.push_str(&format!(
+        "- id: {}\n",
+        comment.id.clone().unwrap_or_default()
+    ));
+    out.push_str(&format!("- author: {}\n", author));
+    out.push_str(&format!("- created_at: {}\n", created));
+    if let Some(updated) = updated.filter(|updated| *updated != created) {
+        let editor = comment
+            .update_author_display_name
+            .as_deref()
+            .map(redact_secrets)
+            .unwrap_or_else(|| author.clone());
+        out.push_str(&format!("- edited_at: {} by {}\n", updated, editor));
+    }
+    if let Some(parent_id) = &comment.parent_id {
+        out.push_str(&format!("- reply_to: {}\n", parent_id));
+    }
+    if !visibility.is_empty() {
+        out.push_str(&format!("- visibility: {}\n", visibility.join(", ")));
+    }
+    out.push('\n');
+    if body.trim().is_empty() {
+        out.push_str("(empty comment)\n\n");
+    } else {
+        out.push_str(body.trim());
+        out.push_str("\n\n");
+    }
     out
 }
 
+/// Orders comment indexes so replies follow their parent, paired with each reply depth.
+///
+/// Replies whose parent is not in `comments` stay in place at depth one.
+fn thread_order(comments: &[IssueComment]) -> Vec<(usize, usize)> {
+    let position: HashMap<&str, usize> = comments
+        .iter()
+        .enumerate()
+        .filter_map(|(idx, comment)| comment.id.as_deref().map(|id| (id, idx)))
+        .collect();
+    let parent_of = |idx: usize| {
+        comments[idx]
+            .parent_id
+            .as_deref()
+            .and_then(|id| position.get(id).copied())
+            .filter(|parent| *parent != idx)
+    };
+
+    let mut children: HashMap<usize, Vec<usize>> = HashMap::new();
+    let mut roots = Vec::new();
+    for idx in 0..comments.len() {
+        match parent_of(idx) {
+            Some(parent) => children.entry(parent).or_default().push(idx),
+            None => roots.push(idx),
+        }
+    }
+
+    let mut ordered = Vec::with_capacity(comments.len());
+    let mut visited = vec![false; comments.len()];
+    let mut stack: Vec<(usize, usize)> = roots
+        .iter()
+        .rev()
+        .map(|idx| (*idx, usize::from(comments[*idx].parent_id.is_some())))
+        .collect();
+    while let Some((idx, depth)) = stack.pop() {
+        if std::mem::replace(&mut visited[idx], true) {
+            continue;
+        }
+        ordered.push((idx, depth));
+        if let Some(replies) = children.get(&idx) {
+            stack.extend(replies.iter().rev().map(|reply| (*reply, depth + 1)));
+        }
+    }
+
+    // Reply cycles never reach a root; keep those comments flat rather than dropping them.
+    for (idx, seen) in visited.iter().enumerate() {
+        if !seen {
+            ordered.push((idx, 0));
+        }
+    }
+    ordered
+}
+
 fn split_acceptance_criteria(markdown: &str) -> (Vec<String>, String) {
     let mut criteria = Vec::new();
     let mut notes = Vec::new();
@@ -193,33 +507,7 @@ fn yaml_quote(v: &str) -> String {
 }
 
 fn normalize_iso_utc(raw: Option<&str>) -> Option<String> {
-    let value = raw?.trim();
-    if value.is_empty() {
-        return None;
-    }
-
-    if let Ok(ts) = DateTime::parse_from_rfc3339(value) {
-        return Some(
-            ts.with_timezone(&Utc)
-                .to_rfc3339_opts(SecondsFormat::Secs, true),
-        );
-    }
-
-    if let Ok(ts) = DateTime::parse_from_str(value, "%Y-%m-%dT%H:%M:%S%.f%z") {
-        return Some(
-            ts.with_timezone(&Utc)
-                .to_rfc3339_opts(SecondsFormat::Secs, true),
-        );
-    }
-
-    if let Ok(ts) = DateTime::parse_from_str(value, "%Y-%m-%dT%H:%M:%S%z") {
-        return Some(
-            ts.with_timezone(&Utc)
-                .to_rfc3339_opts(SecondsFormat::Secs, true),
-        );
-    }
-
-    None
+    parse_jira_timestamp(raw?).map(|ts| ts.to_rfc3339_opts(SecondsFormat::Secs, true))
 }
 
 fn adf_to_markdown(value: &Value) -> String {
@@ -334,28 +622,7 @@ fn extract_mark_link(marks: Option<&Value>) -> Option<String> {
 }
 
 fn redact_secrets(input: &str) -> String {
-    static BEARER: OnceLock<Regex> = OnceLock::new();
-    static ASSIGNMENT: OnceLock<Regex> = OnceLock::new();
-    static LONG_TOKEN: OnceLock<Regex> = OnceLock::new();
-
-    let mut out = input.to_string();
-    out = BEARER
-        .get_or_init(|| {
-            Regex::new(r"(?i)bearer\s+[A-Za-z0-9._\-]{16,}").expect("valid bearer regex")
-        })
-        .replace_all(&out, "Bearer [REDACTED]")
-        .to_string();
-    out = ASSIGNMENT
-        .get_or_init(|| {
-            Regex::new(r#"(?i)(api[_-]?key|token|secret|password)\s*[:=]\s*['\"]?[A-Za-z0-9._\-]{8,}['\"]?"#)
-                .expect("valid assignment regex")
-        })
-        .replace_all(&out, "$1=[REDACTED]")
-        .to_string();
-    LONG_TOKEN
-        .get_or_init(|| Regex::new(r"\b[A-Za-z0-9_\-]{32,}\b").expect("valid long token regex"))
-        .replace_all(&out, "[REDACTED]")
-        .to_string()
+    redaction::redact(input)
 }
 
 #[cfg(test)]
@@ -363,11 +630,10 @@ mod tests {
     use serde_json::json;
 
     use super::*;
-    use crate::jira::{IssueAttachment, IssueComment};
+    use crate::jira::IssueAttachment;
 
-    #[test]
-    fn renders_schema_v2_layout() {
-        let issue = IssueData {
+    fn sample_issue() -> IssueData {
+        IssueData {
             key: "ST-100".to_string(),
             project: "ST".to_string(),
             issue_type: Some("Story".to_string()),
@@ -375,6 +641,7 @@ mod tests {
             status: Some("In Progress".to_string()),
             priority: Some("High".to_string()),
             assignee: Some("Ada".to_string()),
+            assignee_account_id: Some("5b10ac8d82e05b22cc7d4ef5".to_string()),
             reporter: Some("Bob".to_string()),
             labels: vec!["sync".to_string()],
             created: Some("2026-02-21T00:00:00.000+0000".to_string()),
@@ -396,8 +663,14 @@ mod tests {
                 author_display_name: Some("Chad".to_string()),
                 body: json!({"type":"doc","content":[{"type":"paragraph","content":[{"type":"text","text":"Looks good"}]}]}),
                 created: Some("2026-02-21T02:00:00.000+0000".to_string()),
+                ..IssueComment::default()
             }],
-        };
+        }
+    }
+
+    #[test]
+    fn renders_schema_v2_layout() {
+        let issue = sample_issue();
 
         let rendered = render_issue_markdown(&issue);
         assert!(rendered.contains("id: ST-100"));
@@ -407,4 +680,94 @@ mod tests {
         assert!(rendered.contains("## Comments"));
         assert!(rendered.contains("ST-100.comments.md"));
     }
+
+    #[test]
+    fn template_replaces_default_layout() {
+        let template = IssueTemplate::parse(
+            "---\nid: {{key}}\nlabels: {{labels | yaml}}\n---\n\n## Definition of Done\n\n{{acceptance_criteria}}\n\n## Discussion\n\n{{comments}}\n",
+        )
+        .expect("template parses");
+
+        let options = RenderOptions {
+            template: Some(&template),
+            ..RenderOptions::default()
+        };
+        let rendered = render_issue_markdown_with(&sample_issue(), &options);
+        assert_eq!(
+            rendered,
+            "---\nid: ST-100\nlabels: [\"sync\"]\n---\n\n## Definition of Done\n\n- [ ] do thing\n\n## Discussion\n\n1 comment(s). See `ST-100.comments.md`.\n"
+        );
+        assert!(!rendered.contains("## Test Evidence"));
+    }
+
+    #[test]
+    fn inline_comments_embed_most_recent_entries() {
+        let mut issue = sample_issue();
+        issue.comments.push(IssueComment {
+            id: Some("11".to_string()),
+            author_display_name: Some("Dee".to_string()),
+            body: json!("Shipped"),
+            created: Some("2026-02-21T03:00:00.000+0000".to_string()),
+            ..IssueComment::default()
+        });
+
+        let last = RenderOptions {
+            inline_comments: InlineComments::Last(1),
+            ..RenderOptions::default()
+        };
+        let rendered = render_issue_markdown_with(&issue, &last);
+        assert!(rendered.contains(
+            "## Comments\n\nShowing last 1 of 2 comment(s). Full thread in `ST-100.comments.md`.\n\n### 2\n\n- id: 11\n- author: Dee\n- created_at: 2026-02-21T03:00:00Z\n\nShipped\n"
+        ));
+        assert!(!rendered.contains("Looks good"));
+
+        let all = RenderOptions {
+            inline_comments: InlineComments::All,
+            ..RenderOptions::default()
+        };
+        let rendered = render_issue_markdown_with(&issue, &all);
+        assert!(rendered.contains("### 1\n\n- id: 10"));
+        assert!(rendered.contains("### 2\n\n- id: 11"));
+        assert!(!rendered.contains("Showing last"));
+
+        let sidecar = render_issue_comments_markdown(&issue);
+        assert!(sidecar.starts_with("# ST-100 comments\n\n## 1\n\n- id: 10"));
+    }
+
+    #[test]
+    fn comments_show_edits_visibility_and_replies() {
+        let mut issue = sample_issue();
+        issue.comments[0].updated = Some("2026-02-21T02:30:00.000+0000".to_string());
+        issue.comments[0].update_author_display_name = Some("Dee".to_string());
+        issue.comments.push(IssueComment {
+            id: Some("11".to_string()),
+            author_display_name: Some("Eve".to_string()),
+            body: json!("Customer should not see this"),
+            created: Some("2026-02-21T03:00:00.000+0000".to_string()),
+            updated: Some("2026-02-21T03:00:00.000+0000".to_string()),
+            jsd_public: Some(false),
+            ..IssueComment::default()
+        });
+        issue.comments.push(IssueComment {
+            id: Some("12".to_string()),
+            author_display_name: Some("Fay".to_string()),
+            body: json!("Agreed"),
+            created: Some("2026-02-21T04:00:00.000+0000".to_string()),
+            parent_id: Some("10".to_string()),
+            visibility: Some("role:Developers".to_string()),
+            ..IssueComment::default()
+        });
+
+        let rendered = render_issue_comments_markdown(&issue);
+        assert_eq!(
+            rendered,
+            "# ST-100 comments\n\n\
+             ## 1\n\n- id: 10\n- author: Chad\n- created_at: 2026-02-21T02:00:00Z\n\
+             - edited_at: 2026-02-21T02:30:00Z by Dee\n\nLooks good\n\n\
+             > ## 3 [internal]\n>\n> - id: 12\n> - author: Fay\n> - created_at: 2026-02-21T04:00:00Z\n\
+             > - reply_to: 10\n> - visibility: restricted to role:Developers\n>\n> Agreed\n>\n\n\
+             ## 2 [internal]\n\n- id: 11\n- author: Eve\n- created_at: 2026-02-21T03:00:00Z\n\
+             - visibility: internal\n\nCustomer should not see this\n\n"
+        );
+    }
 }
@@ -0,0 +1,226 @@
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+
+use super::{yaml_array, yaml_opt, yaml_quote};
+
+/// Field names accepted inside `{{ ... }}` placeholders.
+pub const TEMPLATE_FIELDS: &[&str] = &[
+    "key",
+    "project",
+    "type",
+    "status",
+    "priority",
+    "assignee",
+    "reporter",
+    "labels",
+    "created_at",
+    "updated_at",
+    "parent",
+    "epic",
+    "blocks",
+    "blocked_by",
+    "relates_to",
+    "due_at",
+    "source_url",
+    "summary",
+    "description",
+    "acceptance_criteria",
+    "implementation_notes",
+    "attachments",
+    "comments",
+    "comment_count",
+];
+
+#[derive(Debug, thiserror::Error)]
+/// Errors returned while loading or parsing an [`IssueTemplate`].
+pub enum TemplateError {
+    #[error("failed to read issue template at {path}: {source}")]
+    ReadFailed {
+        path: PathBuf,
+        source: std::io::Error,
+    },
+    #[error("unclosed placeholder at byte {0} in issue template")]
+    Unclosed(usize),
+    #[error("unknown issue template field '{0}'")]
+    UnknownField(String),
+    #[error("unknown issue template filter '{0}'; expected 'yaml'")]
+    UnknownFilter(String),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Filter {
+    Raw,
+    Yaml,
+}
+
+#[derive(Debug, Clone)]
+enum Segment {
+    Literal(String),
+    Field { name: String, filter: Filter },
+}
+
+#[derive(Debug, Clone)]
+/// User-defined markdown layout for issue files.
+///
+/// Templates are plain markdown with `{{ field }}` placeholders. Appending
+/// `| yaml` (for example `{{ labels | yaml }}`) emits the value the way the
+/// default frontmatter does: quoted strings, `null`, or inline arrays.
+pub struct IssueTemplate {
+    segments: Vec<Segment>,
+}
+
+impl IssueTemplate {
+    /// Loads and parses a template file.
+    ///
+    /// # Errors
+    /// Returns [`TemplateError`] when the file cannot be read or fails to parse.
+    pub fn load(path: &Path) -> Result<Self, TemplateError> {
+        let source = std::fs::read_to_string(path).map_err(|source| TemplateError::ReadFailed {
+            path: path.to_path_buf(),
+            source,
+        })?;
+        Self::parse(&source)
+    }
+
+    /// Parses template source text.
+    ///
+    /// # Errors
+    /// Returns [`TemplateError`] for unclosed placeholders, unknown fields, or unknown filters.
+    pub fn parse(source: &str) -> Result<Self, TemplateError> {
+        let mut segments = Vec::new();
+        let mut rest = source;
+        let mut consumed = 0;
+
+        while let Some(open) = rest.find("{{") {
+            if open > 0 {
+                segments.push(Segment::Literal(rest[..open].to_string()));
+            }
+            let after_open = &rest[open + 2..];
+            let Some(close) = after_open.find("}}") else {
+                return Err(TemplateError::Unclosed(consumed + open));
+            };
+
+            let inner = &after_open[..close];
+            let (name, filter) = match inner.split_once('|') {
+                Some((name, filter)) => (name.trim(), parse_filter(filter.trim())?),
+                None => (inner.trim(), Filter::Raw),
+            };
+            if !TEMPLATE_FIELDS.contains(&name) {
+                return Err(TemplateError::UnknownField(name.to_string()));
+            }
+            segments.push(Segment::Field {
+                name: name.to_string(),
+                filter,
+            });
+
+            let advance = open + 2 + close + 2;
+            consumed += advance;
+            rest = &rest[advance..];
+        }
+
+        if !rest.is_empty() {
+            segments.push(Segment::Literal(rest.to_string()));
+        }
+
+        Ok(Self { segments })
+    }
+
+    pub(crate) fn render(&self, context: &TemplateContext) -> String {
+        let mut out = String::new();
+        for segment in &self.segments {
+            match segment {
+                Segment::Literal(text) => out.push_str(text),
+                Segment::Field { name, filter } => {
+                    if let Some(value) = context.values.get(name.as_str()) {
+                        out.push_str(&value.render(*filter));
+                    }
+                }
+            }
+        }
+        out
+    }
+}
+
+fn parse_filter(raw: &str) -> Result<Filter, TemplateError> {
+    match raw {
+        "yaml" => Ok(Filter::Yaml),
+        other => Err(TemplateError::UnknownFilter(other.to_string())),
+    }
+}
+
+#[derive(Debug, Clone)]
+pub(crate) enum TemplateValue {
+    Text(String),
+    Optional(Option<String>),
+    List(Vec<String>),
+}
+
+impl TemplateValue {
+    fn render(&self, filter: Filter) -> String {
+        match (self, filter) {
+            (Self::Text(value), Filter::Raw) => value.clone(),
+            (Self::Text(value), Filter::Yaml) => yaml_quote(value),
+            (Self::Optional(value), Filter::Raw) => value.clone().unwrap_or_default(),
+            (Self::Optional(value), Filter::Yaml) => yaml_opt(value),
+            (Self::List(values), Filter::Raw) => values.join(", "),
+            (Self::List(values), Filter::Yaml) => yaml_array(values),
+        }
+    }
+}
+
+#[derive(Debug, Default)]
+/// Field values an [`IssueTemplate`] is filled from.
+pub(crate) struct TemplateContext {
+    values: BTreeMap<&'static str, TemplateValue>,
+}
+
+impl TemplateContext {
+    pub(crate) fn insert(&mut self, name: &'static str, value: TemplateValue) {
+        self.values.insert(name, value);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn context() -> TemplateContext {
+        let mut context = TemplateContext::default();
+        context.insert("key", TemplateValue::Text("ST-1".to_string()));
+        context.insert("parent", TemplateValue::Optional(None));
+        context.insert(
+            "labels",
+            TemplateValue::List(vec!["a".to_string(), "b".to_string()]),
+        );
+        context
+    }
+
+    #[test]
+    fn renders_raw_and_yaml_placeholders() {
+        let template = IssueTemplate::parse(
+            "id: {{key}}\nparent: {{ parent | yaml }}\nlabels: {{labels|yaml}}\n# {{ key }} ({{labels}})\n",
+        )
+        .expect("template parses");
+
+        assert_eq!(
+            template.render(&context()),
+            "id: ST-1\nparent: null\nlabels: [\"a\", \"b\"]\n# ST-1 (a, b)\n"
+        );
+    }
+
+    #[test]
+    fn rejects_unknown_fields_and_filters() {
+        assert!(matches!(
+            IssueTemplate::parse("{{ nope }}"),
+            Err(TemplateError::UnknownField(name)) if name == "nope"
+        ));
+        assert!(matches!(
+            IssueTemplate::parse("{{ key | upper }}"),
+            Err(TemplateError::UnknownFilter(name)) if name == "upper"
+        ));
+        assert!(matches!(
+            IssueTemplate::parse("ok {{ key"),
+            Err(TemplateError::Unclosed(3))
+        ));
+    }
+}
@@ -1,5 +1,6 @@
-use std::sync::atomic::{AtomicBool, Ordering};
-use std::sync::{Mutex, MutexGuard};
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::sync::{Condvar, Mutex, MutexGuard};
 use std::time::{Duration, Instant};
 
 use crate::logging;
@@ -9,9 +10,14 @@ pub struct SyncState {
     last_sync: Mutex<Option<Instant>>,
     last_full_sync: Mutex<Option<Instant>>,
     sync_interval: Duration,
+    workspace_intervals: Vec<(String, Duration)>,
+    workspace_last_sync: Mutex<HashMap<String, Instant>>,
     manual_trigger: AtomicBool,
     manual_full_trigger: AtomicBool,
     sync_in_progress: AtomicBool,
+    refresh_queue: Mutex<Vec<String>>,
+    refresh_ready: Condvar,
+    allocation_start: AtomicUsize,
 }
 
 impl SyncState {
@@ -20,9 +26,44 @@ impl SyncState {
             last_sync: Mutex::new(None),
             last_full_sync: Mutex::new(None),
             sync_interval,
+            workspace_intervals: Vec::new(),
+            workspace_last_sync: Mutex::new(HashMap::new()),
             manual_trigger: AtomicBool::new(false),
             manual_full_trigger: AtomicBool::new(false),
             sync_in_progress: AtomicBool::new(false),
+            refresh_queue: Mutex::new(Vec::new()),
+            refresh_ready: Condvar::new(),
+            allocation_start: AtomicUsize::new(0),
+        }
+    }
+
+    /// Tracks workspaces individually, each with its own sync interval.
+    ///
+    /// Without this, every sync run covers all workspaces on `sync_interval`.
+    pub fn with_workspace_intervals(mut self, intervals: Vec<(String, Duration)>) -> Self {
+        self.workspace_intervals = intervals;
+        self
+    }
+
+    /// Returns the tracked workspaces whose interval has elapsed since their last sync.
+    pub fn due_workspaces(&self) -> Vec<String> {
+        let guard = lock_or_recover(&self.workspace_last_sync, "workspace_last_sync");
+        self.workspace_intervals
+            .iter()
+            .filter(|(name, interval)| {
+                guard
+                    .get(name)
+                    .is_none_or(|last| last.elapsed() >= *interval)
+            })
+            .map(|(name, _)| name.clone())
+            .collect()
+    }
+
+    pub fn mark_workspaces_synced(&self, names: &[String]) {
+        let now = Instant::now();
+        let mut guard = lock_or_recover(&self.workspace_last_sync, "workspace_last_sync");
+        for name in names {
+            guard.insert(name.clone(), now);
         }
     }
 
@@ -45,6 +86,20 @@ impl SyncState {
     }
 
     pub fn seconds_until_next_sync(&self) -> u64 {
+        if !self.workspace_intervals.is_empty() {
+            let guard = lock_or_recover(&self.workspace_last_sync, "workspace_last_sync");
+            return self
+                .workspace_intervals
+                .iter()
+                .map(|(name, interval)| {
+                    guard
+                        .get(name)
+                        .map_or(0, |last| interval.saturating_sub(last.elapsed()).as_secs())
+                })
+                .min()
+                .unwrap_or(0);
+        }
+
         let guard = lock_or_recover(&self.last_sync, "last_sync");
         match *guard {
             Some(last) => {
@@ -92,6 +147,54 @@ impl SyncState {
     pub fn is_sync_in_progress(&self) -> bool {
         self.sync_in_progress.load(Ordering::Relaxed)
     }
+
+    /// Returns the workspace index that gets the first budget turn this run.
+    ///
+    /// Rotating it keeps ties and partial pages from always favoring the
+    /// alphabetically first workspace.
+    pub fn next_allocation_start(&self, workspaces: usize) -> usize {
+        self.allocation_start.fetch_add(1, Ordering::Relaxed) % workspaces.max(1)
+    }
+
+    /// Queues issue keys for an immediate targeted refresh and returns how many were new.
+    pub fn request_issue_refresh<I>(&self, keys: I) -> usize
+    where
+        I: IntoIterator<Item = String>,
+    {
+        let mut guard = lock_or_recover(&self.refresh_queue, "refresh_queue");
+        let before = guard.len();
+        for key in keys {
+            if !guard.contains(&key) {
+                guard.push(key);
+            }
+        }
+        let added = guard.len() - before;
+        if added > 0 {
+            self.refresh_ready.notify_all();
+        }
+        added
+    }
+
+    /// Returns issue keys waiting for a targeted refresh.
+    pub fn pending_issue_refreshes(&self) -> Vec<String> {
+        lock_or_recover(&self.refresh_queue, "refresh_queue").clone()
+    }
+
+    /// Waits up to `timeout` for queued refresh keys and drains them.
+    pub fn wait_for_issue_refresh(&self, timeout: Duration) -> Vec<String> {
+        let guard = lock_or_recover(&self.refresh_queue, "refresh_queue");
+        let mut guard = match self
+            .refresh_ready
+            .wait_timeout_while(guard, timeout, |queue| queue.is_empty())
+        {
+            Ok((guard, _)) => guard,
+            Err(poisoned) => {
+                logging::warn("recovering poisoned mutex: refresh_queue");
+                poisoned.into_inner().0
+            }
+        };
+        std::mem::take(&mut *guard)
+    }
 }
 
 fn lock_or_recover<'a, T>(mutex: &'a Mutex<T>, name: &'static str) -> MutexGuard<'a, T> {
@@ -1,20 +1,32 @@
+use std::collections::{HashMap, HashSet};
+use std::str::FromStr;
 use std::sync::Arc;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
 
+use chrono::{DateTime, SecondsFormat, Utc};
+use chrono_tz::Tz;
 use regex::Regex;
+use serde::Deserialize;
 
-use crate::cache::InMemoryCache;
-use crate::jira::JiraClient;
+use crate::cache::{InMemoryCache, IssueAttributes, IssueCacheRow, IssueSidecarRow};
+use crate::config::WorkspaceConfig;
+use crate::jira::{parse_jira_timestamp, IssueData, IssueRef, JiraClient, JiraError};
 use crate::logging;
-use crate::render::{render_issue_comments_markdown, render_issue_markdown};
+use crate::parallel;
+use crate::render::template::IssueTemplate;
+use crate::render::{
+    render_issue_comments_markdown, render_issue_markdown_with, InlineComments, RenderOptions,
+};
+use crate::sync_state::SyncState;
 
 pub fn seed_workspace_listings(
     jira: &JiraClient,
     cache: &InMemoryCache,
-    workspaces: &[(String, String)],
+    workspaces: &[(String, WorkspaceConfig)],
 ) -> usize {
     let mut seeded = 0;
-    for (workspace, jql) in workspaces {
-        match jira.list_issue_refs_for_jql(jql) {
+    for (workspace, config) in workspaces {
+        match jira.list_issue_refs_for_jql(&config.jql) {
             Ok(items) => {
                 let count = items.len();
                 cache.upsert_workspace_issues(workspace, items);
@@ -32,19 +44,184 @@ pub fn seed_workspace_listings(
     seeded
 }
 
+/// Maximum number of keys hydrated per `key in (...)` query.
+const HYDRATE_BATCH_SIZE: usize = 50;
+
+/// Budget handed to each workspace per turn under [`AllocationStrategy::RoundRobin`].
+const ROUND_ROBIN_PAGE_SIZE: usize = 100;
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+/// How the per-run sync budget is split across workspaces.
+pub enum AllocationStrategy {
+    /// Proportional to each workspace's `weight` (default 1).
+    #[default]
+    Weighted,
+    /// Fixed-size pages handed out to each workspace in turn, ignoring weights.
+    RoundRobin,
+    /// Proportional to `weight` scaled by how often the workspace's issues are read.
+    Activity,
+}
+
+impl FromStr for AllocationStrategy {
+    type Err = String;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        match value.trim() {
+            "weighted" => Ok(Self::Weighted),
+            "round_robin" => Ok(Self::RoundRobin),
+            "activity" => Ok(Self::Activity),
+            other => Err(format!("invalid allocation strategy: {other}")),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+/// Allocation inputs for one workspace.
+pub struct WorkspaceShare {
+    pub weight: u32,
+    pub activity: u64,
+    pub cap: Option<usize>,
+}
+
+/// Splits `total` across workspaces according to `strategy`.
+///
+/// No workspace receives more than its `cap`; budget a capped workspace cannot
+/// use flows to the others. Leftover units after proportional rounding, and
+/// round-robin turns, start from workspace `start` and wrap around. Under
+/// [`AllocationStrategy::Activity`] every workspace with room gets at least
+/// one unit while the budget allows, so quiet workspaces still make progress.
+pub fn allocate_budget(
+    total: usize,
+    shares: &[WorkspaceShare],
+    strategy: AllocationStrategy,
+    start: usize,
+) -> Vec<usize> {
+    let mut allocated = vec![0; shares.len()];
+    if shares.is_empty() {
+        return allocated;
+    }
+    let room = |allocated: &[usize], idx: usize| {
+        shares[idx]
+            .cap
+            .map_or(usize::MAX, |cap| cap.saturating_sub(allocated[idx]))
+    };
+    let order: Vec<usize> = (0..shares.len())
+        .map(|offset| (start + offset) % shares.len())
+        .collect();
+    let mut remaining = total;
+
+    if strategy == AllocationStrategy::RoundRobin {
+        while remaining > 0 {
+            let mut progressed = false;
+            for &idx in &order {
+                let take = ROUND_ROBIN_PAGE_SIZE
+                    .min(room(&allocated, idx))
+                    .min(remaining);
+                if take > 0 {
+                    allocated[idx] += take;
+                    remaining -= take;
+                    progressed = true;
+                }
+            }
+            if !progressed {
+                break;
+            }
+        }
+        return allocated;
+    }
+
+    let weight = |idx: usize| -> u128 {
+        let base = u128::from(shares[idx].weight.max(1));
+        match strategy {
+            AllocationStrategy::Activity => base * (1 + u128::from(shares[idx].activity)),
+            _ => base,
+        }
+    };
+    let mut open: Vec<usize> = order
+        .iter()
+        .copied()
+        .filter(|&idx| room(&allocated, idx) > 0)
+        .collect();
+    while remaining > 0 && !open.is_empty() {
+        let total_weight: u128 = open.iter().map(|&idx| weight(idx)).sum();
+        let round = remaining as u128;
+        let mut fractions = Vec::with_capacity(open.len());
+        for &idx in &open {
+            let exact = round * weight(idx);
+            let floor = usize::try_from(exact / total_weight).unwrap_or(usize::MAX);
+            let take = floor.min(room(&allocated, idx));
+            allocated[idx] += take;
+            remaining -= take;
+            fractions.push((exact % total_weight, idx));
+        }
+        // Stable sort keeps the rotated order among equal remainders.
+        fractions.sort_by_key(|&(fraction, _)| std::cmp::Reverse(fraction));
+        for (_, idx) in fractions {
+            if remaining == 0 {
+                break;
+            }
+            if room(&allocated, idx) > 0 {
+                allocated[idx] += 1;
+                remaining -= 1;
+            }
+        }
+        let before = open.len();
+        open.retain(|&idx| room(&allocated, idx) > 0);
+        if open.len() == before {
+            break;
+        }
+    }
+
+    if strategy == AllocationStrategy::Activity {
+        for &idx in &order {
+            if allocated[idx] > 0 || room(&allocated, idx) == 0 {
+                continue;
+            }
+            let donor = (0..allocated.len())
+                .filter(|&other| allocated[other] > 1)
+                .max_by_key(|&other| allocated[other]);
+            let Some(donor) = donor else {
+                break;
+            };
+            allocated[donor] -= 1;
+            allocated[idx] += 1;
+        }
+    }
+    allocated
+}
+
+#[derive(Debug, Clone, Default)]
+/// Settings shared by every sync run.
+pub struct SyncOptions {
+    pub budget: usize,
+    pub template: Option<Arc<IssueTemplate>>,
+    pub reconcile_interval: Duration,
+    pub cursor_overlap: Duration,
+    pub allocation: AllocationStrategy,
+    pub concurrency: usize,
+}
+
 pub struct SyncResult {
     pub issues_cached: usize,
     pub issues_skipped: usize,
     pub errors: Vec<String>,
 }
 
+/// Syncs the workspaces named in `only`, or all of them for `None`.
+///
+/// Every workspace in `workspaces` is still consulted for inline comment
+/// modes, so an issue listed by a skipped workspace keeps its widest mode.
 pub fn sync_issues(
     jira: &JiraClient,
     cache: &Arc<InMemoryCache>,
-    workspaces: &[(String, String)],
-    budget: usize,
+    sync_state: &SyncState,
+    workspaces: &[(String, WorkspaceConfig)],
+    only: Option<&[String]>,
+    options: &SyncOptions,
     force_full: bool,
 ) -> SyncResult {
+    let budget = options.budget;
     let mut result = SyncResult {
         issues_cached: 0,
         issues_skipped: 0,
@@ -62,25 +239,53 @@ pub fn sync_issues(
         return result;
     }
 
-    for (workspace, base_jql) in workspaces {
+    let mut inline_modes = inline_comment_modes(cache, workspaces);
+    let mut account_tz: Option<Tz> = None;
+    let workspaces: Vec<&(String, WorkspaceConfig)> = workspaces
+        .iter()
+        .filter(|(name, _)| only.is_none_or(|names| names.contains(name)))
+        .collect();
+
+    let start = sync_state.next_allocation_start(workspaces.len());
+
+    // Phase 1: list only `key, updated` per workspace and queue what changed.
+    let mut fetches = Vec::new();
+    for (idx, (workspace, config)) in workspaces.iter().enumerate() {
+        let base_jql = &config.jql;
         let cursor = if force_full {
             None
         } else {
-            cache.get_sync_cursor(workspace)
+            cache
+                .get_sync_cursor(workspace)
+                .and_then(|raw| match parse_jira_timestamp(&raw) {
+                    Some(since) => Some(since),
+                    None => {
+                        logging::warn(format!(
+                            "ignoring unparseable sync cursor for workspace {}: {}",
+                            workspace, raw
+                        ));
+                        None
+                    }
+                })
         };
 
         let (base_filter, base_order) = split_jql_order_by(base_jql);
-        let jql = match &cursor {
+        let jql = match cursor {
             Some(since) => {
+                let tz = *account_tz.get_or_insert_with(|| jira.account_time_zone());
+                let bound = cursor_jql_bound(since, options.cursor_overlap, tz);
                 logging::info(format!(
-                    "incremental sync for workspace {} since {}",
-                    workspace, since
+                    "incremental sync for workspace {} since {} ({} {})",
+                    workspace,
+                    since.to_rfc3339_opts(SecondsFormat::Secs, true),
+                    bound,
+                    tz
                 ));
                 let order_clause =
                     base_order.unwrap_or_else(|| "ORDER BY updated DESC".to_string());
                 format!(
-                    "({}) AND updated > \"{}\" {}",
-                    base_filter, since, order_clause
+                    "({}) AND updated >= \"{}\" {}",
+                    base_filter, bound, order_clause
                 )
             }
             None => {
@@ -89,102 +294,632 @@ pub fn sync_issues(
             }
         };
 
-        let page_size = budget.min(100);
+        fetches.push(WorkspaceFetch { idx, jql, cursor });
+    }
 
-        match jira.search_issues_bulk(&jql, page_size) {
-            Ok(issues) => {
-                let latest_refs: Vec<_> = issues
-                    .iter()
-                    .map(|issue| crate::jira::IssueRef {
-                        key: issue.key.clone(),
-                        updated: issue.updated.clone(),
-                    })
-                    .collect();
+    // Listings are fetched on worker threads; listings, cursors, and the
+    // queue are only written here on the sync thread.
+    parallel::for_each_concurrent(
+        fetches,
+        options.concurrency,
+        |fetch| {
+            let listed = jira.list_issue_refs_for_jql(&fetch.jql);
+            (fetch, listed)
+        },
+        |(fetch, listed)| {
+            let (workspace, _) = workspaces[fetch.idx];
+            match listed {
+                Ok(refs) => {
+                    if queue_changed_refs(cache, workspace, fetch.cursor, refs, force_full) == 0 {
+                        result.issues_skipped += 1;
+                    }
+                }
+                Err(err) => {
+                    let msg = format!("sync failed for workspace {}: {}", workspace, err);
+                    logging::warn(&msg);
+                    result.errors.push(msg);
+                }
+            }
+        },
+    );
 
-                if cursor.is_none() {
-                    cache.upsert_workspace_issues(workspace, latest_refs);
+    // Phase 2: hydrate queued keys, newest first, within each workspace's
+    // share. The queue also holds what earlier runs could not fit, so
+    // budget a quiet workspace does not need goes to the others' backlog.
+    let shares: Vec<WorkspaceShare> = workspaces
+        .iter()
+        .map(|(workspace, config)| {
+            let queued = cache.workspace_backfill_remaining(workspace);
+            WorkspaceShare {
+                weight: config.weight.unwrap_or(1),
+                activity: if options.allocation == AllocationStrategy::Activity {
+                    cache.workspace_activity(workspace)
                 } else {
-                    let mut merged = cache
-                        .get_workspace_issues_snapshot(workspace)
-                        .map(|snapshot| snapshot.issues)
-                        .unwrap_or_default();
-
-                    for new_ref in latest_refs {
-                        if let Some(existing) =
-                            merged.iter_mut().find(|item| item.key == new_ref.key)
-                        {
-                            existing.updated = new_ref.updated.clone();
-                        } else {
-                            merged.push(new_ref);
-                        }
-                    }
+                    0
+                },
+                cap: Some(config.budget.map_or(queued, |cap| cap.min(queued))),
+            }
+        })
+        .collect();
+    let allocation = allocate_budget(budget, &shares, options.allocation, start);
 
-                    merged.sort_by(|a, b| a.key.cmp(&b.key));
-                    cache.upsert_workspace_issues(workspace, merged);
-                }
+    let mut batches = Vec::new();
+    for (idx, (workspace, _)) in workspaces.iter().enumerate() {
+        if allocation[idx] == 0 {
+            continue;
+        }
+        let keys: Vec<String> = cache
+            .next_backfill(workspace, allocation[idx])
+            .into_iter()
+            .map(|item| item.key)
+            .collect();
+        for chunk in keys.chunks(HYDRATE_BATCH_SIZE) {
+            batches.push((idx, chunk.to_vec()));
+        }
+    }
 
-                if issues.is_empty() {
-                    logging::info(format!("sync for workspace {}: no changes", workspace));
-                    result.issues_skipped += 1;
-                    continue;
+    let mut hydrated = vec![0; workspaces.len()];
+    parallel::for_each_concurrent(
+        batches,
+        options.concurrency,
+        |(idx, keys)| {
+            let fetched = fetch_issue_batch(jira, &keys);
+            (idx, keys, fetched)
+        },
+        |(idx, keys, fetched)| {
+            let (workspace, config) = workspaces[idx];
+            match fetched {
+                Ok(batch) => {
+                    let cached = cache_issues(
+                        cache,
+                        options,
+                        &batch.issues,
+                        config.inline_comments,
+                        &mut inline_modes,
+                    );
+                    if !batch.missing.is_empty() {
+                        logging::info(format!(
+                            "sync for workspace {}: {} queued issues no longer exist in jira",
+                            workspace,
+                            batch.missing.len()
+                        ));
+                        cache.tombstone_issues(&batch.missing);
+                    }
+                    // Keys a successful search left out were moved or deleted
+                    // and leave the queue too; only per-key failures stay.
+                    let done: Vec<String> = keys
+                        .into_iter()
+                        .filter(|key| !batch.failed.iter().any(|(failed, _)| failed == key))
+                        .collect();
+                    cache.remove_backfill(None, &done);
+                    for (key, err) in &batch.failed {
+                        let msg = format!(
+                            "hydration failed for issue {} in workspace {}: {}",
+                            key, workspace, err
+                        );
+                        logging::warn(&msg);
+                        result.errors.push(msg);
+                    }
+                    result.issues_cached += cached;
+                    hydrated[idx] += cached;
+                }
+                Err(err) => {
+                    let msg = format!("hydration failed for workspace {}: {}", workspace, err);
+                    logging::warn(&msg);
+                    result.errors.push(msg);
                 }
+            }
+        },
+    );
+    for (idx, (workspace, _)) in workspaces.iter().enumerate() {
+        if hydrated[idx] > 0 {
+            logging::info(format!(
+                "sync for workspace {}: cached {} of {} allocated issues, {} queued",
+                workspace,
+                hydrated[idx],
+                allocation[idx],
+                cache.workspace_backfill_remaining(workspace)
+            ));
+        }
+    }
+
+    result
+}
 
-                let remaining_budget = budget.saturating_sub(result.issues_cached);
-                let count = issues.len().min(remaining_budget);
-
-                let to_cache: Vec<_> = issues
-                    .iter()
-                    .take(count)
-                    .map(|issue| {
-                        let markdown = render_issue_markdown(issue).into_bytes();
-                        (issue.key.clone(), markdown, issue.updated.clone())
-                    })
-                    .collect();
-
-                let sidecars: Vec<_> = issues
-                    .iter()
-                    .take(count)
-                    .map(|issue| {
-                        (
-                            issue.key.clone(),
-                            render_issue_comments_markdown(issue).into_bytes(),
-                            issue.updated.clone(),
-                        )
-                    })
-                    .collect();
-
-                let cached = cache.upsert_issues_batch(&to_cache);
-                let _ = cache.upsert_issue_sidecars_batch(&sidecars);
+/// Re-fetches specific issues right away, outside the sync schedule and TTL.
+///
+/// Keys are fetched one by one so an unknown key cannot fail the others.
+/// Refreshed issues replace both the in-memory and persisted copies and
+/// leave the backfill queue. Each workspace listing is then re-checked for
+/// the refreshed keys, so newly created issues appear and issues that no
+/// longer match a workspace JQL leave it.
+pub fn refresh_issues(
+    jira: &JiraClient,
+    cache: &InMemoryCache,
+    workspaces: &[(String, WorkspaceConfig)],
+    options: &SyncOptions,
+    keys: &[String],
+) -> SyncResult {
+    let mut result = SyncResult {
+        issues_cached: 0,
+        issues_skipped: 0,
+        errors: Vec::new(),
+    };
+    let mut inline_modes = inline_comment_modes(cache, workspaces);
+    let mut refreshed: Vec<IssueRef> = Vec::new();
+
+    parallel::for_each_concurrent(
+        keys.to_vec(),
+        options.concurrency,
+        |key| {
+            let fetched = jira.get_issue(&key);
+            (key, fetched)
+        },
+        |(key, fetched)| match fetched {
+            Err(err) if err.http_status() == Some(reqwest::StatusCode::NOT_FOUND) => {
+                let msg = format!("refresh failed for issue {}: no longer exists in jira", key);
+                logging::info(&msg);
+                cache.tombstone_issues(std::slice::from_ref(&key));
+                cache.remove_backfill(None, std::slice::from_ref(&key));
+                result.errors.push(msg);
+            }
+            Ok(issue) => {
+                let cached = cache_issues(
+                    cache,
+                    options,
+                    std::slice::from_ref(&issue),
+                    InlineComments::None,
+                    &mut inline_modes,
+                );
+                cache.remove_backfill(None, std::slice::from_ref(&key));
                 result.issues_cached += cached;
+                refreshed.push(IssueRef {
+                    key: issue.key,
+                    updated: issue.updated,
+                });
+            }
+            Err(err) => {
+                let msg = format!("refresh failed for issue {}: {}", key, err);
+                logging::warn(&msg);
+                result.errors.push(msg);
+            }
+        },
+    );
 
-                if let Some(latest) = issues.first().and_then(|i| i.updated.as_ref()) {
-                    cache.set_sync_cursor(workspace, latest);
-                    logging::info(format!(
-                        "updated sync cursor for workspace {} to {}",
-                        workspace, latest
-                    ));
+    if refreshed.is_empty() {
+        return result;
+    }
+    let refreshed_keys: Vec<String> = refreshed.iter().map(|item| item.key.clone()).collect();
+    let probe_jql = key_in_jql(&refreshed_keys);
+    parallel::for_each_concurrent(
+        workspaces.iter().collect(),
+        options.concurrency,
+        |(workspace, config)| {
+            let (filter, _) = split_jql_order_by(&config.jql);
+            let matched = jira.probe_issue_refs(&format!("({}) AND {}", filter, probe_jql));
+            (workspace, matched)
+        },
+        |(workspace, matched)| match matched {
+            Ok(matched) => {
+                let mut listing = cache
+                    .get_workspace_issues_snapshot(workspace)
+                    .map(|snapshot| snapshot.issues)
+                    .unwrap_or_default();
+                let before = listing.len();
+                listing.retain(|item| !refreshed_keys.contains(&item.key));
+                if matched.is_empty() && listing.len() == before {
+                    return;
                 }
+                listing.extend(matched);
+                listing.sort_by(|a, b| a.key.cmp(&b.key));
+                cache.upsert_workspace_issues(workspace, listing);
+            }
+            Err(err) => {
+                let msg = format!(
+                    "refresh listing check failed for workspace {}: {}",
+                    workspace, err
+                );
+                logging::warn(&msg);
+                result.errors.push(msg);
+            }
+        },
+    );
 
-                logging::info(format!(
-                    "sync for workspace {}: cached {} issues",
-                    workspace, cached
-                ));
+    result
+}
+
+/// Fetches and caches one issue that was read before sync reached it.
+///
+/// # Errors
+///
+/// Returns the Jira error when the issue cannot be fetched.
+pub fn fetch_issue(
+    jira: &JiraClient,
+    cache: &InMemoryCache,
+    workspaces: &[(String, WorkspaceConfig)],
+    options: &SyncOptions,
+    key: &str,
+) -> Result<(), JiraError> {
+    let issue = jira.get_issue(key)?;
+    let mode = workspaces
+        .iter()
+        .filter(|(workspace, _)| {
+            cache
+                .get_workspace_issues_snapshot(workspace)
+                .is_some_and(|snapshot| snapshot.issues.iter().any(|item| item.key == key))
+        })
+        .map(|(_, config)| config.inline_comments)
+        .max()
+        .unwrap_or_default();
+    let mut inline_modes = HashMap::new();
+    cache_issues(
+        cache,
+        options,
+        std::slice::from_ref(&issue),
+        mode,
+        &mut inline_modes,
+    );
+    cache.remove_backfill(None, &[key.to_string()]);
+    Ok(())
+}
+
+/// Renders and stores issues, widening each key's inline comment mode with
+/// `workspace_mode`. Returns the number of issues cached.
+fn cache_issues(
+    cache: &InMemoryCache,
+    options: &SyncOptions,
+    issues: &[IssueData],
+    workspace_mode: InlineComments,
+    inline_modes: &mut HashMap<String, InlineComments>,
+) -> usize {
+    let (to_cache, sidecars): (Vec<_>, Vec<_>) = issues
+        .iter()
+        .filter(|issue| !is_older_than_cached(cache, issue))
+        .map(|issue| {
+            let mode = inline_modes.entry(issue.key.clone()).or_default();
+            *mode = (*mode).max(workspace_mode);
+            render_issue_rows(issue, options, *mode)
+        })
+        .unzip();
+    let cached = cache.upsert_issues_batch(&to_cache);
+    let _ = cache.upsert_issue_sidecars_batch(&sidecars);
+    cached
+}
+
+/// One planned workspace listing for a sync run.
+struct WorkspaceFetch {
+    idx: usize,
+    jql: String,
+    cursor: Option<DateTime<Utc>>,
+}
+
+/// Merges listed refs into the workspace listing, advances the cursor, and
+/// queues refs whose `updated` differs from the cached issue for hydration.
+///
+/// Returns the number of refs queued.
+fn queue_changed_refs(
+    cache: &InMemoryCache,
+    workspace: &str,
+    cursor: Option<DateTime<Utc>>,
+    refs: Vec<IssueRef>,
+    force_full: bool,
+) -> usize {
+    let advanced =
+        latest_updated(&refs).filter(|latest| cursor.is_none_or(|since| *latest > since));
+    // A full refresh re-renders everything, so template, inline comment, and
+    // redaction changes reach issues Jira has not touched.
+    let changed: Vec<IssueRef> = refs
+        .iter()
+        .filter(|item| force_full || !is_unchanged(cache, item))
+        .cloned()
+        .collect();
+
+    if cursor.is_none() {
+        cache.upsert_workspace_issues(workspace, refs);
+    } else {
+        let mut merged = cache
+            .get_workspace_issues_snapshot(workspace)
+            .map(|snapshot| snapshot.issues)
+            .unwrap_or_default();
+
+        for new_ref in refs {
+            if let Some(existing) = merged.iter_mut().find(|item| item.key == new_ref.key) {
+                existing.updated = new_ref.updated;
+            } else {
+                merged.push(new_ref);
+            }
+        }
+
+        merged.sort_by(|a, b| a.key.cmp(&b.key));
+        cache.upsert_workspace_issues(workspace, merged);
+    }
+
+    // Queued refs survive a failed or budget-truncated hydration, so the
+    // cursor can move past them safely.
+    let queued = cache.enqueue_backfill(workspace, &changed);
+    if let Some(latest) = advanced {
+        let latest = latest.to_rfc3339_opts(SecondsFormat::Secs, true);
+        cache.set_sync_cursor(workspace, &latest);
+        logging::info(format!(
+            "updated sync cursor for workspace {} to {}",
+            workspace, latest
+        ));
+    }
+
+    if queued == 0 {
+        logging::info(format!("sync for workspace {}: no changes", workspace));
+    } else {
+        logging::info(format!(
+            "sync for workspace {}: {} changed issues queued for hydration",
+            workspace, queued
+        ));
+    }
+    queued
+}
 
-                if result.issues_cached >= budget {
-                    break;
+/// Issues fetched for one hydration batch.
+#[derive(Debug, Default)]
+struct IssueBatch {
+    issues: Vec<IssueData>,
+    /// Keys Jira answered 404 for: deleted, or no longer visible.
+    missing: Vec<String>,
+    /// Keys whose per-key fetch failed otherwise, with the error; they stay
+    /// queued.
+    failed: Vec<(String, String)>,
+}
+
+/// Fetches `keys` with one `key in (...)` search.
+///
+/// Jira rejects the whole query with HTTP 400 when any listed key no longer
+/// exists, so a rejected batch falls back to fetching each key on its own.
+fn fetch_issue_batch(jira: &JiraClient, keys: &[String]) -> Result<IssueBatch, JiraError> {
+    match jira.search_issues_bulk(&key_in_jql(keys), keys.len()) {
+        Ok(issues) => Ok(IssueBatch {
+            issues,
+            ..IssueBatch::default()
+        }),
+        Err(err) if err.http_status() == Some(reqwest::StatusCode::BAD_REQUEST) => {
+            logging::debug(format!(
+                "jira rejected a {}-key hydration batch; fetching keys one by one",
+                keys.len()
+            ));
+            let mut batch = IssueBatch::default();
+            for key in keys {
+                match jira.get_issue(key) {
+                    Ok(issue) => batch.issues.push(issue),
+                    Err(err) if err.http_status() == Some(reqwest::StatusCode::NOT_FOUND) => {
+                        batch.missing.push(key.clone());
+                    }
+                    Err(err) => batch.failed.push((key.clone(), err.to_string())),
                 }
             }
+            Ok(batch)
+        }
+        Err(err) => Err(err),
+    }
+}
+
+fn key_in_jql(keys: &[String]) -> String {
+    let quoted: Vec<String> = keys.iter().map(|key| format!("\"{}\"", key)).collect();
+    format!("key in ({})", quoted.join(", "))
+}
+
+pub struct ReconcileResult {
+    pub workspaces_reconciled: usize,
+    pub refs_removed: usize,
+    pub tombstoned: Vec<String>,
+    pub errors: Vec<String>,
+}
+
+/// Drops listing refs for issues that no longer match a workspace's JQL.
+///
+/// Each workspace is re-listed (keys and `updated` only) once per
+/// `options.reconcile_interval`, or immediately when `force` is set. When
+/// every due workspace reconciles cleanly, cached issues that no workspace
+/// lists anymore are tombstoned.
+pub fn reconcile_workspaces(
+    jira: &JiraClient,
+    cache: &InMemoryCache,
+    workspaces: &[(String, WorkspaceConfig)],
+    options: &SyncOptions,
+    force: bool,
+) -> ReconcileResult {
+    let mut result = ReconcileResult {
+        workspaces_reconciled: 0,
+        refs_removed: 0,
+        tombstoned: Vec::new(),
+        errors: Vec::new(),
+    };
+    if !cache.has_persistence() {
+        return result;
+    }
+
+    let now = unix_now_secs();
+    for (workspace, config) in workspaces {
+        let due = force
+            || cache.last_reconciled(workspace).is_none_or(|last| {
+                now.saturating_sub(last) >= options.reconcile_interval.as_secs()
+            });
+        if !due {
+            continue;
+        }
+
+        let fresh = match jira.list_issue_refs_for_jql(&config.jql) {
+            Ok(refs) => refs,
             Err(err) => {
-                let msg = format!("sync failed for workspace {}: {}", workspace, err);
+                let msg = format!("reconcile failed for workspace {}: {}", workspace, err);
                 logging::warn(&msg);
                 result.errors.push(msg);
+                continue;
             }
+        };
+        let live: HashSet<&str> = fresh.iter().map(|item| item.key.as_str()).collect();
+
+        let existing = cache
+            .get_workspace_issues_snapshot(workspace)
+            .map(|snapshot| snapshot.issues)
+            .or_else(|| cache.list_workspace_issue_refs_from_persistence(workspace))
+            .unwrap_or_default();
+        let before = existing.len();
+        let existing_keys: Vec<String> = existing.iter().map(|item| item.key.clone()).collect();
+        let retained: Vec<_> = existing
+            .into_iter()
+            .filter(|item| live.contains(item.key.as_str()))
+            .collect();
+        let removed = before - retained.len();
+        if removed > 0 {
+            let stale: Vec<String> = existing_keys
+                .into_iter()
+                .filter(|key| !live.contains(key.as_str()))
+                .collect();
+            cache.remove_backfill(Some(workspace), &stale);
+            cache.upsert_workspace_issues(workspace, retained);
+            logging::info(format!(
+                "reconciled workspace {}: removed {} stale issues",
+                workspace, removed
+            ));
+        }
+
+        cache.mark_reconciled(workspace);
+        result.workspaces_reconciled += 1;
+        result.refs_removed += removed;
+    }
+
+    if result.workspaces_reconciled > 0 && result.errors.is_empty() {
+        let names: Vec<String> = workspaces.iter().map(|(name, _)| name.clone()).collect();
+        cache.retain_workspaces(&names);
+        result.tombstoned = cache.tombstone_orphaned_issues();
+        if !result.tombstoned.is_empty() {
+            logging::info(format!(
+                "tombstoned {} issues no longer in any workspace",
+                result.tombstoned.len()
+            ));
         }
     }
 
     result
 }
 
+fn unix_now_secs() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|duration| duration.as_secs())
+        .unwrap_or(0)
+}
+
+/// Widest inline comment mode per issue key across every workspace listing it.
+fn inline_comment_modes(
+    cache: &InMemoryCache,
+    workspaces: &[(String, WorkspaceConfig)],
+) -> HashMap<String, InlineComments> {
+    let mut modes: HashMap<String, InlineComments> = HashMap::new();
+    for (workspace, config) in workspaces {
+        let Some(snapshot) = cache.get_workspace_issues_snapshot(workspace) else {
+            continue;
+        };
+        for issue in snapshot.issues {
+            let mode = modes.entry(issue.key).or_default();
+            *mode = (*mode).max(config.inline_comments);
+        }
+    }
+    modes
+}
+
+/// Renders the `KEY.md` and `KEY.comments.md` rows persisted for one issue.
+fn render_issue_rows(
+    issue: &IssueData,
+    options: &SyncOptions,
+    inline_comments: InlineComments,
+) -> (IssueCacheRow, IssueSidecarRow) {
+    let render_options = RenderOptions {
+        template: options.template.as_deref(),
+        inline_comments,
+    };
+    let markdown = render_issue_markdown_with(issue, &render_options).into_bytes();
+    let comments = render_issue_comments_markdown(issue).into_bytes();
+    (
+        IssueCacheRow {
+            key: issue.key.clone(),
+            markdown,
+            updated: issue.updated.clone(),
+            created: issue.created.clone(),
+            attributes: Some(IssueAttributes {
+                status: issue.status.clone(),
+                assignee: issue.assignee.clone(),
+                assignee_id: issue.assignee_account_id.clone(),
+                url: issue.source_url.clone(),
+                labels: issue.labels.clone(),
+            }),
+        },
+        (issue.key.clone(), comments, comments_updated(issue)),
+    )
+}
+
+/// Latest comment edit or creation time, or the issue's creation time when
+/// it has no comments.
+fn comments_updated(issue: &IssueData) -> Option<String> {
+    issue
+        .comments
+        .iter()
+        .filter_map(|comment| comment.updated.as_ref().or(comment.created.as_ref()))
+        .filter_map(|raw| parse_jira_timestamp(raw).map(|at| (at, raw)))
+        .max_by_key(|(at, _)| *at)
+        .map(|(_, raw)| raw.clone())
+        .or_else(|| issue.created.clone())
+}
+
+/// Formats the JQL lower bound for an incremental sync.
+///
+/// JQL date literals are minute precision and interpreted in the account's
+/// profile timezone, so the cursor is shifted back by `overlap`, converted to
+/// `tz`, and truncated to the minute. The resulting `>=` window always
+/// includes the issue that set the cursor.
+fn cursor_jql_bound(since: DateTime<Utc>, overlap: Duration, tz: Tz) -> String {
+    let overlap = chrono::Duration::from_std(overlap).unwrap_or_else(|_| chrono::Duration::zero());
+    (since - overlap)
+        .with_timezone(&tz)
+        .format("%Y-%m-%d %H:%M")
+        .to_string()
+}
+
+fn latest_updated(refs: &[IssueRef]) -> Option<DateTime<Utc>> {
+    refs.iter()
+        .filter_map(|item| item.updated.as_deref().and_then(parse_jira_timestamp))
+        .max()
+}
+
+/// Whether the cache already holds a newer copy of `issue`.
+///
+/// Sync hydration and targeted refreshes run on separate threads, so a batch
+/// fetched before a refresh can land after it; such stale copies are dropped.
+fn is_older_than_cached(cache: &InMemoryCache, issue: &IssueData) -> bool {
+    let Some(updated) = issue.updated.as_deref().and_then(parse_jira_timestamp) else {
+        return false;
+    };
+    let stale = cache
+        .persisted_issue_updated(&issue.key)
+        .and_then(|cached| parse_jira_timestamp(&cached))
+        .is_some_and(|cached| cached > updated);
+    if stale {
+        logging::debug(format!(
+            "skipping stale copy of issue {} updated {}",
+            issue.key, updated
+        ));
+    }
+    stale
+}
+
+/// Reports whether the cached copy of an issue already has the listed `updated`.
+fn is_unchanged(cache: &InMemoryCache, item: &IssueRef) -> bool {
+    let Some(updated) = item.updated.as_deref().and_then(parse_jira_timestamp) else {
+        return false;
+    };
+    cache
+        .persisted_issue_updated(&item.key)
+        .and_then(|cached| parse_jira_timestamp(&cached))
+        == Some(updated)
+}
+
 fn split_jql_order_by(jql: &str) -> (String, Option<String>) {
     let order_re = Regex::new(r"(?i)\border\s+by\b").expect("valid order by regex");
     let trimmed = jql.trim();
@@ -204,7 +939,811 @@ fn split_jql_order_by(jql: &str) -> (String, Option<String>) {
 
 #[cfg(test)]
 mod tests {
-    use super::split_jql_order_by;
+    use std::path::Path;
+
+    use httpmock::Method::GET;
+    use httpmock::MockServer;
+
+    use super::*;
+    use crate::metrics::Metrics;
+
+    fn mock_hydration<'a>(server: &'a MockServer, issues: &[(&str, &str)]) -> httpmock::Mock<'a> {
+        let keys: Vec<String> = issues.iter().map(|(key, _)| key.to_string()).collect();
+        let payload: Vec<_> = issues
+            .iter()
+            .map(|(key, updated)| serde_json::json!({"key": key, "fields": {"updated": updated}}))
+            .collect();
+        server.mock(|when, then| {
+            when.method(GET)
+                .path("/rest/api/3/search/jql")
+                .query_param("jql", key_in_jql(&keys));
+            then.status(200)
+                .json_body_obj(&serde_json::json!({"isLast": true, "issues": payload}));
+        })
+    }
+
+    #[test]
+    fn budget_truncated_sync_resumes_from_backfill() {
+        let server = MockServer::start();
+        let _full = server.mock(|when, then| {
+            when.method(GET)
+                .path("/rest/api/3/search/jql")
+                .query_param("jql", "project = ST");
+            then.status(200).json_body_obj(&serde_json::json!({
+                "isLast": true,
+                "issues": [
+                    {"key": "ST-2", "fields": {"updated": "2026-02-22T00:00:00.000+0000"}},
+                    {"key": "ST-1", "fields": {"updated": "2026-02-21T00:00:00.000+0000"}}
+                ]
+            }));
+        });
+        let _myself = server.mock(|when, then| {
+            when.method(GET).path("/rest/api/3/myself");
+            then.status(200)
+                .json_body_obj(&serde_json::json!({"accountId": "a", "timeZone": "UTC"}));
+        });
+        let _incremental = server.mock(|when, then| {
+            when.method(GET).path("/rest/api/3/search/jql").query_param(
+                "jql",
+                "(project = ST) AND updated >= \"2026-02-22 00:00\" ORDER BY updated DESC",
+            );
+            then.status(200).json_body_obj(&serde_json::json!({
+                "isLast": true,
+                "issues": [
+                    {"key": "ST-2", "fields": {"updated": "2026-02-22T00:00:00.000+0000"}}
+                ]
+            }));
+        });
+        let _hydrate = mock_hydration(&server, &[("ST-2", "2026-02-22T00:00:00.000+0000")]);
+        let backfill = mock_hydration(&server, &[("ST-1", "2026-02-21T00:00:00.000+0000")]);
+
+        let jira = JiraClient::new(server.base_url(), "e".into(), "t".into()).expect("client");
+        let cache = Arc::new(
+            InMemoryCache::with_persistence(
+                Duration::from_secs(30),
+                Duration::from_secs(30),
+                Path::new(":memory:"),
+                Arc::new(Metrics::new()),
+            )
+            .expect("cache"),
+        );
+        let workspaces = vec![(
+            "default".to_string(),
+            WorkspaceConfig {
+                jql: "project = ST".to_string(),
+                ..WorkspaceConfig::default()
+            },
+        )];
+        let options = SyncOptions {
+            budget: 1,
+            ..SyncOptions::default()
+        };
+
+        let sync_state = SyncState::new(Duration::from_secs(60));
+        let first = sync_issues(
+            &jira,
+            &cache,
+            &sync_state,
+            &workspaces,
+            None,
+            &options,
+            false,
+        );
+        assert_eq!(first.issues_cached, 1);
+        assert_eq!(cache.backfill_remaining(), 1);
+        assert!(cache.persistent_issue_len("ST-1").is_none());
+
+        let second = sync_issues(
+            &jira,
+            &cache,
+            &sync_state,
+            &workspaces,
+            None,
+            &options,
+            false,
+        );
+        assert!(second.errors.is_empty());
+        assert_eq!(second.issues_cached, 1);
+        backfill.assert();
+        assert_eq!(cache.backfill_remaining(), 0);
+        assert!(cache.persistent_issue_len("ST-1").is_some());
+    }
+
+    #[test]
+    fn hydration_batch_with_deleted_key_falls_back_to_single_fetches() {
+        let server = MockServer::start();
+        let _myself = server.mock(|when, then| {
+            when.method(GET).path("/rest/api/3/myself");
+            then.status(200)
+                .json_body_obj(&serde_json::json!({"accountId": "a", "timeZone": "UTC"}));
+        });
+        let _full = server.mock(|when, then| {
+            when.method(GET)
+                .path("/rest/api/3/search/jql")
+                .query_param("jql", "project = ST");
+            then.status(200).json_body_obj(&serde_json::json!({
+                "isLast": true,
+                "issues": [
+                    {"key": "ST-2", "fields": {"updated": "2026-02-22T00:00:00.000+0000"}},
+                    {"key": "ST-1", "fields": {"updated": "2026-02-21T00:00:00.000+0000"}}
+                ]
+            }));
+        });
+        let batch = server.mock(|when, then| {
+            when.method(GET)
+                .path("/rest/api/3/search/jql")
+                .query_param("jql", key_in_jql(&["ST-2".to_string(), "ST-1".to_string()]));
+            then.status(400).json_body_obj(&serde_json::json!({
+                "errorMessages": ["An issue with key 'ST-1' does not exist for field 'key'."]
+            }));
+        });
+        let live = server.mock(|when, then| {
+            when.method(GET).path("/rest/api/3/issue/ST-2");
+            then.status(200).json_body_obj(&serde_json::json!({
+                "key": "ST-2",
+                "fields": {"summary": "Still here", "updated": "2026-02-22T00:00:00.000+0000"}
+            }));
+        });
+        let deleted = server.mock(|when, then| {
+            when.method(GET).path("/rest/api/3/issue/ST-1");
+            then.status(404).json_body_obj(&serde_json::json!({
+                "errorMessages": ["Issue does not exist or you do not have permission to see it."]
+            }));
+        });
+
+        let jira = JiraClient::new(server.base_url(), "e".into(), "t".into()).expect("client");
+        let cache = Arc::new(
+            InMemoryCache::with_persistence(
+                Duration::from_secs(30),
+                Duration::from_secs(30),
+                Path::new(":memory:"),
+                Arc::new(Metrics::new()),
+            )
+            .expect("cache"),
+        );
+        let workspaces = vec![(
+            "default".to_string(),
+            WorkspaceConfig {
+                jql: "project = ST".to_string(),
+                ..WorkspaceConfig::default()
+            },
+        )];
+
+        let options = SyncOptions {
+            budget: 10,
+            ..SyncOptions::default()
+        };
+
+        let sync_state = SyncState::new(Duration::from_secs(60));
+        let result = sync_issues(
+            &jira,
+            &cache,
+            &sync_state,
+            &workspaces,
+            None,
+            &options,
+            false,
+        );
+        assert!(result.errors.is_empty(), "{:?}", result.errors);
+        assert_eq!(result.issues_cached, 1);
+        batch.assert();
+        live.assert();
+        deleted.assert();
+        assert_eq!(cache.backfill_remaining(), 0);
+        assert!(cache.persistent_issue_len("ST-2").is_some());
+        let listing: Vec<String> = cache
+            .get_workspace_issues_snapshot("default")
+            .expect("listing")
+            .issues
+            .into_iter()
+            .map(|item| item.key)
+            .collect();
+        assert_eq!(listing, vec!["ST-2"]);
+    }
+
+    #[test]
+    fn incremental_sync_overlaps_cursor_in_account_timezone() {
+        let server = MockServer::start();
+        let myself = server.mock(|when, then| {
+            when.method(GET).path("/rest/api/3/myself");
+            then.status(200).json_body_obj(
+                &serde_json::json!({"accountId": "a", "timeZone": "America/New_York"}),
+            );
+        });
+        let _full = server.mock(|when, then| {
+            when.method(GET)
+                .path("/rest/api/3/search/jql")
+                .query_param("jql", "project = ST");
+            then.status(200).json_body_obj(&serde_json::json!({
+                "isLast": true,
+                "issues": [
+                    {"key": "ST-1", "fields": {"updated": "2026-02-21T01:00:30.000+0000"}}
+                ]
+            }));
+        });
+        let _hydrate_first = mock_hydration(&server, &[("ST-1", "2026-02-21T01:00:30.000+0000")]);
+        let hydrate_second = mock_hydration(&server, &[("ST-2", "2026-02-21T01:00:45.000+0000")]);
+        let incremental = server.mock(|when, then| {
+            when.method(GET).path("/rest/api/3/search/jql").query_param(
+                "jql",
+                "(project = ST) AND updated >= \"2026-02-20 19:58\" ORDER BY updated DESC",
+            );
+            then.status(200).json_body_obj(&serde_json::json!({
+                "isLast": true,
+                "issues": [
+                    {"key": "ST-2", "fields": {"updated": "2026-02-21T01:00:45.000+0000"}},
+                    {"key": "ST-1", "fields": {"updated": "2026-02-21T01:00:30.000+0000"}}
+                ]
+            }));
+        });
+
+        let jira = JiraClient::new(server.base_url(), "e".into(), "t".into()).expect("client");
+        let cache = Arc::new(
+            InMemoryCache::with_persistence(
+                Duration::from_secs(30),
+                Duration::from_secs(30),
+                Path::new(":memory:"),
+                Arc::new(Metrics::new()),
+            )
+            .expect("cache"),
+        );
+        let workspaces = vec![(
+            "default".to_string(),
+            WorkspaceConfig {
+                jql: "project = ST".to_string(),
+                ..WorkspaceConfig::default()
+            },
+        )];
+        let options = SyncOptions {
+            budget: 10,
+            cursor_overlap: Duration::from_secs(120),
+            ..SyncOptions::default()
+        };
+
+        let sync_state = SyncState::new(Duration::from_secs(60));
+        let first = sync_issues(
+            &jira,
+            &cache,
+            &sync_state,
+            &workspaces,
+            None,
+            &options,
+            false,
+        );
+        assert_eq!(first.issues_cached, 1);
+        assert_eq!(
+            cache.get_sync_cursor("default").as_deref(),
+            Some("2026-02-21T01:00:30Z")
+        );
+
+        let second = sync_issues(
+            &jira,
+            &cache,
+            &sync_state,
+            &workspaces,
+            None,
+            &options,
+            false,
+        );
+        assert!(second.errors.is_empty());
+        incremental.assert();
+        hydrate_second.assert();
+        myself.assert();
+        assert_eq!(second.issues_cached, 1);
+        assert!(cache.persistent_issue_len("ST-2").is_some());
+        assert_eq!(
+            cache.get_sync_cursor("default").as_deref(),
+            Some("2026-02-21T01:00:45Z")
+        );
+    }
+
+    #[test]
+    fn full_refresh_rerenders_unchanged_issues() {
+        let server = MockServer::start();
+        let _listing = server.mock(|when, then| {
+            when.method(GET)
+                .path("/rest/api/3/search/jql")
+                .query_param("jql", "project = ST")
+                .query_param("fields", "updated");
+            then.status(200).json_body_obj(&serde_json::json!({
+                "isLast": true,
+                "issues": [
+                    {"key": "ST-2", "fields": {"updated": "2026-02-22T00:00:00.000+0000"}},
+                    {"key": "ST-1", "fields": {"updated": "2026-02-21T00:00:00.000+0000"}}
+                ]
+            }));
+        });
+        let both = mock_hydration(
+            &server,
+            &[
+                ("ST-2", "2026-02-22T00:00:00.000+0000"),
+                ("ST-1", "2026-02-21T00:00:00.000+0000"),
+            ],
+        );
+
+        let jira = JiraClient::new(server.base_url(), "e".into(), "t".into()).expect("client");
+        let cache = Arc::new(
+            InMemoryCache::with_persistence(
+                Duration::from_secs(30),
+                Duration::from_secs(30),
+                Path::new(":memory:"),
+                Arc::new(Metrics::new()),
+            )
+            .expect("cache"),
+        );
+        let workspaces = vec![(
+            "default".to_string(),
+            WorkspaceConfig {
+                jql: "project = ST".to_string(),
+                ..WorkspaceConfig::default()
+            },
+        )];
+        let options = SyncOptions {
+            budget: 10,
+            ..SyncOptions::default()
+        };
+        let sync_state = SyncState::new(Duration::from_secs(60));
+
+        let first = sync_issues(
+            &jira,
+            &cache,
+            &sync_state,
+            &workspaces,
+            None,
+            &options,
+            true,
+        );
+        assert_eq!(first.issues_cached, 2);
+
+        // Nothing changed in Jira, but the template did.
+        let options = SyncOptions {
+            template: Some(Arc::new(
+                IssueTemplate::parse("templated {{ key }}\n").expect("template"),
+            )),
+            ..options
+        };
+        let second = sync_issues(
+            &jira,
+            &cache,
+            &sync_state,
+            &workspaces,
+            None,
+            &options,
+            true,
+        );
+        assert!(second.errors.is_empty());
+        assert_eq!(second.issues_cached, 2);
+        both.assert_hits(2);
+        assert_eq!(cache.backfill_remaining(), 0);
+        for key in ["ST-1", "ST-2"] {
+            let markdown = cache
+                .get_issue_markdown_stale_safe(key, || Err(()))
+                .expect("cached");
+            assert!(String::from_utf8_lossy(&markdown).starts_with("templated "));
+        }
+    }
+
+    #[test]
+    fn allocates_budget_by_weight_cap_and_strategy() {
+        let share = |weight, activity, cap| WorkspaceShare {
+            weight,
+            activity,
+            cap,
+        };
+
+        let shares = [share(1, 0, None), share(3, 0, None)];
+        assert_eq!(
+            allocate_budget(100, &shares, AllocationStrategy::Weighted, 0),
+            vec![25, 75]
+        );
+
+        // A capped workspace hands its unused share to the others.
+        let shares = [share(1, 0, Some(10)), share(1, 0, None), share(1, 0, None)];
+        assert_eq!(
+            allocate_budget(100, &shares, AllocationStrategy::Weighted, 0),
+            vec![10, 45, 45]
+        );
+
+        // Rounding leftovers follow the rotated start, not alphabetical order.
+        let shares = [share(1, 0, None), share(1, 0, None)];
+        assert_eq!(
+            allocate_budget(1, &shares, AllocationStrategy::Weighted, 0),
+            vec![1, 0]
+        );
+        assert_eq!(
+            allocate_budget(1, &shares, AllocationStrategy::Weighted, 1),
+            vec![0, 1]
+        );
+
+        let shares = [share(5, 0, None), share(1, 0, None), share(1, 0, Some(50))];
+        assert_eq!(
+            allocate_budget(350, &shares, AllocationStrategy::RoundRobin, 1),
+            vec![100, 200, 50]
+        );
+
+        let shares = [share(1, 0, None), share(1, 3, None)];
+        assert_eq!(
+            allocate_budget(100, &shares, AllocationStrategy::Activity, 0),
+            vec![20, 80]
+        );
+
+        // A quiet workspace keeps one slot next to a very busy one.
+        let shares = [share(1, 0, None), share(1, 1000, None)];
+        assert_eq!(
+            allocate_budget(10, &shares, AllocationStrategy::Activity, 0),
+            vec![1, 9]
+        );
+    }
+
+    #[test]
+    fn busy_workspace_does_not_starve_later_ones() {
+        let server = MockServer::start();
+        for project in ["AA", "ZZ"] {
+            server.mock(|when, then| {
+                when.method(GET)
+                    .path("/rest/api/3/search/jql")
+                    .query_param("jql", format!("project = {project}"));
+                then.status(200).json_body_obj(&serde_json::json!({
+                    "isLast": true,
+                    "issues": [
+                        {"key": format!("{project}-2"), "fields": {"updated": "2026-02-22T00:00:00.000+0000"}},
+                        {"key": format!("{project}-1"), "fields": {"updated": "2026-02-21T00:00:00.000+0000"}}
+                    ]
+                }));
+            });
+            mock_hydration(
+                &server,
+                &[(&format!("{project}-2"), "2026-02-22T00:00:00.000+0000")],
+            );
+        }
+
+        let jira = JiraClient::new(server.base_url(), "e".into(), "t".into()).expect("client");
+        let cache = Arc::new(
+            InMemoryCache::with_persistence(
+                Duration::from_secs(30),
+                Duration::from_secs(30),
+                Path::new(":memory:"),
+                Arc::new(Metrics::new()),
+            )
+            .expect("cache"),
+        );
+        let workspace = |name: &str, project: &str| {
+            (
+                name.to_string(),
+                WorkspaceConfig {
+                    jql: format!("project = {project}"),
+                    ..WorkspaceConfig::default()
+                },
+            )
+        };
+        let workspaces = vec![workspace("aa", "AA"), workspace("zz-oncall", "ZZ")];
+        let options = SyncOptions {
+            budget: 2,
+            ..SyncOptions::default()
+        };
+
+        let sync_state = SyncState::new(Duration::from_secs(60));
+        let result = sync_issues(
+            &jira,
+            &cache,
+            &sync_state,
+            &workspaces,
+            None,
+            &options,
+            false,
+        );
+        assert_eq!(result.issues_cached, 2);
+        assert!(cache.persistent_issue_len("AA-2").is_some());
+        assert!(cache.persistent_issue_len("ZZ-2").is_some());
+        assert_eq!(cache.workspace_backfill_remaining("aa"), 1);
+        assert_eq!(cache.workspace_backfill_remaining("zz-oncall"), 1);
+    }
+
+    #[test]
+    fn syncs_workspaces_concurrently() {
+        let server = MockServer::start();
+        let projects = ["AA", "BB", "CC", "DD"];
+        for project in projects {
+            server.mock(|when, then| {
+                when.method(GET)
+                    .path("/rest/api/3/search/jql")
+                    .query_param("jql", format!("project = {project}"));
+                then.status(200)
+                    .delay(Duration::from_millis(300))
+                    .json_body_obj(&serde_json::json!({
+                        "isLast": true,
+                        "issues": [
+                            {"key": format!("{project}-1"), "fields": {"updated": "2026-02-21T00:00:00.000+0000"}}
+                        ]
+                    }));
+            });
+            mock_hydration(
+                &server,
+                &[(&format!("{project}-1"), "2026-02-21T00:00:00.000+0000")],
+            );
+        }
+
+        let jira = JiraClient::new(server.base_url(), "e".into(), "t".into())
+            .expect("client")
+            .with_max_in_flight(4);
+        let cache = Arc::new(
+            InMemoryCache::with_persistence(
+                Duration::from_secs(30),
+                Duration::from_secs(30),
+                Path::new(":memory:"),
+                Arc::new(Metrics::new()),
+            )
+            .expect("cache"),
+        );
+        let workspaces: Vec<_> = projects
+            .iter()
+            .map(|project| {
+                (
+                    project.to_lowercase(),
+                    WorkspaceConfig {
+                        jql: format!("project = {project}"),
+                        ..WorkspaceConfig::default()
+                    },
+                )
+            })
+            .collect();
+        let options = SyncOptions {
+            budget: 10,
+            concurrency: 4,
+            ..SyncOptions::default()
+        };
+
+        let sync_state = SyncState::new(Duration::from_secs(60));
+        let started = std::time::Instant::now();
+        let result = sync_issues(
+            &jira,
+            &cache,
+            &sync_state,
+            &workspaces,
+            None,
+            &options,
+            false,
+        );
+        let elapsed = started.elapsed();
+
+        assert!(result.errors.is_empty());
+        assert_eq!(result.issues_cached, 4);
+        assert!(
+            elapsed < Duration::from_millis(900),
+            "four 300ms fetches took {elapsed:?}"
+        );
+        for project in projects {
+            assert!(cache
+                .persistent_issue_len(&format!("{project}-1"))
+                .is_some());
+            assert!(cache.get_sync_cursor(&project.to_lowercase()).is_some());
+        }
+    }
+
+    #[test]
+    fn refresh_replaces_cached_issue_and_reports_unknown_keys() {
+        let server = MockServer::start();
+        let _issue = server.mock(|when, then| {
+            when.method(GET).path("/rest/api/3/issue/ST-1");
+            then.status(200).json_body_obj(&serde_json::json!({
+                "key": "ST-1",
+                "fields": {"summary": "Fresh title", "updated": "2026-02-23T00:00:00.000+0000"}
+            }));
+        });
+        let _missing = server.mock(|when, then| {
+            when.method(GET).path("/rest/api/3/issue/ST-404");
+            then.status(404).body("{}");
+        });
+
+        let jira = JiraClient::new(server.base_url(), "e".into(), "t".into()).expect("client");
+        let cache = InMemoryCache::with_persistence(
+            Duration::from_secs(30),
+            Duration::from_secs(30),
+            Path::new(":memory:"),
+            Arc::new(Metrics::new()),
+        )
+        .expect("cache");
+        cache.upsert_issues_batch(&[IssueCacheRow {
+            key: "ST-1".to_string(),
+            markdown: b"stale".to_vec(),
+            updated: Some("2026-02-21T00:00:00.000+0000".to_string()),
+            ..IssueCacheRow::default()
+        }]);
+        cache.enqueue_backfill(
+            "default",
+            &[IssueRef {
+                key: "ST-1".to_string(),
+                updated: None,
+            }],
+        );
+
+        let result = refresh_issues(
+            &jira,
+            &cache,
+            &[],
+            &SyncOptions::default(),
+            &["ST-1".to_string(), "ST-404".to_string()],
+        );
+
+        assert_eq!(result.issues_cached, 1);
+        assert_eq!(result.errors.len(), 1);
+        assert!(result.errors[0].contains("ST-404"));
+        let markdown = cache
+            .get_issue_markdown_stale_safe("ST-1", || Err(()))
+            .expect("cached");
+        assert!(String::from_utf8_lossy(&markdown).contains("Fresh title"));
+        assert_eq!(cache.backfill_remaining(), 0);
+    }
+
+    #[test]
+    fn stale_fetch_does_not_overwrite_newer_cached_issue() {
+        let server = MockServer::start();
+        let _issue = server.mock(|when, then| {
+            when.method(GET).path("/rest/api/3/issue/ST-1");
+            then.status(200).json_body_obj(&serde_json::json!({
+                "key": "ST-1",
+                "fields": {"summary": "Old title", "updated": "2026-02-21T00:00:00.000+0000"}
+            }));
+        });
+
+        let jira = JiraClient::new(server.base_url(), "e".into(), "t".into()).expect("client");
+        let cache = InMemoryCache::with_persistence(
+            Duration::from_secs(30),
+            Duration::from_secs(30),
+            Path::new(":memory:"),
+            Arc::new(Metrics::new()),
+        )
+        .expect("cache");
+        cache.upsert_issues_batch(&[IssueCacheRow {
+            key: "ST-1".to_string(),
+            markdown: b"newer".to_vec(),
+            updated: Some("2026-02-23T00:00:00.000+0000".to_string()),
+            ..IssueCacheRow::default()
+        }]);
+
+        let result = refresh_issues(
+            &jira,
+            &cache,
+            &[],
+            &SyncOptions::default(),
+            &["ST-1".to_string()],
+        );
+
+        assert!(result.errors.is_empty());
+        assert_eq!(result.issues_cached, 0);
+        let markdown = cache
+            .get_issue_markdown_stale_safe("ST-1", || Err(()))
+            .expect("cached");
+        assert_eq!(markdown, b"newer");
+        assert_eq!(
+            cache.persisted_issue_updated("ST-1").as_deref(),
+            Some("2026-02-23T00:00:00.000+0000")
+        );
+    }
+
+    #[test]
+    fn refresh_moves_issues_into_and_out_of_workspace_listings() {
+        let server = MockServer::start();
+        for key in ["ST-1", "ST-2"] {
+            server.mock(|when, then| {
+                when.method(GET).path(format!("/rest/api/3/issue/{key}"));
+                then.status(200).json_body_obj(&serde_json::json!({
+                    "key": key,
+                    "fields": {"summary": "Fresh", "updated": "2026-02-23T00:00:00.000+0000"}
+                }));
+            });
+        }
+        let _probe = server.mock(|when, then| {
+            when.method(GET)
+                .path("/rest/api/3/search/jql")
+                .query_param_exists("jql");
+            then.status(200).json_body_obj(&serde_json::json!({
+                "isLast": true,
+                "issues": [{"key": "ST-1", "fields": {"updated": "2026-02-23T00:00:00.000+0000"}}]
+            }));
+        });
+
+        let jira = JiraClient::new(server.base_url(), "e".into(), "t".into()).expect("client");
+        let cache = InMemoryCache::with_persistence(
+            Duration::from_secs(30),
+            Duration::from_secs(30),
+            Path::new(":memory:"),
+            Arc::new(Metrics::new()),
+        )
+        .expect("cache");
+        let workspace_ref = |key: &str| IssueRef {
+            key: key.to_string(),
+            updated: None,
+        };
+        cache.upsert_workspace_issues(
+            "default",
+            vec![workspace_ref("ST-2"), workspace_ref("ST-9")],
+        );
+        let workspaces = vec![(
+            "default".to_string(),
+            WorkspaceConfig {
+                jql: "project = ST ORDER BY updated DESC".to_string(),
+                ..WorkspaceConfig::default()
+            },
+        )];
+
+        let result = refresh_issues(
+            &jira,
+            &cache,
+            &workspaces,
+            &SyncOptions::default(),
+            &["ST-1".to_string(), "ST-2".to_string()],
+        );
+
+        assert!(result.errors.is_empty(), "{:?}", result.errors);
+        let keys: Vec<String> = cache
+            .get_workspace_issues_snapshot("default")
+            .expect("listing")
+            .issues
+            .into_iter()
+            .map(|item| item.key)
+            .collect();
+        assert_eq!(keys, vec!["ST-1".to_string(), "ST-9".to_string()]);
+    }
+
+    #[test]
+    fn reconcile_drops_refs_that_left_the_jql() {
+        let server = MockServer::start();
+        let _listing = server.mock(|when, then| {
+            when.method(GET).path("/rest/api/3/search/jql");
+            then.status(200).json_body_obj(&serde_json::json!({
+                "isLast": true,
+                "issues": [{"key": "ST-1", "fields": {"updated": "2026-02-21T00:00:00.000+0000"}}]
+            }));
+        });
+
+        let jira = JiraClient::new(server.base_url(), "e".into(), "t".into()).expect("client");
+        let cache = InMemoryCache::with_persistence(
+            Duration::from_secs(30),
+            Duration::from_secs(30),
+            Path::new(":memory:"),
+            Arc::new(Metrics::new()),
+        )
+        .expect("cache");
+        let listed = |key: &str| IssueRef {
+            key: key.to_string(),
+            updated: None,
+        };
+        cache.upsert_workspace_issues("default", vec![listed("ST-1"), listed("ST-2")]);
+        let row = |key: &str, markdown: &[u8]| IssueCacheRow {
+            key: key.to_string(),
+            markdown: markdown.to_vec(),
+            ..IssueCacheRow::default()
+        };
+        cache.upsert_issues_batch(&[row("ST-1", b"one"), row("ST-2", b"two")]);
+
+        let workspaces = vec![(
+            "default".to_string(),
+            WorkspaceConfig {
+                jql: "project = ST".to_string(),
+                ..WorkspaceConfig::default()
+            },
+        )];
+        let options = SyncOptions {
+            reconcile_interval: Duration::from_secs(3600),
+            ..SyncOptions::default()
+        };
+
+        let result = reconcile_workspaces(&jira, &cache, &workspaces, &options, false);
+        assert_eq!(result.refs_removed, 1);
+        assert_eq!(result.tombstoned, vec!["ST-2".to_string()]);
+        let keys: Vec<_> = cache
+            .get_workspace_issues_snapshot("default")
+            .expect("listing")
+            .issues
+            .into_iter()
+            .map(|item| item.key)
+            .collect();
+        assert_eq!(keys, vec!["ST-1".to_string()]);
+
+        let again = reconcile_workspaces(&jira, &cache, &workspaces, &options, false);
+        assert_eq!(again.workspaces_reconciled, 0);
+    }
 
     #[test]
     fn split_jql_order_by_extracts_order_clause() {
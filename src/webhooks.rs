@@ -0,0 +1,291 @@
+use std::io::Read;
+use std::net::SocketAddr;
+use std::sync::Arc;
+use std::thread::{self, JoinHandle};
+
+use hmac::{Hmac, Mac};
+use serde::Deserialize;
+use sha2::Sha256;
+use tiny_http::{Method, Request, Response, Server};
+
+use crate::cache::InMemoryCache;
+use crate::logging;
+use crate::sync_state::SyncState;
+
+/// Header Jira uses for the HMAC signature of a webhook body.
+pub const SIGNATURE_HEADER: &str = "X-Hub-Signature";
+
+const MAX_BODY_BYTES: u64 = 1024 * 1024;
+
+#[derive(Debug, thiserror::Error)]
+pub enum WebhookError {
+    #[error("failed to bind webhook listener on {addr}: {message}")]
+    Bind { addr: String, message: String },
+    #[error("missing or malformed {SIGNATURE_HEADER} header")]
+    MissingSignature,
+    #[error("webhook signature does not match")]
+    BadSignature,
+    #[error("invalid webhook payload: {0}")]
+    InvalidPayload(String),
+}
+
+/// What a verified webhook asks the daemon to do.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum WebhookEvent {
+    /// Re-fetch the issue; covers creates, edits and comment changes.
+    Refresh(String),
+    /// Drop the issue from listings and caches.
+    Delete(String),
+    /// A verified event type jirafs does not act on.
+    Ignored(String),
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct WebhookPayload {
+    webhook_event: String,
+    issue: Option<WebhookIssue>,
+}
+
+#[derive(Debug, Deserialize)]
+struct WebhookIssue {
+    key: String,
+}
+
+/// Verifies a webhook body against the shared secret and classifies it.
+///
+/// The signature is the `sha256=<hex>` HMAC of the raw body, as sent by Jira
+/// in the `X-Hub-Signature` header.
+///
+/// # Errors
+///
+/// Returns an error when the signature is missing or wrong, or when the body
+/// is not a Jira webhook payload with an issue key.
+pub fn parse_webhook(
+    secret: &str,
+    signature: Option<&str>,
+    body: &[u8],
+) -> Result<WebhookEvent, WebhookError> {
+    verify_signature(secret, signature, body)?;
+
+    let payload: WebhookPayload = serde_json::from_slice(body)
+        .map_err(|err| WebhookError::InvalidPayload(err.to_string()))?;
+    let event = payload.webhook_event;
+    let action = match event.as_str() {
+        "jira:issue_created" | "jira:issue_updated" | "comment_created" | "comment_updated"
+        | "comment_deleted" => WebhookEvent::Refresh,
+        "jira:issue_deleted" => WebhookEvent::Delete,
+        _ => return Ok(WebhookEvent::Ignored(event)),
+    };
+    let key = payload
+        .issue
+        .map(|issue| issue.key.trim().to_ascii_uppercase())
+        .filter(|key| !key.is_empty())
+        .ok_or_else(|| WebhookError::InvalidPayload(format!("{event} event has no issue key")))?;
+    Ok(action(key))
+}
+
+fn verify_signature(
+    secret: &str,
+    signature: Option<&str>,
+    body: &[u8],
+) -> Result<(), WebhookError> {
+    let expected = signature
+        .and_then(|value| value.trim().strip_prefix("sha256="))
+        .and_then(|hex_digest| hex::decode(hex_digest).ok())
+        .ok_or(WebhookError::MissingSignature)?;
+    let mut mac = Hmac::<Sha256>::new_from_slice(secret.as_bytes())
+        .map_err(|_| WebhookError::BadSignature)?;
+    mac.update(body);
+    mac.verify_slice(&expected)
+        .map_err(|_| WebhookError::BadSignature)
+}
+
+/// Starts the webhook listener on its own thread.
+///
+/// Refresh events are queued on the issue refresher; deletes tombstone the
+/// issue immediately. Returns the bound address, which differs from `listen`
+/// when binding to port 0.
+///
+/// # Errors
+///
+/// Returns an error when the listener cannot bind.
+pub fn spawn(
+    listen: &str,
+    secret: String,
+    sync_state: Arc<SyncState>,
+    cache: Arc<InMemoryCache>,
+) -> Result<(SocketAddr, JoinHandle<()>), WebhookError> {
+    let server = Server::http(listen).map_err(|err| WebhookError::Bind {
+        addr: listen.to_string(),
+        message: err.to_string(),
+    })?;
+    let addr = server
+        .server_addr()
+        .to_ip()
+        .ok_or_else(|| WebhookError::Bind {
+            addr: listen.to_string(),
+            message: "listener is not bound to an IP address".into(),
+        })?;
+    logging::info(format!("webhook listener ready on {addr}"));
+
+    let handle = thread::spawn(move || {
+        for mut request in server.incoming_requests() {
+            let status = handle_request(&mut request, &secret, &sync_state, &cache);
+            if let Err(err) = request.respond(Response::empty(status)) {
+                logging::debug(format!("failed to answer webhook request: {err}"));
+            }
+        }
+    });
+
+    Ok((addr, handle))
+}
+
+fn handle_request(
+    request: &mut Request,
+    secret: &str,
+    sync_state: &SyncState,
+    cache: &InMemoryCache,
+) -> u16 {
+    if *request.method() != Method::Post {
+        return 405;
+    }
+    let signature = request
+        .headers()
+        .iter()
+        .find(|header| header.field.equiv(SIGNATURE_HEADER))
+        .map(|header| header.value.as_str().to_string());
+    let mut body = Vec::new();
+    let mut reader = request.as_reader().take(MAX_BODY_BYTES + 1);
+    if let Err(err) = reader.read_to_end(&mut body) {
+        logging::warn(format!("failed to read webhook body: {err}"));
+        return 400;
+    }
+    if body.len() as u64 > MAX_BODY_BYTES {
+        return 413;
+    }
+    handle_body(secret, signature.as_deref(), &body, sync_state, cache)
+}
+
+fn handle_body(
+    secret: &str,
+    signature: Option<&str>,
+    body: &[u8],
+    sync_state: &SyncState,
+    cache: &InMemoryCache,
+) -> u16 {
+    match parse_webhook(secret, signature, body) {
+        Ok(WebhookEvent::Refresh(key)) => {
+            logging::debug(format!("webhook queued refresh for {key}"));
+            sync_state.request_issue_refresh([key]);
+            204
+        }
+        Ok(WebhookEvent::Delete(key)) => {
+            logging::info(format!("webhook removed deleted issue {key}"));
+            cache.tombstone_issues(&[key]);
+            204
+        }
+        Ok(WebhookEvent::Ignored(event)) => {
+            logging::debug(format!("ignoring webhook event {event}"));
+            202
+        }
+        Err(err @ (WebhookError::MissingSignature | WebhookError::BadSignature)) => {
+            logging::warn(format!("rejected webhook: {err}"));
+            401
+        }
+        Err(err) => {
+            logging::warn(format!("rejected webhook: {err}"));
+            400
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Write;
+    use std::net::TcpStream;
+    use std::time::Duration;
+
+    const SECRET: &str = "shared-secret";
+    const UPDATED: &str = r#"{"timestamp":1771622400000,"webhookEvent":"jira:issue_updated","issue_event_type_name":"issue_generic","issue":{"id":"10001","key":"PROJ-7","fields":{"summary":"Recorded"}}}"#;
+
+    fn sign(body: &str) -> String {
+        let mut mac = Hmac::<Sha256>::new_from_slice(SECRET.as_bytes()).expect("hmac key");
+        mac.update(body.as_bytes());
+        format!("sha256={}", hex::encode(mac.finalize().into_bytes()))
+    }
+
+    fn post(addr: SocketAddr, body: &str, signature: &str) -> String {
+        let mut stream = TcpStream::connect(addr).expect("connect");
+        stream
+            .set_read_timeout(Some(Duration::from_secs(5)))
+            .expect("timeout");
+        write!(
+            stream,
+            "POST /jira HTTP/1.1\r\nHost: {addr}\r\nContent-Type: application/json\r\n{SIGNATURE_HEADER}: {signature}\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{body}",
+            body.len()
+        )
+        .expect("write request");
+        let mut response = String::new();
+        stream.read_to_string(&mut response).expect("read response");
+        response
+    }
+
+    #[test]
+    fn verifies_signature_and_classifies_events() {
+        assert_eq!(
+            parse_webhook(SECRET, Some(&sign(UPDATED)), UPDATED.as_bytes()).expect("valid"),
+            WebhookEvent::Refresh("PROJ-7".into())
+        );
+
+        let deleted = UPDATED.replace("jira:issue_updated", "jira:issue_deleted");
+        assert_eq!(
+            parse_webhook(SECRET, Some(&sign(&deleted)), deleted.as_bytes()).expect("valid"),
+            WebhookEvent::Delete("PROJ-7".into())
+        );
+
+        let sprint = r#"{"webhookEvent":"sprint_started"}"#;
+        assert_eq!(
+            parse_webhook(SECRET, Some(&sign(sprint)), sprint.as_bytes()).expect("valid"),
+            WebhookEvent::Ignored("sprint_started".into())
+        );
+
+        assert!(matches!(
+            parse_webhook(SECRET, Some(&sign(UPDATED)), deleted.as_bytes()),
+            Err(WebhookError::BadSignature)
+        ));
+        assert!(matches!(
+            parse_webhook(SECRET, None, UPDATED.as_bytes()),
+            Err(WebhookError::MissingSignature)
+        ));
+    }
+
+    #[test]
+    fn listener_queues_refresh_for_signed_posts_only() {
+        let sync_state = Arc::new(SyncState::new(Duration::from_secs(60)));
+        let cache = Arc::new(InMemoryCache::new(
+            Duration::from_secs(60),
+            Duration::from_secs(60),
+            Arc::new(crate::metrics::Metrics::new()),
+        ));
+        let (addr, _handle) = spawn(
+            "127.0.0.1:0",
+            SECRET.to_string(),
+            Arc::clone(&sync_state),
+            cache,
+        )
+        .expect("listener should bind");
+
+        let rejected = post(addr, UPDATED, "sha256=00");
+        assert!(rejected.starts_with("HTTP/1.1 401"), "{rejected}");
+        assert!(sync_state.pending_issue_refreshes().is_empty());
+
+        let accepted = post(addr, UPDATED, &sign(UPDATED));
+        assert!(accepted.starts_with("HTTP/1.1 204"), "{accepted}");
+        assert_eq!(
+            sync_state.pending_issue_refreshes(),
+            vec!["PROJ-7".to_string()]
+        );
+    }
+}